@@ -0,0 +1,11 @@
+-- Track worker liveness so jobs abandoned by a crashed worker can be requeued.
+
+ALTER TABLE public.processing_data
+    ADD COLUMN heartbeat_at timestamp with time zone;
+
+ALTER TABLE public.processing_data
+    ADD COLUMN attempts integer DEFAULT 0 NOT NULL;
+
+CREATE INDEX idx_processing_data_indexing_heartbeat
+ON public.processing_data USING btree (heartbeat_at)
+WHERE (status = 'indexing');
@@ -0,0 +1,4 @@
+-- Support keyset pagination of a tracked address' transactions, newest first.
+
+CREATE INDEX idx_transactions_owner_block_time_signature
+ON public.transactions USING btree (owner_address, block_time DESC, signature DESC);
@@ -0,0 +1,4 @@
+-- Support keyset pagination of a tracked address' token transfers, newest first.
+
+CREATE INDEX idx_token_transfers_owner_block_time_id
+ON public.token_transfers USING btree (tracked_owner, block_time DESC, id DESC);
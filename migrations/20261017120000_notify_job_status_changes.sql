@@ -0,0 +1,14 @@
+-- Publish job status transitions on the job_events channel so API processes can stream them.
+
+CREATE OR REPLACE FUNCTION public.notify_job_status_change() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify('job_events', NEW.id::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER processing_data_status_notify
+AFTER UPDATE OF status ON public.processing_data
+FOR EACH ROW
+WHEN (OLD.status IS DISTINCT FROM NEW.status)
+EXECUTE FUNCTION public.notify_job_status_change();
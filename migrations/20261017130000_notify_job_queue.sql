@@ -0,0 +1,14 @@
+-- Wake idle workers whenever a job becomes claimable: new jobs, retries, and requeues.
+
+CREATE OR REPLACE FUNCTION public.notify_job_queued() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify('job_queue', NEW.id::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER processing_data_queued_notify
+AFTER INSERT OR UPDATE OF status ON public.processing_data
+FOR EACH ROW
+WHEN (NEW.status = 'pending')
+EXECUTE FUNCTION public.notify_job_queued();
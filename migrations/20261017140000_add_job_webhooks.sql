@@ -0,0 +1,26 @@
+-- Optional completion callbacks and their persisted delivery attempts.
+
+ALTER TABLE public.processing_data
+    ADD COLUMN callback_url text;
+
+CREATE TABLE public.webhook_deliveries (
+    id bigserial PRIMARY KEY,
+    job_id bigint NOT NULL,
+    callback_url text NOT NULL,
+    payload jsonb NOT NULL,
+    status text DEFAULT 'pending' NOT NULL,
+    attempts integer DEFAULT 0 NOT NULL,
+    next_attempt_at timestamp with time zone DEFAULT now() NOT NULL,
+    last_status_code integer,
+    last_error text,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    delivered_at timestamp with time zone
+);
+
+CREATE INDEX idx_webhook_deliveries_due
+ON public.webhook_deliveries USING btree (next_attempt_at)
+WHERE (status = 'pending');
+
+CREATE INDEX idx_webhook_deliveries_job_id
+ON public.webhook_deliveries USING btree (job_id);
@@ -0,0 +1,29 @@
+-- API keys are stored as SHA-256 hashes; the plaintext is only shown once at creation.
+
+CREATE TABLE public.api_keys (
+    id bigserial PRIMARY KEY,
+    name text NOT NULL,
+    key_hash text NOT NULL,
+    max_concurrent_jobs integer NOT NULL,
+    max_tx_limit smallint NOT NULL,
+    max_requested_hours smallint NOT NULL,
+    requests_per_minute integer NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    revoked_at timestamp with time zone,
+    CONSTRAINT api_keys_limits_positive CHECK (
+        max_concurrent_jobs > 0
+        AND max_tx_limit > 0
+        AND max_requested_hours > 0
+        AND requests_per_minute > 0
+    )
+);
+
+CREATE UNIQUE INDEX idx_api_keys_key_hash ON public.api_keys USING btree (key_hash);
+CREATE UNIQUE INDEX idx_api_keys_name ON public.api_keys USING btree (name);
+
+ALTER TABLE public.processing_data
+    ADD COLUMN api_key_id bigint REFERENCES public.api_keys (id);
+
+CREATE INDEX idx_processing_data_api_key_active
+ON public.processing_data USING btree (api_key_id)
+WHERE (status IN ('pending', 'indexing', 'cancelling'));
@@ -0,0 +1,16 @@
+-- Priority tiers and fair-share claiming across submitters.
+
+ALTER TABLE public.processing_data
+    ADD COLUMN priority smallint DEFAULT 0 NOT NULL,
+    ADD COLUMN claimed_at timestamp with time zone,
+    ADD CONSTRAINT processing_data_priority_non_negative CHECK (priority >= 0);
+
+ALTER TABLE public.api_keys
+    ADD COLUMN max_priority smallint DEFAULT 0 NOT NULL;
+
+CREATE INDEX idx_processing_data_pending_priority
+ON public.processing_data USING btree (priority DESC, created_at)
+WHERE (status = 'pending');
+
+CREATE INDEX idx_processing_data_submitter_claimed_at
+ON public.processing_data USING btree ((COALESCE(api_key_id, 0)), claimed_at DESC);
@@ -0,0 +1,14 @@
+-- Groups jobs submitted together through POST /analyze/batch.
+
+CREATE TABLE public.job_batches (
+    id bigserial PRIMARY KEY,
+    api_key_id bigint REFERENCES public.api_keys (id),
+    created_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+ALTER TABLE public.processing_data
+    ADD COLUMN batch_id bigint REFERENCES public.job_batches (id);
+
+CREATE INDEX idx_processing_data_batch_id
+ON public.processing_data USING btree (batch_id)
+WHERE (batch_id IS NOT NULL);
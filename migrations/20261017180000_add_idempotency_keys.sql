@@ -0,0 +1,13 @@
+-- Maps a client-supplied Idempotency-Key to the job it created or reused.
+
+CREATE TABLE public.idempotency_keys (
+    api_key_id bigint NOT NULL REFERENCES public.api_keys (id),
+    key text NOT NULL,
+    request_fingerprint text NOT NULL,
+    job_id bigint NOT NULL REFERENCES public.processing_data (id),
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    PRIMARY KEY (api_key_id, key)
+);
+
+CREATE INDEX idx_idempotency_keys_created_at
+ON public.idempotency_keys USING btree (created_at);
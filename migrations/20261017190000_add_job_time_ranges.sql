@@ -0,0 +1,15 @@
+-- Absolute time ranges for jobs. `range_start` is fixed when the job is created, and
+-- `range_end` is NULL for relative (`requested_hours`) jobs, which end at `created_at`.
+
+ALTER TABLE public.processing_data
+    ADD COLUMN range_start timestamp with time zone,
+    ADD COLUMN range_end timestamp with time zone;
+
+UPDATE public.processing_data
+SET range_start = created_at - requested_hours * INTERVAL '1 hour';
+
+ALTER TABLE public.processing_data
+    ALTER COLUMN range_start SET NOT NULL,
+    ALTER COLUMN requested_hours DROP NOT NULL,
+    ADD CONSTRAINT processing_data_range_ordered CHECK (range_end IS NULL OR range_end > range_start),
+    ADD CONSTRAINT processing_data_range_or_hours CHECK ((requested_hours IS NULL) <> (range_end IS NULL));
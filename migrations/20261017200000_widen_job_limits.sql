@@ -0,0 +1,16 @@
+-- Widen job limits past smallint and allow unlimited, full-history jobs. A NULL
+-- `tx_limit` means no transaction cap; a job with neither `requested_hours` nor
+-- `range_end` indexes the address's entire history.
+
+ALTER TABLE public.processing_data
+    DROP CONSTRAINT processing_data_range_or_hours,
+    ALTER COLUMN tx_limit TYPE integer,
+    ALTER COLUMN tx_limit DROP NOT NULL,
+    ALTER COLUMN requested_hours TYPE integer,
+    ADD CONSTRAINT processing_data_hours_without_range_end CHECK (requested_hours IS NULL OR range_end IS NULL),
+    ADD CONSTRAINT processing_data_tx_limit_positive CHECK (tx_limit IS NULL OR tx_limit > 0);
+
+ALTER TABLE public.api_keys
+    ALTER COLUMN max_tx_limit TYPE integer,
+    ALTER COLUMN max_requested_hours TYPE integer,
+    ADD COLUMN allow_full_history boolean DEFAULT false NOT NULL;
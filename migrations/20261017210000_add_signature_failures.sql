@@ -0,0 +1,16 @@
+-- Record per-signature transaction fetch failures. Failed signatures are retried with
+-- exponential backoff via `next_attempt_at` and dead-lettered (`is_dead`) once they run
+-- out of attempts, so they no longer block their jobs from finishing.
+
+ALTER TABLE public.signatures
+    ADD COLUMN attempts integer DEFAULT 0 NOT NULL,
+    ADD COLUMN last_status_code integer,
+    ADD COLUMN last_rpc_code bigint,
+    ADD COLUMN last_error text,
+    ADD COLUMN last_attempt_at timestamp with time zone,
+    ADD COLUMN next_attempt_at timestamp with time zone,
+    ADD COLUMN is_dead boolean DEFAULT false NOT NULL;
+
+CREATE INDEX idx_signatures_dead
+    ON public.signatures (owner_address, block_time)
+    WHERE is_dead = TRUE;
@@ -0,0 +1,10 @@
+-- Keeps the raw getTransaction result per signature so transactions and token transfers
+-- can be rebuilt offline when the parser changes. Large payloads are compressed by TOAST.
+
+CREATE TABLE public.raw_transactions (
+    signature text PRIMARY KEY,
+    slot bigint NOT NULL,
+    block_time bigint,
+    payload jsonb NOT NULL,
+    fetched_at timestamp with time zone DEFAULT now() NOT NULL
+);
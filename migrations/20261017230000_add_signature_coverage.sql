@@ -0,0 +1,32 @@
+-- The contiguous span of each address's signature history that has been fetched.
+-- Incremental jobs walk newer than newest_signature and older than oldest_signature, and
+-- the span only grows when a walk joins onto it, so a walk cut short by txLimit or the
+-- window start never leaves an unfetched gap inside it.
+
+CREATE TABLE public.signature_coverage (
+    owner_address text PRIMARY KEY,
+    newest_signature text NOT NULL,
+    oldest_signature text NOT NULL,
+    oldest_block_time bigint NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+INSERT INTO public.signature_coverage (
+    owner_address,
+    newest_signature,
+    oldest_signature,
+    oldest_block_time
+)
+SELECT newest.owner_address, newest.signature, oldest.signature, oldest.block_time
+FROM (
+    SELECT DISTINCT ON (owner_address) owner_address, signature
+    FROM public.signatures
+    WHERE block_time IS NOT NULL
+    ORDER BY owner_address, block_time DESC, signature DESC
+) newest
+JOIN (
+    SELECT DISTINCT ON (owner_address) owner_address, signature, block_time
+    FROM public.signatures
+    WHERE block_time IS NOT NULL
+    ORDER BY owner_address, block_time ASC, signature ASC
+) oldest USING (owner_address);
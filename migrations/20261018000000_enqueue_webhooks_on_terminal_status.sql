@@ -0,0 +1,50 @@
+-- Queue the completion webhook in the same statement that finishes the job, so every path
+-- to a terminal status (workers, the lease reaper, manual updates) gets a callback.
+
+CREATE OR REPLACE FUNCTION public.enqueue_job_webhook() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO public.webhook_deliveries (job_id, callback_url, payload)
+    SELECT
+        NEW.id,
+        NEW.callback_url,
+        jsonb_build_object(
+            'job_id', NEW.id,
+            'address', NEW.address,
+            'status', NEW.status,
+            'requested_hours', NEW.requested_hours,
+            'range_start', NEW.range_start,
+            'range_end', NEW.range_end,
+            'tx_limit', NEW.tx_limit,
+            'attempts', NEW.attempts,
+            'total_transactions', progress.total_transactions,
+            'processed_transactions', progress.processed_transactions,
+            'dead_transactions', progress.dead_transactions,
+            'created_at', NEW.created_at,
+            'finished_at', NEW.updated_at
+        )
+    FROM (
+        SELECT
+            COUNT(*)::bigint AS total_transactions,
+            COUNT(*) FILTER (WHERE s.is_processed = TRUE)::bigint AS processed_transactions,
+            COUNT(*) FILTER (
+                WHERE s.is_processed = FALSE AND s.is_dead = TRUE
+            )::bigint AS dead_transactions
+        FROM public.signatures s
+        WHERE s.owner_address = NEW.address
+          AND s.block_time >= EXTRACT(EPOCH FROM NEW.range_start)::bigint
+          AND s.block_time <= EXTRACT(EPOCH FROM COALESCE(NEW.range_end, NEW.created_at))::bigint
+    ) progress;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER processing_data_webhook_enqueue
+AFTER UPDATE OF status ON public.processing_data
+FOR EACH ROW
+WHEN (
+    OLD.status IS DISTINCT FROM NEW.status
+    AND NEW.status IN ('ready', 'error')
+    AND NEW.callback_url IS NOT NULL
+)
+EXECUTE FUNCTION public.enqueue_job_webhook();
@@ -0,0 +1,51 @@
+-- Store each job's window as unix-second bounds so queries compare `block_time` against
+-- columns instead of repeating the timestamp conversion.
+
+-- Epoch seconds do not depend on the session time zone, so this is safe to mark IMMUTABLE
+-- even though EXTRACT on timestamptz is only STABLE.
+CREATE OR REPLACE FUNCTION public.epoch_seconds(ts timestamp with time zone) RETURNS bigint
+LANGUAGE sql IMMUTABLE PARALLEL SAFE
+AS $$ SELECT EXTRACT(EPOCH FROM ts)::bigint $$;
+
+ALTER TABLE public.processing_data
+    ADD COLUMN range_start_ts bigint
+        GENERATED ALWAYS AS (public.epoch_seconds(range_start)) STORED,
+    ADD COLUMN range_end_ts bigint
+        GENERATED ALWAYS AS (public.epoch_seconds(COALESCE(range_end, created_at))) STORED;
+
+CREATE OR REPLACE FUNCTION public.enqueue_job_webhook() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO public.webhook_deliveries (job_id, callback_url, payload)
+    SELECT
+        NEW.id,
+        NEW.callback_url,
+        jsonb_build_object(
+            'job_id', NEW.id,
+            'address', NEW.address,
+            'status', NEW.status,
+            'requested_hours', NEW.requested_hours,
+            'range_start', NEW.range_start,
+            'range_end', NEW.range_end,
+            'tx_limit', NEW.tx_limit,
+            'attempts', NEW.attempts,
+            'total_transactions', progress.total_transactions,
+            'processed_transactions', progress.processed_transactions,
+            'dead_transactions', progress.dead_transactions,
+            'created_at', NEW.created_at,
+            'finished_at', NEW.updated_at
+        )
+    FROM (
+        SELECT
+            COUNT(*)::bigint AS total_transactions,
+            COUNT(*) FILTER (WHERE s.is_processed = TRUE)::bigint AS processed_transactions,
+            COUNT(*) FILTER (
+                WHERE s.is_processed = FALSE AND s.is_dead = TRUE
+            )::bigint AS dead_transactions
+        FROM public.signatures s
+        WHERE s.owner_address = NEW.address
+          AND s.block_time BETWEEN NEW.range_start_ts AND NEW.range_end_ts
+    ) progress;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
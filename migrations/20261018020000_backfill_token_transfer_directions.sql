@@ -0,0 +1,41 @@
+-- One-off backfill of transfer directions for rows stored before classification existed.
+
+-- Rows re-saved after classification was introduced already have a classified twin;
+-- updating the stale copy would violate the natural unique index.
+DELETE FROM public.token_transfers stale
+WHERE stale.direction = 'unknown'
+  AND EXISTS (
+      SELECT 1
+      FROM public.token_transfers classified
+      WHERE classified.direction <> 'unknown'
+        AND classified.tracked_owner = stale.tracked_owner
+        AND classified.signature = stale.signature
+        AND (
+            classified.source_owner, classified.destination_owner,
+            classified.source_token_account, classified.destination_token_account,
+            classified.token_mint, classified.token_program, classified.amount_raw,
+            classified.amount_ui, classified.decimals, classified.asset_type,
+            classified.transfer_type, classified.instruction_idx, classified.inner_idx,
+            classified.authority, classified.slot, classified.block_time
+        ) IS NOT DISTINCT FROM (
+            stale.source_owner, stale.destination_owner,
+            stale.source_token_account, stale.destination_token_account,
+            stale.token_mint, stale.token_program, stale.amount_raw,
+            stale.amount_ui, stale.decimals, stale.asset_type,
+            stale.transfer_type, stale.instruction_idx, stale.inner_idx,
+            stale.authority, stale.slot, stale.block_time
+        )
+  );
+
+UPDATE public.token_transfers
+SET direction = CASE
+    WHEN (source_owner = tracked_owner OR source_token_account = tracked_owner)
+     AND (destination_owner = tracked_owner OR destination_token_account = tracked_owner)
+        THEN 'self'
+    WHEN source_owner = tracked_owner OR source_token_account = tracked_owner
+        THEN 'out'
+    WHEN destination_owner = tracked_owner OR destination_token_account = tracked_owner
+        THEN 'in'
+    ELSE 'external'
+END
+WHERE direction = 'unknown';
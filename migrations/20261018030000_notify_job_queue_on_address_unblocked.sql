@@ -0,0 +1,25 @@
+-- A pending job waits while another job for the same address is indexing or cancelling.
+-- Wake idle workers once that job finishes so the waiting job is not left to the fallback poll.
+
+CREATE OR REPLACE FUNCTION public.notify_job_queue_address_unblocked() RETURNS trigger AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1
+        FROM public.processing_data
+        WHERE address = NEW.address
+          AND status = 'pending'
+    ) THEN
+        PERFORM pg_notify('job_queue', NEW.id::text);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER processing_data_unblocked_notify
+AFTER UPDATE OF status ON public.processing_data
+FOR EACH ROW
+WHEN (
+    OLD.status IN ('indexing', 'cancelling')
+    AND NEW.status NOT IN ('indexing', 'cancelling')
+)
+EXECUTE FUNCTION public.notify_job_queue_address_unblocked();
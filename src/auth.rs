@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use axum::{
+    Json,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use governor::{
+    Quota, RateLimiter,
+    clock::{Clock, DefaultClock},
+    middleware::NoOpMiddleware,
+    state::{InMemoryState, NotKeyed},
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::AppState;
+use crate::metrics;
+use crate::types::ApiKey;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+pub const API_KEY_PREFIX: &str = "oei_";
+
+type KeyRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>;
+
+pub fn generate_api_key() -> String {
+    format!(
+        "{API_KEY_PREFIX}{}",
+        hex::encode(rand::random::<[u8; 32]>())
+    )
+}
+
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn presented_api_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get(API_KEY_HEADER) {
+        return key.to_str().ok().filter(|key| !key.is_empty());
+    }
+
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// One `governor` limiter per API key, rebuilt whenever the key's
+/// `requests_per_minute` changes.
+#[derive(Debug, Default)]
+pub struct ApiKeyRateLimiters {
+    limiters: Mutex<HashMap<i64, (i32, Arc<KeyRateLimiter>)>>,
+}
+
+impl ApiKeyRateLimiters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns how long the caller should wait when the key is over its quota.
+    pub fn check(&self, api_key: &ApiKey) -> Result<(), Duration> {
+        let requests_per_minute = api_key.limits.requests_per_minute;
+        let limiter = {
+            let mut limiters = self.limiters.lock().unwrap_or_else(PoisonError::into_inner);
+            match limiters.get(&api_key.id) {
+                Some((quota, limiter)) if *quota == requests_per_minute => Arc::clone(limiter),
+                _ => {
+                    let per_minute = u32::try_from(requests_per_minute)
+                        .ok()
+                        .and_then(NonZeroU32::new)
+                        .unwrap_or(NonZeroU32::MIN);
+                    let limiter = Arc::new(RateLimiter::direct(Quota::per_minute(per_minute)));
+                    limiters.insert(api_key.id, (requests_per_minute, Arc::clone(&limiter)));
+                    limiter
+                }
+            }
+        };
+
+        limiter
+            .check()
+            .map_err(|not_until| not_until.wait_time_from(DefaultClock::default().now()))
+    }
+}
+
+fn rejection(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+pub async fn require_api_key(
+    State(app_state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(key_hash) = presented_api_key(request.headers()).map(hash_api_key) else {
+        metrics::record_auth_rejection("missing");
+        warn!(path = %request.uri().path(), "Rejected request without API key");
+        return rejection(StatusCode::UNAUTHORIZED, "Missing API key");
+    };
+
+    let api_key = match app_state.database.find_active_api_key(&key_hash).await {
+        Ok(Some(api_key)) => api_key,
+        Ok(None) => {
+            metrics::record_auth_rejection("invalid");
+            warn!(path = %request.uri().path(), "Rejected request with invalid API key");
+            return rejection(StatusCode::UNAUTHORIZED, "Invalid API key");
+        }
+        Err(e) => {
+            warn!(%e, "Failed to look up API key");
+            return rejection(StatusCode::INTERNAL_SERVER_ERROR, "internal error");
+        }
+    };
+
+    if let Err(retry_after) = app_state.api_key_rate_limiters.check(&api_key) {
+        metrics::record_auth_rejection("rate_limited");
+        warn!(
+            api_key_id = api_key.id,
+            retry_after_ms = retry_after.as_millis(),
+            "Rejected request over API key rate limit"
+        );
+        let mut response = rejection(StatusCode::TOO_MANY_REQUESTS, "API key rate limit exceeded");
+        let retry_after_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+        return response;
+    }
+
+    request.extensions_mut().insert(api_key);
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ApiKeyLimits;
+    use pretty_assertions::assert_eq;
+
+    fn api_key(id: i64, requests_per_minute: i32) -> ApiKey {
+        ApiKey {
+            id,
+            name: format!("key-{id}"),
+            limits: ApiKeyLimits {
+                max_concurrent_jobs: 1,
+                max_tx_limit: 1000,
+                max_requested_hours: 24,
+                max_priority: 0,
+                requests_per_minute,
+                allow_full_history: false,
+            },
+        }
+    }
+
+    #[test]
+    fn should_hash_generated_key_to_stable_hex_digest() {
+        let key = generate_api_key();
+
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(hash_api_key(&key), hash_api_key(&key));
+        assert_eq!(hash_api_key(&key).len(), 64);
+        assert_ne!(hash_api_key(&key), hash_api_key(&generate_api_key()));
+    }
+
+    #[test]
+    fn should_read_api_key_from_header_or_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(presented_api_key(&headers), None);
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer oei_abc"),
+        );
+        assert_eq!(presented_api_key(&headers), Some("oei_abc"));
+
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("oei_def"));
+        assert_eq!(presented_api_key(&headers), Some("oei_def"));
+
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static(""));
+        assert_eq!(presented_api_key(&headers), None);
+    }
+
+    #[test]
+    fn should_limit_each_key_independently_when_quota_is_spent() {
+        let limiters = ApiKeyRateLimiters::new();
+        let limited = api_key(1, 2);
+        let other = api_key(2, 2);
+
+        assert!(limiters.check(&limited).is_ok());
+        assert!(limiters.check(&limited).is_ok());
+        let retry_after = limiters.check(&limited).unwrap_err();
+        assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(30));
+
+        assert!(limiters.check(&other).is_ok());
+        assert!(limiters.check(&api_key(1, 5)).is_ok());
+    }
+}
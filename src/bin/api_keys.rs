@@ -0,0 +1,82 @@
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand};
+use on_chain_event_indexer::auth::{generate_api_key, hash_api_key};
+use on_chain_event_indexer::db::Database;
+use on_chain_event_indexer::settings::Settings;
+use on_chain_event_indexer::types::ApiKeyLimits;
+
+#[derive(Parser)]
+#[command(about = "Manage API keys for the indexer HTTP API")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a key and print it once; only its hash is stored.
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value_t = 2)]
+        max_concurrent_jobs: i32,
+        #[arg(long, default_value_t = 1000)]
+        max_tx_limit: i32,
+        #[arg(long, default_value_t = 24)]
+        max_requested_hours: i32,
+        #[arg(long, default_value_t = 0)]
+        max_priority: i16,
+        #[arg(long, default_value_t = 60)]
+        requests_per_minute: i32,
+        /// Allow jobs without a transaction cap or time window.
+        #[arg(long)]
+        allow_full_history: bool,
+    },
+    /// Revoke an active key by name.
+    Revoke {
+        #[arg(long)]
+        name: String,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let settings = Settings::load()?;
+    let database = Database::new(settings.database.url, 1).await?;
+    database.migrate().await?;
+
+    match cli.command {
+        Command::Create {
+            name,
+            max_concurrent_jobs,
+            max_tx_limit,
+            max_requested_hours,
+            max_priority,
+            requests_per_minute,
+            allow_full_history,
+        } => {
+            let key = generate_api_key();
+            let limits = ApiKeyLimits {
+                max_concurrent_jobs,
+                max_tx_limit,
+                max_requested_hours,
+                max_priority,
+                requests_per_minute,
+                allow_full_history,
+            };
+            let id = database
+                .create_api_key(&name, &hash_api_key(&key), limits)
+                .await?;
+            println!("Created API key {name} (id {id}): {key}");
+        }
+        Command::Revoke { name } => {
+            if database.revoke_api_key(&name).await? == 0 {
+                bail!("no active API key named {name}");
+            }
+            println!("Revoked API key {name}");
+        }
+    }
+
+    Ok(())
+}
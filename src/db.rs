@@ -1,28 +1,51 @@
+pub mod api_keys;
+pub mod batches;
+pub mod charts;
 pub mod jobs;
+pub mod raw_transactions;
 pub mod signatures;
 pub mod transactions;
+pub mod webhooks;
 
+use api_keys::ApiKeys;
+use batches::Batches;
+use charts::Charts;
 use jobs::Jobs;
+use raw_transactions::RawTransactions;
 use signatures::Signatures;
 use transactions::Transactions;
+use webhooks::Webhooks;
 
 use crate::backoff::WorkerBackoff;
-use crate::requests::{RpcResponse, TransactionResult};
-use crate::types::{ClaimedJob, JobInfo, SaveStats};
+use crate::metrics::{self, observe_db_query};
+use crate::requests::{RpcResponse, TransactionFetchError, TransactionResult};
+use crate::types::{
+    ApiKey, ApiKeyLimits, BatchInfo, ChartBucket, ClaimedJob, ClaimedWebhookDelivery, CreatedBatch,
+    JobCharts, JobDeadSignatures, JobDedup, JobInfo, JobSubmission, JobTransactions, JobTransfers,
+    JobTransition, JobWebhooks, NewJob, RawTransaction, ReapedJob, SaveStats, SignatureCoverage,
+    SignatureFailure, TransactionFilter, TransferFilter,
+};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, ensure};
 use sqlx::PgPool;
 use sqlx::migrate::Migrator;
-use sqlx::postgres::PgPoolOptions;
-use std::time::Instant;
+use sqlx::postgres::{PgListener, PgPoolOptions};
+use std::time::{Duration, Instant};
 use tracing::{error, info, instrument, warn};
 
 pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+pub const JOB_EVENTS_CHANNEL: &str = "job_events";
+pub const JOB_QUEUE_CHANNEL: &str = "job_queue";
 
 pub struct Database {
+    api_keys: ApiKeys,
+    batches: Batches,
+    charts: Charts,
     jobs: Jobs,
+    raw_transactions: RawTransactions,
     signatures: Signatures,
     transactions: Transactions,
+    webhooks: Webhooks,
     pool: PgPool,
 }
 
@@ -81,9 +104,14 @@ impl Database {
     #[instrument(skip(pool))]
     pub fn from_pool(pool: PgPool) -> Self {
         Self {
+            api_keys: ApiKeys::new(pool.clone()),
+            batches: Batches::new(pool.clone()),
+            charts: Charts::new(pool.clone()),
             jobs: Jobs::new(pool.clone()),
+            raw_transactions: RawTransactions::new(pool.clone()),
             signatures: Signatures::new(pool.clone()),
             transactions: Transactions::new(pool.clone()),
+            webhooks: Webhooks::new(pool.clone()),
             pool,
         }
     }
@@ -96,8 +124,137 @@ impl Database {
         Ok(())
     }
 
+    /// Fails unless every migration shipped with this binary has been applied, for
+    /// commands that must not change the schema themselves.
+    pub async fn ensure_migrated(&self) -> Result<()> {
+        let applied = sqlx::query_scalar::<_, i64>(
+            "SELECT version FROM _sqlx_migrations WHERE success = TRUE",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("failed to read applied migrations; run `migrate` first")?;
+
+        let pending = MIGRATOR
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .filter(|migration| !applied.contains(&migration.version))
+            .map(|migration| migration.version)
+            .collect::<Vec<_>>();
+        ensure!(
+            pending.is_empty(),
+            "database schema is behind by {} migration(s), starting at {}; run `migrate` first",
+            pending.len(),
+            pending.first().copied().unwrap_or_default()
+        );
+
+        Ok(())
+    }
+
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn job_events_listener(&self) -> Result<PgListener> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener
+            .listen_all([JOB_EVENTS_CHANNEL, JOB_QUEUE_CHANNEL])
+            .await?;
+        Ok(listener)
+    }
+
+    pub async fn notify_job_progress(&self, job_id: i64) -> Result<()> {
+        observe_db_query(
+            "jobs",
+            "notify_job_progress",
+            self.jobs.notify_job_progress(job_id),
+        )
+        .await
+    }
+
     pub async fn claim_pending_job(&self, worker_id: u32) -> Result<Option<ClaimedJob>> {
-        self.jobs.claim_pending_job(worker_id).await
+        let claimed_job = observe_db_query(
+            "jobs",
+            "claim_pending_job",
+            self.jobs.claim_pending_job(worker_id),
+        )
+        .await?;
+        if claimed_job.is_some() {
+            metrics::record_job_claimed();
+        }
+
+        Ok(claimed_job)
+    }
+
+    pub async fn record_heartbeat(&self, job_id: i64, worker_id: u32) -> Result<u64> {
+        observe_db_query(
+            "jobs",
+            "record_heartbeat",
+            self.jobs.record_heartbeat(job_id, worker_id),
+        )
+        .await
+    }
+
+    pub async fn release_job(&self, job_id: i64, worker_id: u32) -> Result<u64> {
+        observe_db_query(
+            "jobs",
+            "release_job",
+            self.jobs.release_job(job_id, worker_id),
+        )
+        .await
+    }
+
+    pub async fn cancel_job(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+    ) -> Result<Option<JobTransition>> {
+        observe_db_query(
+            "jobs",
+            "cancel_job",
+            self.jobs.cancel_job(job_id, api_key_id),
+        )
+        .await
+    }
+
+    pub async fn retry_job(
+        &self,
+        job_id: i64,
+        api_key: Option<&ApiKey>,
+    ) -> Result<Option<JobTransition>> {
+        observe_db_query("jobs", "retry_job", self.jobs.retry_job(job_id, api_key)).await
+    }
+
+    pub async fn is_cancellation_requested(&self, job_id: i64) -> Result<bool> {
+        observe_db_query(
+            "jobs",
+            "is_cancellation_requested",
+            self.jobs.is_cancellation_requested(job_id),
+        )
+        .await
+    }
+
+    pub async fn finish_cancelled_job(&self, job_id: i64, worker_id: u32) -> Result<u64> {
+        observe_db_query(
+            "jobs",
+            "finish_cancelled_job",
+            self.jobs.finish_cancelled_job(job_id, worker_id),
+        )
+        .await
+    }
+
+    pub async fn reap_expired_jobs(
+        &self,
+        lease_timeout_secs: i64,
+        max_attempts: i32,
+    ) -> Result<Vec<ReapedJob>> {
+        observe_db_query(
+            "jobs",
+            "reap_expired_jobs",
+            self.jobs
+                .reap_expired_jobs(lease_timeout_secs, max_attempts),
+        )
+        .await
     }
 
     pub async fn update_processing_status_by_job_id(
@@ -105,9 +262,12 @@ impl Database {
         job_id: i64,
         status: &str,
     ) -> Result<u64> {
-        self.jobs
-            .update_processing_status_by_job_id(job_id, status)
-            .await
+        observe_db_query(
+            "jobs",
+            "update_processing_status_by_job_id",
+            self.jobs.update_processing_status_by_job_id(job_id, status),
+        )
+        .await
     }
 
     pub async fn get_unprocessed_signatures(
@@ -115,9 +275,81 @@ impl Database {
         address: &str,
         limit: i64,
     ) -> Result<Vec<String>> {
-        self.signatures
-            .get_unprocessed_signatures(address, limit)
-            .await
+        observe_db_query(
+            "signatures",
+            "get_unprocessed_signatures",
+            self.signatures.get_unprocessed_signatures(address, limit),
+        )
+        .await
+    }
+
+    pub async fn record_signature_failures(
+        &self,
+        address: &str,
+        errors: &[TransactionFetchError],
+        max_attempts: i32,
+        retry_base: Duration,
+        retry_max: Duration,
+    ) -> Result<Vec<SignatureFailure>> {
+        observe_db_query(
+            "signatures",
+            "record_signature_failures",
+            self.signatures.record_signature_failures(
+                address,
+                errors,
+                max_attempts,
+                retry_base,
+                retry_max,
+            ),
+        )
+        .await
+    }
+
+    pub async fn get_next_signature_retry_at(
+        &self,
+        address: &str,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        observe_db_query(
+            "signatures",
+            "get_next_signature_retry_at",
+            self.signatures.get_next_signature_retry_at(address),
+        )
+        .await
+    }
+
+    pub async fn get_job_dead_signatures(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+    ) -> Result<Option<JobDeadSignatures>> {
+        observe_db_query(
+            "signatures",
+            "get_job_dead_signatures",
+            self.signatures.get_job_dead_signatures(job_id, api_key_id),
+        )
+        .await
+    }
+
+    pub async fn get_signature_coverage(&self, address: &str) -> Result<Option<SignatureCoverage>> {
+        observe_db_query(
+            "signatures",
+            "get_signature_coverage",
+            self.signatures.get_signature_coverage(address),
+        )
+        .await
+    }
+
+    pub async fn save_signature_coverage(
+        &self,
+        address: &str,
+        coverage: &SignatureCoverage,
+    ) -> Result<()> {
+        observe_db_query(
+            "signatures",
+            "save_signature_coverage",
+            self.signatures.save_signature_coverage(address, coverage),
+        )
+        .await
     }
 
     pub async fn mark_signatures_processed(
@@ -125,13 +357,22 @@ impl Database {
         address: &str,
         signatures: &[String],
     ) -> Result<u64> {
-        self.signatures
-            .mark_signatures_processed(address, signatures)
-            .await
+        observe_db_query(
+            "signatures",
+            "mark_signatures_processed",
+            self.signatures
+                .mark_signatures_processed(address, signatures),
+        )
+        .await
     }
 
     pub async fn write_signatures(&self, signatures: &RpcResponse, address: &str) -> Result<u64> {
-        self.signatures.write_signatures(signatures, address).await
+        observe_db_query(
+            "signatures",
+            "write_signatures",
+            self.signatures.write_signatures(signatures, address),
+        )
+        .await
     }
 
     pub async fn save_transaction_data(
@@ -139,23 +380,299 @@ impl Database {
         transaction_info: &[TransactionResult],
         address: &str,
     ) -> Result<SaveStats> {
-        self.transactions
-            .save_transaction_data(transaction_info, address)
-            .await
+        observe_db_query(
+            "transactions",
+            "save_transaction_data",
+            self.transactions
+                .save_transaction_data(transaction_info, address),
+        )
+        .await
+    }
+
+    pub async fn save_transaction_batch(
+        &self,
+        transaction_info: &[TransactionResult],
+        address: &str,
+        processed_signatures: &[String],
+        store_raw_payloads: bool,
+    ) -> Result<SaveStats> {
+        observe_db_query("transactions", "save_transaction_batch", async {
+            let mut tx = self.pool.begin().await?;
+            let mut save_stats =
+                Transactions::save_transaction_data_in(&mut tx, transaction_info, address).await?;
+            if store_raw_payloads {
+                save_stats.raw_payloads =
+                    RawTransactions::write_raw_transactions_in(&mut tx, transaction_info).await?;
+            }
+            save_stats.signatures_processed =
+                Signatures::mark_signatures_processed_in(&mut tx, address, processed_signatures)
+                    .await?;
+            tx.commit().await?;
+
+            Ok(save_stats)
+        })
+        .await
+    }
+
+    pub async fn replace_transaction_data(
+        &self,
+        transaction_info: &[TransactionResult],
+        address: &str,
+    ) -> Result<SaveStats> {
+        observe_db_query("transactions", "replace_transaction_data", async {
+            let mut tx = self.pool.begin().await?;
+            let save_stats =
+                Transactions::replace_transaction_data_in(&mut tx, transaction_info, address)
+                    .await?;
+            tx.commit().await?;
+
+            Ok(save_stats)
+        })
+        .await
+    }
+
+    pub async fn get_raw_transactions(
+        &self,
+        address: &str,
+        after_signature: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<RawTransaction>> {
+        observe_db_query(
+            "raw_transactions",
+            "get_raw_transactions",
+            self.raw_transactions
+                .get_raw_transactions(address, after_signature, limit),
+        )
+        .await
     }
 
-    pub async fn get_job_info(&self, job_id: i64) -> Result<Option<JobInfo>> {
-        self.jobs.get_job_info(job_id).await
+    pub async fn get_raw_transaction_owners(&self) -> Result<Vec<String>> {
+        observe_db_query(
+            "raw_transactions",
+            "get_raw_transaction_owners",
+            self.raw_transactions.get_raw_transaction_owners(),
+        )
+        .await
+    }
+
+    /// Job-scoped lookups take the requesting key's `api_key_id` and treat jobs owned by
+    /// another key as missing. `None` skips the check for workers and operator commands.
+    pub async fn get_job_info(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+    ) -> Result<Option<JobInfo>> {
+        observe_db_query(
+            "jobs",
+            "get_job_info",
+            self.jobs.get_job_info(job_id, api_key_id),
+        )
+        .await
+    }
+
+    pub async fn get_job_charts(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+        bucket: ChartBucket,
+    ) -> Result<Option<JobCharts>> {
+        observe_db_query(
+            "charts",
+            "get_job_charts",
+            self.charts.get_job_charts(job_id, api_key_id, bucket),
+        )
+        .await
+    }
+
+    pub async fn get_job_transactions(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+        filter: &TransactionFilter,
+    ) -> Result<Option<JobTransactions>> {
+        observe_db_query(
+            "transactions",
+            "get_job_transactions",
+            self.transactions
+                .get_job_transactions(job_id, api_key_id, filter),
+        )
+        .await
+    }
+
+    pub async fn get_job_transfers(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+        filter: &TransferFilter,
+    ) -> Result<Option<JobTransfers>> {
+        observe_db_query(
+            "transactions",
+            "get_job_transfers",
+            self.transactions
+                .get_job_transfers(job_id, api_key_id, filter),
+        )
+        .await
     }
 
     pub async fn create_processing_job(
         &self,
         address: &str,
-        tx_limit: i16,
-        requested_hours: i16,
+        tx_limit: i32,
+        requested_hours: i32,
+        callback_url: Option<&str>,
+        priority: i16,
     ) -> Result<Option<i64>> {
-        self.jobs
-            .create_processing_job(address, tx_limit, requested_hours)
-            .await
+        observe_db_query(
+            "jobs",
+            "create_processing_job",
+            self.jobs.create_processing_job(
+                address,
+                tx_limit,
+                requested_hours,
+                callback_url,
+                priority,
+            ),
+        )
+        .await
+    }
+
+    pub async fn create_processing_job_for_api_key(
+        &self,
+        api_key: &ApiKey,
+        job: &NewJob,
+        dedup: &JobDedup<'_>,
+    ) -> Result<JobSubmission> {
+        observe_db_query(
+            "jobs",
+            "create_processing_job_for_api_key",
+            self.jobs
+                .create_processing_job_for_api_key(api_key, job, dedup),
+        )
+        .await
+    }
+
+    pub async fn purge_expired_idempotency_keys(&self, ttl: Duration) -> Result<u64> {
+        observe_db_query(
+            "jobs",
+            "purge_expired_idempotency_keys",
+            self.jobs.purge_expired_idempotency_keys(ttl),
+        )
+        .await
+    }
+
+    pub async fn create_job_batch(
+        &self,
+        api_key: &ApiKey,
+        jobs: &[NewJob],
+    ) -> Result<CreatedBatch> {
+        observe_db_query(
+            "batches",
+            "create_job_batch",
+            self.batches.create_job_batch(api_key, jobs),
+        )
+        .await
+    }
+
+    pub async fn get_batch_info(
+        &self,
+        batch_id: i64,
+        api_key_id: Option<i64>,
+    ) -> Result<Option<BatchInfo>> {
+        observe_db_query(
+            "batches",
+            "get_batch_info",
+            self.batches.get_batch_info(batch_id, api_key_id),
+        )
+        .await
+    }
+
+    pub async fn find_active_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        observe_db_query(
+            "api_keys",
+            "find_active_api_key",
+            self.api_keys.find_active_api_key(key_hash),
+        )
+        .await
+    }
+
+    pub async fn create_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        limits: ApiKeyLimits,
+    ) -> Result<i64> {
+        observe_db_query(
+            "api_keys",
+            "create_api_key",
+            self.api_keys.create_api_key(name, key_hash, limits),
+        )
+        .await
+    }
+
+    pub async fn revoke_api_key(&self, name: &str) -> Result<u64> {
+        observe_db_query(
+            "api_keys",
+            "revoke_api_key",
+            self.api_keys.revoke_api_key(name),
+        )
+        .await
+    }
+
+    pub async fn claim_due_webhooks(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<ClaimedWebhookDelivery>> {
+        observe_db_query(
+            "webhooks",
+            "claim_due_webhooks",
+            self.webhooks.claim_due_webhooks(limit, lease),
+        )
+        .await
+    }
+
+    pub async fn mark_webhook_delivered(&self, delivery_id: i64, status_code: i32) -> Result<u64> {
+        observe_db_query(
+            "webhooks",
+            "mark_webhook_delivered",
+            self.webhooks
+                .mark_webhook_delivered(delivery_id, status_code),
+        )
+        .await
+    }
+
+    pub async fn mark_webhook_failed(
+        &self,
+        delivery_id: i64,
+        status_code: Option<i32>,
+        error: &str,
+        max_attempts: i32,
+        retry_in: Duration,
+    ) -> Result<Option<String>> {
+        observe_db_query(
+            "webhooks",
+            "mark_webhook_failed",
+            self.webhooks.mark_webhook_failed(
+                delivery_id,
+                status_code,
+                error,
+                max_attempts,
+                retry_in,
+            ),
+        )
+        .await
+    }
+
+    pub async fn get_job_webhooks(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+    ) -> Result<Option<JobWebhooks>> {
+        observe_db_query(
+            "webhooks",
+            "get_job_webhooks",
+            self.webhooks.get_job_webhooks(job_id, api_key_id),
+        )
+        .await
     }
 }
@@ -0,0 +1,102 @@
+use crate::types::{ApiKey, ApiKeyLimits};
+use anyhow::Result;
+use sqlx::postgres::PgPool;
+use std::time::Instant;
+use tracing::{debug, instrument};
+
+pub struct ApiKeys {
+    pool: PgPool,
+}
+
+impl ApiKeys {
+    #[instrument]
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[instrument(skip(self, key_hash))]
+    pub async fn find_active_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let started = Instant::now();
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            "
+            SELECT
+                id,
+                name,
+                max_concurrent_jobs,
+                max_tx_limit,
+                max_requested_hours,
+                max_priority,
+                requests_per_minute,
+                allow_full_history
+            FROM api_keys
+            WHERE key_hash = $1
+              AND revoked_at IS NULL
+            ",
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        debug!(
+            api_key_id = api_key.as_ref().map(|key| key.id),
+            elapsed_ms = started.elapsed().as_millis(),
+            "API key looked up"
+        );
+
+        Ok(api_key)
+    }
+
+    #[instrument(skip(self, key_hash), fields(name))]
+    pub async fn create_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        limits: ApiKeyLimits,
+    ) -> Result<i64> {
+        let id = sqlx::query_scalar::<_, i64>(
+            "
+            INSERT INTO api_keys (
+                name,
+                key_hash,
+                max_concurrent_jobs,
+                max_tx_limit,
+                max_requested_hours,
+                max_priority,
+                requests_per_minute,
+                allow_full_history
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            ",
+        )
+        .bind(name)
+        .bind(key_hash)
+        .bind(limits.max_concurrent_jobs)
+        .bind(limits.max_tx_limit)
+        .bind(limits.max_requested_hours)
+        .bind(limits.max_priority)
+        .bind(limits.requests_per_minute)
+        .bind(limits.allow_full_history)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    #[instrument(skip(self), fields(name))]
+    pub async fn revoke_api_key(&self, name: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "
+            UPDATE api_keys
+            SET revoked_at = NOW()
+            WHERE name = $1
+              AND revoked_at IS NULL
+            ",
+        )
+        .bind(name)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,179 @@
+use crate::db::jobs::{ConcurrentJobLimitExceeded, Jobs};
+use crate::types::{ApiKey, BatchInfo, BatchJobInfo, CreatedBatch, CreatedJob, NewJob};
+use anyhow::Result;
+use sqlx::postgres::PgPool;
+use std::time::Instant;
+use tracing::{debug, instrument};
+
+pub struct Batches {
+    pool: PgPool,
+}
+
+impl Batches {
+    #[instrument]
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates the batch and its jobs in one statement. Only as many leading `jobs` as the
+    /// key's remaining concurrent-job capacity are inserted; callers compare the returned
+    /// jobs against their input to report the rest.
+    ///
+    /// Unlike single submissions, entries never reuse an existing covering job: every member
+    /// must belong to the new batch for `get_batch_info` to roll its progress up.
+    #[instrument(skip(self, api_key, jobs), fields(api_key_id = api_key.id, requested = jobs.len()))]
+    pub async fn create_job_batch(
+        &self,
+        api_key: &ApiKey,
+        jobs: &[NewJob],
+    ) -> Result<CreatedBatch> {
+        let started = Instant::now();
+        let mut tx = self.pool.begin().await?;
+
+        let capacity = Jobs::lock_job_capacity_in(&mut tx, api_key).await?;
+        if capacity == 0 {
+            return Err(ConcurrentJobLimitExceeded {
+                limit: api_key.limits.max_concurrent_jobs,
+            }
+            .into());
+        }
+        let accepted = &jobs[..jobs
+            .len()
+            .min(usize::try_from(capacity).unwrap_or(usize::MAX))];
+
+        let rows = sqlx::query_as::<_, (i64, i64, String)>(
+            "
+            WITH batch AS (
+                INSERT INTO job_batches (api_key_id)
+                VALUES ($1)
+                RETURNING id
+            )
+            INSERT INTO processing_data (
+                address,
+                status,
+                created_at,
+                updated_at,
+                tx_limit,
+                requested_hours,
+                range_start,
+                range_end,
+                callback_url,
+                priority,
+                api_key_id,
+                batch_id
+            )
+            SELECT
+                job.address,
+                'pending',
+                NOW(),
+                NOW(),
+                job.tx_limit,
+                job.requested_hours,
+                COALESCE(job.range_start, NOW() - job.requested_hours * INTERVAL '1 hour'),
+                job.range_end,
+                job.callback_url,
+                job.priority,
+                $1,
+                batch.id
+            FROM UNNEST(
+                $2::text[], $3::integer[], $4::integer[], $5::timestamptz[], $6::timestamptz[],
+                $7::text[], $8::smallint[]
+            ) WITH ORDINALITY AS job(
+                address, tx_limit, requested_hours, range_start, range_end, callback_url, priority, ord
+            )
+            CROSS JOIN batch
+            ORDER BY job.ord
+            RETURNING batch_id, id, address
+            ",
+        )
+        .bind(api_key.id)
+        .bind(column(accepted, |job| job.address.clone()))
+        .bind(column(accepted, |job| job.tx_limit))
+        .bind(column(accepted, |job| job.window.requested_hours()))
+        .bind(column(accepted, |job| job.window.range_start()))
+        .bind(column(accepted, |job| job.window.range_end()))
+        .bind(column(accepted, |job| job.callback_url.clone()))
+        .bind(column(accepted, |job| job.priority))
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let batch_id = rows.first().map_or(0, |(batch_id, _, _)| *batch_id);
+        let jobs = rows
+            .into_iter()
+            .map(|(_, job_id, address)| CreatedJob { job_id, address })
+            .collect::<Vec<_>>();
+
+        debug!(
+            batch_id,
+            inserted = jobs.len(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Job batch created"
+        );
+
+        Ok(CreatedBatch { batch_id, jobs })
+    }
+
+    #[instrument(skip(self), fields(batch_id))]
+    pub async fn get_batch_info(
+        &self,
+        batch_id: i64,
+        api_key_id: Option<i64>,
+    ) -> Result<Option<BatchInfo>> {
+        let started = Instant::now();
+        let Some(created_at) = sqlx::query_scalar::<_, chrono::DateTime<chrono::Utc>>(
+            "
+            SELECT created_at
+            FROM job_batches
+            WHERE id = $1
+              AND ($2::bigint IS NULL OR api_key_id = $2)
+            ",
+        )
+        .bind(batch_id)
+        .bind(api_key_id)
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let jobs = sqlx::query_as::<_, BatchJobInfo>(
+            "
+            SELECT
+                pd.id AS job_id,
+                pd.address,
+                pd.status,
+                pd.priority,
+                pd.updated_at,
+                COUNT(s.signature)::bigint AS total_transactions,
+                COUNT(*) FILTER (WHERE s.is_processed = TRUE)::bigint AS processed_transactions,
+                COUNT(*) FILTER (
+                    WHERE s.is_processed = FALSE AND s.is_dead = FALSE
+                )::bigint AS remaining_transactions
+            FROM processing_data pd
+            LEFT JOIN signatures s
+                ON s.owner_address = pd.address
+               AND s.block_time BETWEEN pd.range_start_ts AND pd.range_end_ts
+            WHERE pd.batch_id = $1
+            GROUP BY pd.id
+            ORDER BY pd.id ASC
+            ",
+        )
+        .bind(batch_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        debug!(
+            jobs = jobs.len(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Batch info fetched"
+        );
+
+        Ok(Some(BatchInfo::from_jobs(batch_id, created_at, jobs)))
+    }
+}
+
+/// Collects one field of every job into an array parameter for `UNNEST`.
+fn column<T>(jobs: &[NewJob], field: impl Fn(&NewJob) -> T) -> Vec<T> {
+    jobs.iter().map(field).collect()
+}
@@ -0,0 +1,111 @@
+use crate::types::{ChartBucket, ChartsSummary, JobCharts, TxTimePoint};
+use anyhow::Result;
+use sqlx::postgres::PgPool;
+use std::time::Instant;
+use tracing::{debug, instrument};
+
+pub struct Charts {
+    pool: PgPool,
+}
+
+impl Charts {
+    #[instrument]
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[instrument(skip(self), fields(job_id, bucket = bucket.as_str()))]
+    pub async fn get_job_charts(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+        bucket: ChartBucket,
+    ) -> Result<Option<JobCharts>> {
+        let started = Instant::now();
+        let summary = sqlx::query_as::<_, ChartsSummary>(
+            "
+            SELECT
+                COALESCE((
+                    SELECT COUNT(*)
+                    FROM transactions t
+                    WHERE t.owner_address = pd.address
+                      AND t.block_time BETWEEN pd.range_start_ts AND pd.range_end_ts
+                      AND (t.err IS NULL OR t.err = 'null'::jsonb)
+                ), 0)::bigint AS success_count,
+                COALESCE((
+                    SELECT COUNT(*)
+                    FROM transactions t
+                    WHERE t.owner_address = pd.address
+                      AND t.block_time BETWEEN pd.range_start_ts AND pd.range_end_ts
+                      AND t.err IS NOT NULL
+                      AND t.err <> 'null'::jsonb
+                ), 0)::bigint AS failed_count,
+                COALESCE((
+                    SELECT SUM(t.fee)
+                    FROM transactions t
+                    WHERE t.owner_address = pd.address
+                      AND t.block_time BETWEEN pd.range_start_ts AND pd.range_end_ts
+                ), 0)::bigint AS total_fee,
+                COALESCE((
+                    SELECT SUM(tt.amount_raw)
+                    FROM token_transfers tt
+                    WHERE tt.tracked_owner = pd.address
+                      AND tt.asset_type = 'native'
+                      AND tt.block_time BETWEEN pd.range_start_ts AND pd.range_end_ts
+                ), 0)::text AS native_transfer_volume
+            FROM processing_data pd
+            WHERE pd.id = $1
+              AND ($2::bigint IS NULL OR pd.api_key_id = $2)
+            ",
+        )
+        .bind(job_id)
+        .bind(api_key_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(summary) = summary else {
+            debug!(
+                elapsed_ms = started.elapsed().as_millis(),
+                "Job not found for charts"
+            );
+            return Ok(None);
+        };
+
+        let tx_time_line = sqlx::query_as::<_, TxTimePoint>(
+            "
+            SELECT
+                date_trunc($2, to_timestamp(t.block_time), 'UTC') AS bucket,
+                COUNT(*)::bigint AS transactions,
+                COUNT(*) FILTER (WHERE t.err IS NULL OR t.err = 'null'::jsonb)::bigint AS successful,
+                COUNT(*) FILTER (WHERE t.err IS NOT NULL AND t.err <> 'null'::jsonb)::bigint AS failed,
+                COALESCE(SUM(t.fee), 0)::bigint AS fee
+            FROM processing_data pd
+            JOIN transactions t
+                ON t.owner_address = pd.address
+               AND t.block_time BETWEEN pd.range_start_ts AND pd.range_end_ts
+            WHERE pd.id = $1
+            GROUP BY bucket
+            ORDER BY bucket ASC
+            ",
+        )
+        .bind(job_id)
+        .bind(bucket.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+
+        debug!(
+            points = tx_time_line.len(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Job charts fetched"
+        );
+
+        Ok(Some(JobCharts {
+            bucket,
+            tx_time_line,
+            success_count: summary.success_count,
+            failed_count: summary.failed_count,
+            total_fee: summary.total_fee,
+            native_transfer_volume: summary.native_transfer_volume,
+        }))
+    }
+}
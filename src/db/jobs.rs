@@ -1,9 +1,36 @@
-use crate::types::{ClaimedJob, JobInfo};
+use crate::types::{
+    ApiKey, ClaimedJob, JobDedup, JobInfo, JobSubmission, JobTransition, JobWindow, NewJob,
+    ReapedJob,
+};
 use anyhow::{Ok, Result};
-use sqlx::postgres::PgPool;
-use std::time::Instant;
+use sqlx::postgres::{PgConnection, PgPool};
+use std::time::{Duration, Instant};
 use tracing::{debug, instrument};
 
+#[derive(Debug)]
+pub struct ConcurrentJobLimitExceeded {
+    pub limit: i32,
+}
+
+impl std::fmt::Display for ConcurrentJobLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "API key already has {} active jobs", self.limit)
+    }
+}
+
+impl std::error::Error for ConcurrentJobLimitExceeded {}
+
+#[derive(Debug)]
+pub struct IdempotencyKeyMismatch;
+
+impl std::fmt::Display for IdempotencyKeyMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Idempotency-Key was already used with a different request")
+    }
+}
+
+impl std::error::Error for IdempotencyKeyMismatch {}
+
 pub struct Jobs {
     pool: PgPool,
 }
@@ -14,6 +41,9 @@ impl Jobs {
         Self { pool }
     }
 
+    /// Claims the next job fairly: higher `priority` tiers go first, and within a tier
+    /// submitters (API keys, with keyless jobs as one more submitter) take turns, preferring
+    /// the one with fewer active jobs and then the one served least recently.
     #[instrument(skip(self), fields(worker_id))]
     pub async fn claim_pending_job(&self, worker_id: u32) -> Result<Option<ClaimedJob>> {
         let started = Instant::now();
@@ -21,26 +51,65 @@ impl Jobs {
 
         let claimed_job = sqlx::query_as::<_, ClaimedJob>(
             "
-            WITH next_job AS (
-            SELECT id
-            FROM processing_data pd
-            WHERE status = 'pending'
-            AND NOT EXISTS (
-                SELECT 1 FROM processing_data active
-                WHERE active.address = pd.address
-                    AND active.status = 'indexing'
+            WITH candidates AS (
+                SELECT
+                    pd.id,
+                    pd.priority,
+                    pd.created_at,
+                    COALESCE(pd.api_key_id, 0) AS submitter_id,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY COALESCE(pd.api_key_id, 0), pd.priority
+                        ORDER BY pd.created_at ASC, pd.id ASC
+                    ) AS submitter_rank
+                FROM processing_data pd
+                WHERE pd.status = 'pending'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM processing_data active
+                      WHERE active.address = pd.address
+                        AND active.status IN ('indexing', 'cancelling')
+                  )
+            ),
+            submitters AS (
+                SELECT
+                    s.submitter_id,
+                    (
+                        SELECT COUNT(*)
+                        FROM processing_data active
+                        WHERE COALESCE(active.api_key_id, 0) = s.submitter_id
+                          AND active.status IN ('indexing', 'cancelling')
+                    ) AS active_jobs,
+                    (
+                        SELECT MAX(served.claimed_at)
+                        FROM processing_data served
+                        WHERE COALESCE(served.api_key_id, 0) = s.submitter_id
+                    ) AS last_claimed_at
+                FROM (SELECT DISTINCT submitter_id FROM candidates) s
+            ),
+            next_job AS (
+                SELECT pd.id
+                FROM candidates c
+                JOIN submitters s ON s.submitter_id = c.submitter_id
+                JOIN processing_data pd ON pd.id = c.id
+                WHERE pd.status = 'pending'
+                ORDER BY
+                    c.priority DESC,
+                    c.submitter_rank ASC,
+                    s.active_jobs ASC,
+                    s.last_claimed_at ASC NULLS FIRST,
+                    c.created_at ASC
+                LIMIT 1
+                FOR UPDATE OF pd SKIP LOCKED
             )
-            ORDER BY created_at ASC
-            LIMIT 1
-            FOR UPDATE SKIP LOCKED
-)
             UPDATE processing_data pd
-            SET status     = 'indexing',
-                worker_id  = $1,
-                updated_at = now()
+            SET status       = 'indexing',
+                worker_id    = $1,
+                attempts     = pd.attempts + 1,
+                heartbeat_at = now(),
+                claimed_at   = now(),
+                updated_at   = now()
             FROM next_job
             WHERE pd.id = next_job.id
-            RETURNING pd.id AS job_id, pd.address, pd.requested_hours, pd.tx_limit
+            RETURNING pd.id AS job_id, pd.address, pd.range_start, pd.range_end, pd.tx_limit
             ",
         )
         .bind(worker_id)
@@ -56,6 +125,302 @@ impl Jobs {
         Ok(claimed_job)
     }
 
+    #[instrument(skip(self), fields(job_id, worker_id))]
+    pub async fn record_heartbeat(&self, job_id: i64, worker_id: u32) -> Result<u64> {
+        let worker_id = i32::try_from(worker_id).unwrap_or(i32::MAX);
+        let result = sqlx::query(
+            "
+            UPDATE processing_data
+            SET heartbeat_at = NOW()
+            WHERE id = $1
+              AND worker_id = $2
+              AND status IN ('indexing', 'cancelling')
+            ",
+        )
+        .bind(job_id)
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[instrument(skip(self), fields(job_id, worker_id))]
+    pub async fn release_job(&self, job_id: i64, worker_id: u32) -> Result<u64> {
+        let started = Instant::now();
+        let worker_id = i32::try_from(worker_id).unwrap_or(i32::MAX);
+        let released = sqlx::query_scalar::<_, i64>(
+            "
+            WITH released AS (
+                UPDATE processing_data
+                SET status       = 'pending',
+                    worker_id    = NULL,
+                    heartbeat_at = NULL,
+                    attempts     = GREATEST(attempts - 1, 0),
+                    updated_at   = NOW()
+                WHERE id = $1
+                  AND worker_id = $2
+                  AND status = 'indexing'
+                RETURNING address
+            ),
+            released_signatures AS (
+                UPDATE signatures s
+                SET is_processing = FALSE,
+                    processing_started_at = NULL
+                FROM released
+                WHERE s.owner_address = released.address
+                  AND s.is_processed = FALSE
+                  AND s.is_processing = TRUE
+            )
+            SELECT COUNT(*) FROM released
+            ",
+        )
+        .bind(job_id)
+        .bind(worker_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let released = u64::try_from(released).unwrap_or_default();
+        debug!(
+            released,
+            elapsed_ms = started.elapsed().as_millis(),
+            "Job released back to pending"
+        );
+
+        Ok(released)
+    }
+
+    #[instrument(skip(self), fields(job_id))]
+    pub async fn notify_job_progress(&self, job_id: i64) -> Result<()> {
+        sqlx::query("SELECT pg_notify($1, $2::text)")
+            .bind(super::JOB_EVENTS_CHANNEL)
+            .bind(job_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(job_id))]
+    pub async fn cancel_job(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+    ) -> Result<Option<JobTransition>> {
+        let started = Instant::now();
+        let transition = sqlx::query_as::<_, JobTransition>(
+            "
+            WITH target AS (
+                SELECT id, status
+                FROM processing_data
+                WHERE id = $1
+                  AND ($2::bigint IS NULL OR api_key_id = $2)
+                FOR UPDATE
+            ),
+            updated AS (
+                UPDATE processing_data pd
+                SET status     = CASE WHEN target.status = 'pending' THEN 'cancelled' ELSE 'cancelling' END,
+                    updated_at = NOW()
+                FROM target
+                WHERE pd.id = target.id
+                  AND target.status IN ('pending', 'indexing')
+                RETURNING pd.status
+            )
+            SELECT target.status AS previous_status, updated.status
+            FROM target
+            LEFT JOIN updated ON TRUE
+            ",
+        )
+        .bind(job_id)
+        .bind(api_key_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        debug!(
+            previous_status = transition.as_ref().map(|t| t.previous_status.as_str()),
+            status = transition.as_ref().and_then(|t| t.status.as_deref()),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Job cancellation requested"
+        );
+
+        Ok(transition)
+    }
+
+    /// Requeues an `error` job. With `api_key` the job must belong to that key and the retry
+    /// counts against its `max_concurrent_jobs`, failing with [`ConcurrentJobLimitExceeded`]
+    /// like a new submission would.
+    #[instrument(skip(self, api_key), fields(job_id))]
+    pub async fn retry_job(
+        &self,
+        job_id: i64,
+        api_key: Option<&ApiKey>,
+    ) -> Result<Option<JobTransition>> {
+        let started = Instant::now();
+        let mut tx = self.pool.begin().await?;
+        let capacity = match api_key {
+            Some(api_key) => Some(Self::lock_job_capacity_in(&mut tx, api_key).await?),
+            None => None,
+        };
+
+        let previous_status = sqlx::query_scalar::<_, String>(
+            "
+            SELECT status
+            FROM processing_data
+            WHERE id = $1
+              AND ($2::bigint IS NULL OR api_key_id = $2)
+            FOR UPDATE
+            ",
+        )
+        .bind(job_id)
+        .bind(api_key.map(|api_key| api_key.id))
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(previous_status) = previous_status else {
+            return Ok(None);
+        };
+
+        let status = if previous_status == "error" {
+            if let (Some(api_key), Some(0)) = (api_key, capacity) {
+                return Err(ConcurrentJobLimitExceeded {
+                    limit: api_key.limits.max_concurrent_jobs,
+                }
+                .into());
+            }
+            sqlx::query_scalar::<_, String>(
+                "
+                UPDATE processing_data
+                SET status       = 'pending',
+                    worker_id    = NULL,
+                    heartbeat_at = NULL,
+                    attempts     = 0,
+                    updated_at   = NOW()
+                WHERE id = $1
+                RETURNING status
+                ",
+            )
+            .bind(job_id)
+            .fetch_optional(&mut *tx)
+            .await?
+        } else {
+            None
+        };
+        tx.commit().await?;
+
+        let transition = JobTransition {
+            previous_status,
+            status,
+        };
+        debug!(
+            previous_status = transition.previous_status.as_str(),
+            status = transition.status.as_deref(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Job retry requested"
+        );
+
+        Ok(Some(transition))
+    }
+
+    #[instrument(skip(self), fields(job_id))]
+    pub async fn is_cancellation_requested(&self, job_id: i64) -> Result<bool> {
+        let requested = sqlx::query_scalar::<_, bool>(
+            "
+            SELECT EXISTS (
+                SELECT 1
+                FROM processing_data
+                WHERE id = $1
+                  AND status = 'cancelling'
+            )
+            ",
+        )
+        .bind(job_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(requested)
+    }
+
+    #[instrument(skip(self), fields(job_id, worker_id))]
+    pub async fn finish_cancelled_job(&self, job_id: i64, worker_id: u32) -> Result<u64> {
+        let started = Instant::now();
+        let worker_id = i32::try_from(worker_id).unwrap_or(i32::MAX);
+        let cancelled = sqlx::query_scalar::<_, i64>(
+            "
+            WITH cancelled AS (
+                UPDATE processing_data
+                SET status       = 'cancelled',
+                    worker_id    = NULL,
+                    heartbeat_at = NULL,
+                    updated_at   = NOW()
+                WHERE id = $1
+                  AND worker_id = $2
+                  AND status = 'cancelling'
+                RETURNING address
+            ),
+            released_signatures AS (
+                UPDATE signatures s
+                SET is_processing = FALSE,
+                    processing_started_at = NULL
+                FROM cancelled
+                WHERE s.owner_address = cancelled.address
+                  AND s.is_processed = FALSE
+                  AND s.is_processing = TRUE
+            )
+            SELECT COUNT(*) FROM cancelled
+            ",
+        )
+        .bind(job_id)
+        .bind(worker_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let cancelled = u64::try_from(cancelled).unwrap_or_default();
+        debug!(
+            cancelled,
+            elapsed_ms = started.elapsed().as_millis(),
+            "Cancelled job finished"
+        );
+
+        Ok(cancelled)
+    }
+
+    #[instrument(skip(self), fields(lease_timeout_secs, max_attempts))]
+    pub async fn reap_expired_jobs(
+        &self,
+        lease_timeout_secs: i64,
+        max_attempts: i32,
+    ) -> Result<Vec<ReapedJob>> {
+        let started = Instant::now();
+        let reaped = sqlx::query_as::<_, ReapedJob>(
+            "
+            UPDATE processing_data
+            SET status       = CASE
+                                   WHEN status = 'cancelling' THEN 'cancelled'
+                                   WHEN attempts >= $2 THEN 'error'
+                                   ELSE 'pending'
+                               END,
+                worker_id    = NULL,
+                heartbeat_at = NULL,
+                updated_at   = NOW()
+            WHERE status IN ('indexing', 'cancelling')
+              AND COALESCE(heartbeat_at, updated_at) < NOW() - $1 * INTERVAL '1 second'
+            RETURNING id AS job_id, status, attempts
+            ",
+        )
+        .bind(lease_timeout_secs)
+        .bind(max_attempts)
+        .fetch_all(&self.pool)
+        .await?;
+
+        debug!(
+            reaped = reaped.len(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Expired job leases reaped"
+        );
+
+        Ok(reaped)
+    }
+
     #[instrument(skip(self), fields(job_id, status))]
     pub async fn update_processing_status_by_job_id(
         &self,
@@ -76,9 +441,9 @@ impl Jobs {
                       SELECT 1
                       FROM signatures s
                       WHERE s.owner_address = processing_data.address
-                        AND s.block_time >= EXTRACT(EPOCH FROM (processing_data.created_at - processing_data.requested_hours * INTERVAL '1 hour'))::bigint
-                        AND s.block_time <= EXTRACT(EPOCH FROM processing_data.created_at)::bigint
+                        AND s.block_time BETWEEN processing_data.range_start_ts AND processing_data.range_end_ts
                         AND s.is_processed = FALSE
+                        AND s.is_dead = FALSE
                   )
               )
             ",
@@ -98,27 +463,38 @@ impl Jobs {
         Ok(updated)
     }
 
-    pub async fn get_job_info(&self, job_id: i64) -> Result<Option<JobInfo>> {
+    pub async fn get_job_info(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+    ) -> Result<Option<JobInfo>> {
         let query = "
         SELECT
             pd.status,
+            pd.priority,
+            pd.range_start,
+            pd.range_end,
             pd.updated_at,
             COUNT(s.signature)::bigint AS total_transactions,
             COUNT(*) FILTER (WHERE s.is_processed = TRUE)::bigint AS processed_transactions,
-            (
-                COUNT(s.signature) - COUNT(*) FILTER (WHERE s.is_processed = TRUE)
-            )::bigint AS remaining_transactions
+            COUNT(*) FILTER (
+                WHERE s.is_processed = FALSE AND s.is_dead = FALSE
+            )::bigint AS remaining_transactions,
+            COUNT(*) FILTER (
+                WHERE s.is_processed = FALSE AND s.is_dead = TRUE
+            )::bigint AS dead_transactions
         FROM processing_data pd
         LEFT JOIN signatures s
             ON s.owner_address = pd.address
-           AND s.block_time >= EXTRACT(EPOCH FROM (pd.created_at - pd.requested_hours * INTERVAL '1 hour'))::bigint
-           AND s.block_time <= EXTRACT(EPOCH FROM pd.created_at)::bigint
+           AND s.block_time BETWEEN pd.range_start_ts AND pd.range_end_ts
         WHERE pd.id = $1
-        GROUP BY pd.status, pd.updated_at
+          AND ($2::bigint IS NULL OR pd.api_key_id = $2)
+        GROUP BY pd.status, pd.priority, pd.range_start, pd.range_end, pd.updated_at
     ";
 
         let result = sqlx::query_as::<_, JobInfo>(query)
             .bind(job_id)
+            .bind(api_key_id)
             .fetch_optional(&self.pool)
             .await?;
 
@@ -128,20 +504,252 @@ impl Jobs {
     pub async fn create_processing_job(
         &self,
         address: &str,
-        tx_limit: i16,
-        requested_hours: i16,
+        tx_limit: i32,
+        requested_hours: i32,
+        callback_url: Option<&str>,
+        priority: i16,
+    ) -> Result<Option<i64>> {
+        let mut conn = self.pool.acquire().await?;
+        Self::insert_processing_job_in(
+            &mut conn,
+            address,
+            Some(tx_limit),
+            JobWindow::Recent {
+                hours: requested_hours,
+            },
+            callback_url,
+            priority,
+            None,
+        )
+        .await
+    }
+
+    /// Submits a job owned by `api_key`. A live `Idempotency-Key` returns the job it was
+    /// first used for, and with reuse enabled an existing job covering the same address and
+    /// window is returned instead of inserting a duplicate. New jobs fail with
+    /// [`ConcurrentJobLimitExceeded`] when the key already has `max_concurrent_jobs`
+    /// unfinished jobs; locking the key row keeps concurrent requests from both passing the
+    /// count check.
+    #[instrument(skip(self, api_key, job, dedup), fields(api_key_id = api_key.id))]
+    pub async fn create_processing_job_for_api_key(
+        &self,
+        api_key: &ApiKey,
+        job: &NewJob,
+        dedup: &JobDedup<'_>,
+    ) -> Result<JobSubmission> {
+        let mut tx = self.pool.begin().await?;
+        let capacity = Self::lock_job_capacity_in(&mut tx, api_key).await?;
+        let fingerprint = job.fingerprint();
+
+        if let Some(key) = dedup.idempotency_key {
+            let stored = sqlx::query_as::<_, (i64, String)>(
+                "
+                SELECT job_id, request_fingerprint
+                FROM idempotency_keys
+                WHERE api_key_id = $1
+                  AND key = $2
+                  AND created_at > NOW() - $3 * INTERVAL '1 millisecond'
+                ",
+            )
+            .bind(api_key.id)
+            .bind(key)
+            .bind(duration_millis(dedup.idempotency_ttl))
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if let Some((job_id, stored_fingerprint)) = stored {
+                if stored_fingerprint != fingerprint {
+                    return Err(IdempotencyKeyMismatch.into());
+                }
+                debug!(job_id, "Idempotency key replayed");
+                return Ok(JobSubmission {
+                    job_id,
+                    reused: true,
+                });
+            }
+        }
+
+        let covering_job = match dedup.reuse_ready_within {
+            Some(ready_ttl) => {
+                Self::find_covering_job_in(&mut tx, api_key.id, job, ready_ttl).await?
+            }
+            None => None,
+        };
+
+        let submission = if let Some(job_id) = covering_job {
+            debug!(job_id, "Reusing job covering the requested window");
+            JobSubmission {
+                job_id,
+                reused: true,
+            }
+        } else {
+            if capacity == 0 {
+                return Err(ConcurrentJobLimitExceeded {
+                    limit: api_key.limits.max_concurrent_jobs,
+                }
+                .into());
+            }
+            let job_id = Self::insert_processing_job_in(
+                &mut tx,
+                &job.address,
+                job.tx_limit,
+                job.window,
+                job.callback_url.as_deref(),
+                job.priority,
+                Some(api_key.id),
+            )
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("job insert returned no id"))?;
+            JobSubmission {
+                job_id,
+                reused: false,
+            }
+        };
+
+        if let Some(key) = dedup.idempotency_key {
+            sqlx::query(
+                "
+                INSERT INTO idempotency_keys (api_key_id, key, request_fingerprint, job_id)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (api_key_id, key) DO UPDATE
+                SET request_fingerprint = EXCLUDED.request_fingerprint,
+                    job_id              = EXCLUDED.job_id,
+                    created_at          = NOW()
+                ",
+            )
+            .bind(api_key.id)
+            .bind(key)
+            .bind(&fingerprint)
+            .bind(submission.job_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(submission)
+    }
+
+    /// Finds the newest unfinished job, or `ready` job updated within `ready_ttl`, submitted
+    /// by the same key for the same address whose range starts no later and ends no earlier
+    /// than the request's, and whose `tx_limit` is at least as large. Open-ended requests only
+    /// match open-ended jobs. A capped job indexes only the newest signatures below its own
+    /// upper bound, so for absolute ranges it covers only when that bound equals the
+    /// requested `to`. The address lock serializes submitters racing on the same address.
+    async fn find_covering_job_in(
+        conn: &mut PgConnection,
+        api_key_id: i64,
+        job: &NewJob,
+        ready_ttl: Duration,
+    ) -> Result<Option<i64>> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(&job.address)
+            .execute(&mut *conn)
+            .await?;
+
+        let job_id = sqlx::query_scalar::<_, i64>(
+            "
+            SELECT id
+            FROM processing_data
+            WHERE address = $1
+              AND (
+                  status IN ('pending', 'indexing')
+                  OR (status = 'ready' AND updated_at >= NOW() - $2 * INTERVAL '1 millisecond')
+              )
+              AND range_start <= COALESCE($3, NOW() - $4 * INTERVAL '1 hour')
+              AND CASE
+                  WHEN $5::timestamptz IS NULL THEN range_end IS NULL
+                  ELSE COALESCE(range_end, created_at) >= $5
+                      AND (tx_limit IS NULL OR range_end = $5)
+              END
+              AND (tx_limit IS NULL OR tx_limit >= $6)
+              AND ($7::text IS NULL OR callback_url = $7)
+              AND api_key_id = $8
+            ORDER BY created_at DESC
+            LIMIT 1
+            ",
+        )
+        .bind(&job.address)
+        .bind(duration_millis(ready_ttl))
+        .bind(job.window.range_start())
+        .bind(job.window.requested_hours())
+        .bind(job.window.range_end())
+        .bind(job.tx_limit)
+        .bind(job.callback_url.as_deref())
+        .bind(api_key_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(job_id)
+    }
+
+    #[instrument(skip(self), fields(ttl_secs = ttl.as_secs()))]
+    pub async fn purge_expired_idempotency_keys(&self, ttl: Duration) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM idempotency_keys WHERE created_at < NOW() - $1 * INTERVAL '1 millisecond'",
+        )
+        .bind(duration_millis(ttl))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Locks the key row for the rest of the transaction and returns how many more
+    /// unfinished jobs the key may own.
+    pub async fn lock_job_capacity_in(conn: &mut PgConnection, api_key: &ApiKey) -> Result<i64> {
+        sqlx::query("SELECT 1 FROM api_keys WHERE id = $1 FOR UPDATE")
+            .bind(api_key.id)
+            .execute(&mut *conn)
+            .await?;
+
+        let active_jobs = sqlx::query_scalar::<_, i64>(
+            "
+            SELECT COUNT(*)
+            FROM processing_data
+            WHERE api_key_id = $1
+              AND status IN ('pending', 'indexing', 'cancelling')
+            ",
+        )
+        .bind(api_key.id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let limit = api_key.limits.max_concurrent_jobs;
+        let capacity = (i64::from(limit) - active_jobs).max(0);
+        debug!(active_jobs, limit, capacity, "Job capacity checked");
+
+        Ok(capacity)
+    }
+
+    async fn insert_processing_job_in(
+        conn: &mut PgConnection,
+        address: &str,
+        tx_limit: Option<i32>,
+        window: JobWindow,
+        callback_url: Option<&str>,
+        priority: i16,
+        api_key_id: Option<i64>,
     ) -> Result<Option<i64>> {
-        let query = "INSERT INTO processing_data (address, status, created_at, updated_at, tx_limit, requested_hours)
-                VALUES ($1, 'pending', NOW(), NOW(), $2, $3)
+        let query = "INSERT INTO processing_data (address, status, created_at, updated_at, tx_limit, requested_hours, range_start, range_end, callback_url, priority, api_key_id)
+                VALUES ($1, 'pending', NOW(), NOW(), $2, $3, COALESCE($4, NOW() - $3 * INTERVAL '1 hour'), $5, $6, $7, $8)
                 RETURNING id";
 
         let job_id: Option<i64> = sqlx::query_scalar(query)
             .bind(address)
             .bind(tx_limit)
-            .bind(requested_hours)
-            .fetch_optional(&self.pool)
+            .bind(window.requested_hours())
+            .bind(window.range_start())
+            .bind(window.range_end())
+            .bind(callback_url)
+            .bind(priority)
+            .bind(api_key_id)
+            .fetch_optional(conn)
             .await?;
 
         Ok(job_id)
     }
 }
+
+fn duration_millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
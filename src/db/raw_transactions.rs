@@ -0,0 +1,117 @@
+use crate::logging::mask_addr;
+use crate::requests::TransactionResult;
+use crate::types::RawTransaction;
+
+use anyhow::Result;
+use sqlx::QueryBuilder;
+use sqlx::postgres::{PgConnection, PgPool};
+use std::time::Instant;
+use tracing::{debug, instrument};
+
+pub struct RawTransactions {
+    pool: PgPool,
+}
+
+impl RawTransactions {
+    #[instrument]
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores the raw payload of every transaction that still carries one. Payloads are
+    /// keyed by signature only, so a transaction shared by several tracked addresses is
+    /// stored once.
+    #[instrument(skip(conn, transaction_info), fields(input_count = transaction_info.len()))]
+    pub async fn write_raw_transactions_in(
+        conn: &mut PgConnection,
+        transaction_info: &[TransactionResult],
+    ) -> Result<u64> {
+        let rows: Vec<_> = transaction_info
+            .iter()
+            .filter_map(|tx| tx.raw_payload.as_ref().map(|payload| (tx, payload)))
+            .collect();
+
+        if rows.is_empty() {
+            debug!("No raw transaction payloads to insert");
+            return Ok(0);
+        }
+
+        let started = Instant::now();
+        let mut query_builder: QueryBuilder<sqlx::Postgres> = QueryBuilder::new(
+            "INSERT INTO raw_transactions
+            (signature, slot, block_time, payload)",
+        );
+
+        query_builder.push_values(rows, |mut b, (tx, payload)| {
+            b.push_bind(tx.signature())
+                .push_bind(i64::from(tx.result.slot))
+                .push_bind(i64::from(tx.result.block_time))
+                .push_bind(payload);
+        });
+        query_builder.push("ON CONFLICT (signature) DO NOTHING");
+
+        let result = query_builder.build().execute(&mut *conn).await?;
+        let inserted = result.rows_affected();
+        debug!(
+            inserted,
+            elapsed_ms = started.elapsed().as_millis(),
+            "Raw transaction payloads inserted"
+        );
+
+        Ok(inserted)
+    }
+
+    /// Pages through the stored payloads of an address's processed signatures in signature
+    /// order, starting after `after_signature`.
+    #[instrument(skip(self), fields(address = %mask_addr(address), limit))]
+    pub async fn get_raw_transactions(
+        &self,
+        address: &str,
+        after_signature: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<RawTransaction>> {
+        let started = Instant::now();
+        let raw_transactions = sqlx::query_as::<_, RawTransaction>(
+            "
+            SELECT r.signature, r.payload
+            FROM signatures s
+            JOIN raw_transactions r ON r.signature = s.signature
+            WHERE s.owner_address = $1
+              AND s.is_processed = TRUE
+              AND ($2::text IS NULL OR s.signature > $2)
+            ORDER BY s.signature ASC
+            LIMIT $3
+            ",
+        )
+        .bind(address)
+        .bind(after_signature)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        debug!(
+            count = raw_transactions.len(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Raw transaction payloads fetched"
+        );
+
+        Ok(raw_transactions)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_raw_transaction_owners(&self) -> Result<Vec<String>> {
+        let owners = sqlx::query_scalar::<_, String>(
+            "
+            SELECT DISTINCT s.owner_address
+            FROM signatures s
+            JOIN raw_transactions r ON r.signature = s.signature
+            WHERE s.is_processed = TRUE
+            ORDER BY s.owner_address ASC
+            ",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(owners)
+    }
+}
@@ -1,10 +1,11 @@
 use crate::logging::mask_addr;
-use crate::requests::RpcResponse;
+use crate::requests::{RpcResponse, TransactionFetchError};
+use crate::types::{DeadSignatureRow, JobDeadSignatures, SignatureCoverage, SignatureFailure};
 
 use anyhow::Result;
 use sqlx::QueryBuilder;
-use sqlx::postgres::PgPool;
-use std::time::Instant;
+use sqlx::postgres::{PgConnection, PgPool};
+use std::time::{Duration, Instant};
 use tracing::{debug, instrument};
 
 pub struct Signatures {
@@ -35,6 +36,8 @@ impl Signatures {
                 FROM signatures
                 WHERE owner_address = $1 
                 AND is_processed = FALSE 
+                AND is_dead = FALSE
+                AND (next_attempt_at IS NULL OR next_attempt_at <= NOW())
                 AND (
                     is_processing = FALSE
                     OR processing_started_at IS NULL
@@ -60,11 +63,246 @@ impl Signatures {
         Ok(result)
     }
 
-    #[instrument(skip(self, signatures), fields(address = %mask_addr(address), input_count = signatures.len()))]
+    /// Records failed fetch attempts and releases the signatures. Each failure schedules the
+    /// next attempt `retry_base * 2^(attempts - 1)` later, capped at `retry_max`; signatures
+    /// that reach `max_attempts` are dead-lettered and never claimed again.
+    #[instrument(skip(self, errors), fields(address = %mask_addr(address), input_count = errors.len(), max_attempts))]
+    pub async fn record_signature_failures(
+        &self,
+        address: &str,
+        errors: &[TransactionFetchError],
+        max_attempts: i32,
+        retry_base: Duration,
+        retry_max: Duration,
+    ) -> Result<Vec<SignatureFailure>> {
+        if errors.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let started = Instant::now();
+        let signatures: Vec<&str> = errors.iter().map(|e| e.signature.as_str()).collect();
+        let status_codes: Vec<Option<i32>> = errors
+            .iter()
+            .map(|e| e.status_code.map(i32::from))
+            .collect();
+        let rpc_codes: Vec<Option<i64>> = errors.iter().map(|e| e.rpc_code).collect();
+        let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+
+        let failures = sqlx::query_as::<_, SignatureFailure>(
+            "
+            UPDATE signatures s
+            SET attempts              = s.attempts + 1,
+                last_status_code      = f.status_code,
+                last_rpc_code         = f.rpc_code,
+                last_error            = LEFT(f.message, 512),
+                last_attempt_at       = NOW(),
+                next_attempt_at       = CASE
+                                            WHEN s.attempts + 1 >= $6 THEN NULL
+                                            ELSE NOW() + LEAST(
+                                                $7 * POWER(2, LEAST(s.attempts, 30)),
+                                                $8
+                                            ) * INTERVAL '1 millisecond'
+                                        END,
+                is_dead               = s.attempts + 1 >= $6,
+                is_processing         = FALSE,
+                processing_started_at = NULL
+            FROM UNNEST($2::text[], $3::int[], $4::bigint[], $5::text[])
+                AS f(signature, status_code, rpc_code, message)
+            WHERE s.owner_address = $1
+              AND s.signature = f.signature
+              AND s.is_processed = FALSE
+            RETURNING s.signature, s.attempts, s.is_dead, s.next_attempt_at
+            ",
+        )
+        .bind(address)
+        .bind(&signatures)
+        .bind(&status_codes)
+        .bind(&rpc_codes)
+        .bind(&messages)
+        .bind(max_attempts)
+        .bind(i64::try_from(retry_base.as_millis()).unwrap_or(i64::MAX))
+        .bind(i64::try_from(retry_max.as_millis()).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        debug!(
+            recorded = failures.len(),
+            dead = failures.iter().filter(|f| f.is_dead).count(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Signature failures recorded"
+        );
+        Ok(failures)
+    }
+
+    /// Returns when the earliest failed, not yet dead signature becomes claimable again.
+    /// The time may already have passed if the retry came due after the last claim;
+    /// signatures leased by another worker are left to that worker.
+    #[instrument(skip(self), fields(address = %mask_addr(address)))]
+    pub async fn get_next_signature_retry_at(
+        &self,
+        address: &str,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let retry_at = sqlx::query_scalar::<_, Option<chrono::DateTime<chrono::Utc>>>(
+            "
+            SELECT MIN(next_attempt_at)
+            FROM signatures
+            WHERE owner_address = $1
+              AND is_processed = FALSE
+              AND is_dead = FALSE
+              AND next_attempt_at IS NOT NULL
+              AND (
+                  is_processing = FALSE
+                  OR processing_started_at IS NULL
+                  OR processing_started_at < NOW() - INTERVAL '5 minutes'
+              )
+            ",
+        )
+        .bind(address)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(retry_at)
+    }
+
+    #[instrument(skip(self), fields(job_id))]
+    pub async fn get_job_dead_signatures(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+    ) -> Result<Option<JobDeadSignatures>> {
+        let started = Instant::now();
+        let Some(job_id) = sqlx::query_scalar::<_, i64>(
+            "
+            SELECT id
+            FROM processing_data
+            WHERE id = $1
+              AND ($2::bigint IS NULL OR api_key_id = $2)
+            ",
+        )
+        .bind(job_id)
+        .bind(api_key_id)
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            debug!(
+                elapsed_ms = started.elapsed().as_millis(),
+                "Job not found for dead signatures"
+            );
+            return Ok(None);
+        };
+
+        let signatures = sqlx::query_as::<_, DeadSignatureRow>(
+            "
+            SELECT
+                s.signature,
+                s.block_time,
+                s.attempts,
+                s.last_status_code,
+                s.last_rpc_code,
+                s.last_error,
+                s.last_attempt_at
+            FROM processing_data pd
+            JOIN signatures s
+                ON s.owner_address = pd.address
+               AND s.block_time BETWEEN pd.range_start_ts AND pd.range_end_ts
+            WHERE pd.id = $1
+              AND s.is_dead = TRUE
+              AND s.is_processed = FALSE
+            ORDER BY s.block_time DESC, s.signature ASC
+            ",
+        )
+        .bind(job_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        debug!(
+            dead = signatures.len(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Job dead signatures fetched"
+        );
+
+        Ok(Some(JobDeadSignatures { job_id, signatures }))
+    }
+
+    /// Returns the contiguous span of history already fetched for `address`. Ranged jobs
+    /// only store a slice of the history, so once an address has had one its coverage is
+    /// no longer used.
+    #[instrument(skip(self), fields(address = %mask_addr(address)))]
+    pub async fn get_signature_coverage(&self, address: &str) -> Result<Option<SignatureCoverage>> {
+        let started = Instant::now();
+        let coverage = sqlx::query_as::<_, SignatureCoverage>(
+            "
+            SELECT newest_signature, oldest_signature, oldest_block_time
+            FROM signature_coverage
+            WHERE owner_address = $1
+              AND NOT EXISTS (
+                  SELECT 1
+                  FROM processing_data
+                  WHERE address = $1
+                    AND range_end IS NOT NULL
+              )
+            ",
+        )
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        debug!(
+            covered = coverage.is_some(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Fetched stored signature coverage"
+        );
+        Ok(coverage)
+    }
+
+    #[instrument(skip(self, coverage), fields(address = %mask_addr(address)))]
+    pub async fn save_signature_coverage(
+        &self,
+        address: &str,
+        coverage: &SignatureCoverage,
+    ) -> Result<()> {
+        sqlx::query(
+            "
+            INSERT INTO signature_coverage (
+                owner_address, newest_signature, oldest_signature, oldest_block_time
+            )
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (owner_address) DO UPDATE
+            SET newest_signature = EXCLUDED.newest_signature,
+                oldest_signature = EXCLUDED.oldest_signature,
+                oldest_block_time = EXCLUDED.oldest_block_time,
+                updated_at = NOW()
+            ",
+        )
+        .bind(address)
+        .bind(&coverage.newest_signature)
+        .bind(&coverage.oldest_signature)
+        .bind(coverage.oldest_block_time)
+        .execute(&self.pool)
+        .await?;
+
+        debug!(
+            newest = %coverage.newest_signature,
+            oldest = %coverage.oldest_signature,
+            "Signature coverage saved"
+        );
+        Ok(())
+    }
+
     pub async fn mark_signatures_processed(
         &self,
         address: &str,
         signatures: &[String],
+    ) -> Result<u64> {
+        let mut conn = self.pool.acquire().await?;
+        Self::mark_signatures_processed_in(&mut conn, address, signatures).await
+    }
+
+    #[instrument(skip(conn, signatures), fields(address = %mask_addr(address), input_count = signatures.len()))]
+    pub async fn mark_signatures_processed_in(
+        conn: &mut PgConnection,
+        address: &str,
+        signatures: &[String],
     ) -> Result<u64> {
         if signatures.is_empty() {
             return Ok(0);
@@ -83,7 +321,7 @@ impl Signatures {
         )
         .bind(address)
         .bind(signatures)
-        .execute(&self.pool)
+        .execute(&mut *conn)
         .await?;
 
         let updated = result.rows_affected();
@@ -1,8 +1,12 @@
-use crate::types::SaveStats;
+use crate::types::{
+    AssetTypeFilter, JobTransactions, JobTransfers, SaveStats, TransactionCursor,
+    TransactionFilter, TransactionRow, TransactionStatusFilter, TransferCursor, TransferFilter,
+    TransferRow, TransferTypeFilter,
+};
 use anyhow::Result;
 use bigdecimal::{BigDecimal, FromPrimitive};
 use sqlx::QueryBuilder;
-use sqlx::postgres::PgPool;
+use sqlx::postgres::{PgConnection, PgPool};
 use std::time::Instant;
 use tracing::{debug, info, instrument};
 
@@ -19,9 +23,9 @@ impl Transactions {
         Self { pool }
     }
 
-    #[instrument(skip(self, transaction_info), fields(address = %mask_addr(tracked_owner), input_count = transaction_info.len()))]
+    #[instrument(skip(conn, transaction_info), fields(address = %mask_addr(tracked_owner), input_count = transaction_info.len()))]
     pub async fn write_transaction_info(
-        &self,
+        conn: &mut PgConnection,
         transaction_info: &[TransactionResult],
         tracked_owner: &str,
     ) -> Result<u64> {
@@ -39,13 +43,7 @@ impl Transactions {
         let transaction_iter = transaction_info.iter();
 
         query_builder.push_values(transaction_iter, |mut b, tx| {
-            let signature = tx
-                .result
-                .transaction
-                .signatures
-                .first()
-                .map(String::as_str)
-                .unwrap_or_default();
+            let signature = tx.signature();
             let num_signers = tx.num_signers();
             let num_instructions = tx.num_instructions();
 
@@ -63,7 +61,7 @@ impl Transactions {
 
         let query = query_builder.build();
 
-        let result = query.execute(&self.pool).await?;
+        let result = query.execute(&mut *conn).await?;
         let inserted = result.rows_affected();
         debug!(
             inserted,
@@ -74,9 +72,9 @@ impl Transactions {
         Ok(inserted)
     }
 
-    #[instrument(skip(self, transactions), fields(tracked_owner = %mask_addr(tracked_owner), input_count = transactions.len()))]
+    #[instrument(skip(conn, transactions), fields(tracked_owner = %mask_addr(tracked_owner), input_count = transactions.len()))]
     pub async fn write_token_transfers(
-        &self,
+        conn: &mut PgConnection,
         transactions: &[TransactionResult],
         tracked_owner: &str,
     ) -> Result<u64> {
@@ -120,7 +118,7 @@ impl Transactions {
                 .push_bind(transfer.decimals.map(i32::from))
                 .push_bind(&transfer.asset_type)
                 .push_bind(&transfer.transfer_type)
-                .push_bind(&transfer.direction)
+                .push_bind(transfer.direction_for(tracked_owner))
                 .push_bind(transfer.instruction_idx)
                 .push_bind(transfer.inner_idx)
                 .push_bind(&transfer.authority)
@@ -130,7 +128,7 @@ impl Transactions {
         query_builder.push("ON CONFLICT DO NOTHING");
 
         let query = query_builder.build();
-        let result = query.execute(&self.pool).await?;
+        let result = query.execute(&mut *conn).await?;
         let inserted = result.rows_affected();
         debug!(
             inserted,
@@ -146,22 +144,33 @@ impl Transactions {
         &self,
         transaction_info: &[TransactionResult],
         address: &str,
+    ) -> Result<SaveStats> {
+        let mut tx = self.pool.begin().await?;
+        let save_stats = Self::save_transaction_data_in(&mut tx, transaction_info, address).await?;
+        tx.commit().await?;
+
+        Ok(save_stats)
+    }
+
+    #[instrument(skip(conn, transaction_info), fields(address = %mask_addr(address), input_count = transaction_info.len()))]
+    pub async fn save_transaction_data_in(
+        conn: &mut PgConnection,
+        transaction_info: &[TransactionResult],
+        address: &str,
     ) -> Result<SaveStats> {
         if transaction_info.is_empty() {
             debug!("No transaction payload to save");
             return Ok(SaveStats {
                 transactions: 0,
                 token_transfers: 0,
+                signatures_processed: 0,
+                raw_payloads: 0,
             });
         }
 
         let started = Instant::now();
-        let transactions = self
-            .write_transaction_info(transaction_info, address)
-            .await?;
-        let token_transfers = self
-            .write_token_transfers(transaction_info, address)
-            .await?;
+        let transactions = Self::write_transaction_info(conn, transaction_info, address).await?;
+        let token_transfers = Self::write_token_transfers(conn, transaction_info, address).await?;
         info!(
             transactions,
             token_transfers,
@@ -172,6 +181,255 @@ impl Transactions {
         Ok(SaveStats {
             transactions,
             token_transfers,
+            signatures_processed: 0,
+            raw_payloads: 0,
         })
     }
+
+    /// Deletes the stored rows for the given transactions and saves them again, so a
+    /// reparse replaces what an older parser produced instead of adding to it.
+    #[instrument(skip(conn, transaction_info), fields(address = %mask_addr(address), input_count = transaction_info.len()))]
+    pub async fn replace_transaction_data_in(
+        conn: &mut PgConnection,
+        transaction_info: &[TransactionResult],
+        address: &str,
+    ) -> Result<SaveStats> {
+        let signatures: Vec<&str> = transaction_info
+            .iter()
+            .map(TransactionResult::signature)
+            .collect();
+
+        sqlx::query(
+            "
+            DELETE FROM token_transfers
+            WHERE tracked_owner = $1
+              AND signature = ANY($2)
+            ",
+        )
+        .bind(address)
+        .bind(&signatures)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "
+            DELETE FROM transactions
+            WHERE owner_address = $1
+              AND signature = ANY($2)
+            ",
+        )
+        .bind(address)
+        .bind(&signatures)
+        .execute(&mut *conn)
+        .await?;
+
+        Self::save_transaction_data_in(conn, transaction_info, address).await
+    }
+
+    #[instrument(skip(self), fields(job_id, limit = filter.limit))]
+    pub async fn get_job_transactions(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+        filter: &TransactionFilter,
+    ) -> Result<Option<JobTransactions>> {
+        let started = Instant::now();
+        let job_exists = sqlx::query_scalar::<_, bool>(
+            "
+            SELECT EXISTS (
+                SELECT 1
+                FROM processing_data
+                WHERE id = $1
+                  AND ($2::bigint IS NULL OR api_key_id = $2)
+            )
+            ",
+        )
+        .bind(job_id)
+        .bind(api_key_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if !job_exists {
+            debug!(
+                elapsed_ms = started.elapsed().as_millis(),
+                "Job not found for transactions"
+            );
+            return Ok(None);
+        }
+
+        let mut transactions = sqlx::query_as::<_, TransactionRow>(
+            "
+            SELECT
+                t.signature,
+                t.slot,
+                t.block_time,
+                t.err,
+                t.fee,
+                t.compute_units,
+                t.num_signers,
+                t.num_instructions
+            FROM processing_data pd
+            JOIN transactions t
+                ON t.owner_address = pd.address
+               AND t.block_time BETWEEN pd.range_start_ts AND pd.range_end_ts
+            WHERE pd.id = $1
+              AND (
+                  $2::text IS NULL
+                  OR ($2 = 'success' AND (t.err IS NULL OR t.err = 'null'::jsonb))
+                  OR ($2 = 'failed' AND t.err IS NOT NULL AND t.err <> 'null'::jsonb)
+              )
+              AND ($3::bigint IS NULL OR t.fee >= $3)
+              AND ($4::integer IS NULL OR t.compute_units >= $4)
+              AND ($5::bigint IS NULL OR (t.block_time, t.signature) < ($5, $6::text))
+            ORDER BY t.block_time DESC, t.signature DESC
+            LIMIT $7
+            ",
+        )
+        .bind(job_id)
+        .bind(filter.status.map(TransactionStatusFilter::as_str))
+        .bind(filter.min_fee)
+        .bind(filter.min_compute_units)
+        .bind(filter.cursor.as_ref().map(|cursor| cursor.block_time))
+        .bind(
+            filter
+                .cursor
+                .as_ref()
+                .map(|cursor| cursor.signature.as_str()),
+        )
+        .bind(filter.limit + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let has_more = transactions.len() > usize::try_from(filter.limit).unwrap_or_default();
+        transactions.truncate(usize::try_from(filter.limit).unwrap_or_default());
+        let next_cursor = transactions.last().filter(|_| has_more).map(|last| {
+            TransactionCursor {
+                block_time: last.block_time,
+                signature: last.signature.clone(),
+            }
+            .to_string()
+        });
+
+        debug!(
+            returned = transactions.len(),
+            has_more,
+            elapsed_ms = started.elapsed().as_millis(),
+            "Job transactions fetched"
+        );
+
+        Ok(Some(JobTransactions {
+            transactions,
+            next_cursor,
+        }))
+    }
+
+    #[instrument(skip(self), fields(job_id, limit = filter.limit))]
+    pub async fn get_job_transfers(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+        filter: &TransferFilter,
+    ) -> Result<Option<JobTransfers>> {
+        let started = Instant::now();
+        let job_exists = sqlx::query_scalar::<_, bool>(
+            "
+            SELECT EXISTS (
+                SELECT 1
+                FROM processing_data
+                WHERE id = $1
+                  AND ($2::bigint IS NULL OR api_key_id = $2)
+            )
+            ",
+        )
+        .bind(job_id)
+        .bind(api_key_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if !job_exists {
+            debug!(
+                elapsed_ms = started.elapsed().as_millis(),
+                "Job not found for transfers"
+            );
+            return Ok(None);
+        }
+
+        let mut transfers = sqlx::query_as::<_, TransferRow>(
+            "
+            SELECT
+                tt.id,
+                tt.signature,
+                tt.slot,
+                tt.block_time,
+                tt.token_mint,
+                tt.token_program,
+                tt.source_owner,
+                tt.destination_owner,
+                tt.source_token_account,
+                tt.destination_token_account,
+                tt.amount_raw::text AS amount_raw,
+                tt.amount_ui::double precision AS amount_ui,
+                tt.decimals,
+                tt.transfer_type,
+                tt.asset_type,
+                tt.direction
+            FROM processing_data pd
+            JOIN token_transfers tt
+                ON tt.tracked_owner = pd.address
+               AND tt.block_time BETWEEN pd.range_start_ts AND pd.range_end_ts
+            WHERE pd.id = $1
+              AND ($2::text IS NULL OR tt.token_mint = $2)
+              AND ($3::text IS NULL OR tt.asset_type = $3)
+              AND ($4::text IS NULL OR tt.transfer_type = $4)
+              AND (
+                  $5::text IS NULL
+                  OR $5 IN (
+                      tt.source_owner,
+                      tt.destination_owner,
+                      tt.source_token_account,
+                      tt.destination_token_account
+                  )
+              )
+              AND ($6::numeric IS NULL OR tt.amount_raw >= $6)
+              AND ($7::numeric IS NULL OR tt.amount_raw <= $7)
+              AND ($8::bigint IS NULL OR (tt.block_time, tt.id) < ($8, $9::uuid))
+            ORDER BY tt.block_time DESC, tt.id DESC
+            LIMIT $10
+            ",
+        )
+        .bind(job_id)
+        .bind(filter.token_mint.as_deref())
+        .bind(filter.asset_type.map(AssetTypeFilter::as_str))
+        .bind(filter.transfer_type.map(TransferTypeFilter::as_str))
+        .bind(filter.counterparty.as_deref())
+        .bind(filter.min_amount.map(BigDecimal::from))
+        .bind(filter.max_amount.map(BigDecimal::from))
+        .bind(filter.cursor.as_ref().map(|cursor| cursor.block_time))
+        .bind(filter.cursor.as_ref().map(|cursor| cursor.id))
+        .bind(filter.limit + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let has_more = transfers.len() > usize::try_from(filter.limit).unwrap_or_default();
+        transfers.truncate(usize::try_from(filter.limit).unwrap_or_default());
+        let next_cursor = transfers.last().filter(|_| has_more).map(|last| {
+            TransferCursor {
+                block_time: last.block_time,
+                id: last.id,
+            }
+            .to_string()
+        });
+
+        debug!(
+            returned = transfers.len(),
+            has_more,
+            elapsed_ms = started.elapsed().as_millis(),
+            "Job transfers fetched"
+        );
+
+        Ok(Some(JobTransfers {
+            transfers,
+            next_cursor,
+        }))
+    }
 }
@@ -0,0 +1,176 @@
+use crate::types::{ClaimedWebhookDelivery, JobWebhooks, WebhookDeliveryRow};
+use anyhow::Result;
+use sqlx::postgres::PgPool;
+use std::time::{Duration, Instant};
+use tracing::{debug, instrument};
+
+pub struct Webhooks {
+    pool: PgPool,
+}
+
+impl Webhooks {
+    #[instrument]
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Leases due deliveries by pushing `next_attempt_at` past the request timeout, so a
+    /// crashed dispatcher's deliveries become due again instead of staying stuck.
+    #[instrument(skip(self), fields(limit))]
+    pub async fn claim_due_webhooks(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<ClaimedWebhookDelivery>> {
+        let started = Instant::now();
+        let claimed = sqlx::query_as::<_, ClaimedWebhookDelivery>(
+            "
+            WITH due AS (
+                SELECT id
+                FROM webhook_deliveries
+                WHERE status = 'pending'
+                  AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at ASC
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE webhook_deliveries wd
+            SET attempts        = wd.attempts + 1,
+                next_attempt_at = NOW() + $2 * INTERVAL '1 millisecond',
+                updated_at      = NOW()
+            FROM due
+            WHERE wd.id = due.id
+            RETURNING wd.id, wd.job_id, wd.callback_url, wd.payload::text AS payload, wd.attempts
+            ",
+        )
+        .bind(limit)
+        .bind(i64::try_from(lease.as_millis()).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        debug!(
+            claimed = claimed.len(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Due webhooks claimed"
+        );
+
+        Ok(claimed)
+    }
+
+    #[instrument(skip(self), fields(delivery_id, status_code))]
+    pub async fn mark_webhook_delivered(&self, delivery_id: i64, status_code: i32) -> Result<u64> {
+        let result = sqlx::query(
+            "
+            UPDATE webhook_deliveries
+            SET status           = 'delivered',
+                last_status_code = $2,
+                last_error       = NULL,
+                delivered_at     = NOW(),
+                updated_at       = NOW()
+            WHERE id = $1
+              AND status = 'pending'
+            ",
+        )
+        .bind(delivery_id)
+        .bind(status_code)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Records a failed attempt and returns the resulting status: `pending` when another
+    /// attempt is scheduled after `retry_in`, `dead` once `max_attempts` is exhausted.
+    #[instrument(skip(self, error), fields(delivery_id, status_code, max_attempts))]
+    pub async fn mark_webhook_failed(
+        &self,
+        delivery_id: i64,
+        status_code: Option<i32>,
+        error: &str,
+        max_attempts: i32,
+        retry_in: Duration,
+    ) -> Result<Option<String>> {
+        let status = sqlx::query_scalar::<_, String>(
+            "
+            UPDATE webhook_deliveries
+            SET status           = CASE WHEN attempts >= $4 THEN 'dead' ELSE 'pending' END,
+                next_attempt_at  = NOW() + $5 * INTERVAL '1 millisecond',
+                last_status_code = $2,
+                last_error       = $3,
+                updated_at       = NOW()
+            WHERE id = $1
+              AND status = 'pending'
+            RETURNING status
+            ",
+        )
+        .bind(delivery_id)
+        .bind(status_code)
+        .bind(error)
+        .bind(max_attempts)
+        .bind(i64::try_from(retry_in.as_millis()).unwrap_or(i64::MAX))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(status)
+    }
+
+    #[instrument(skip(self), fields(job_id))]
+    pub async fn get_job_webhooks(
+        &self,
+        job_id: i64,
+        api_key_id: Option<i64>,
+    ) -> Result<Option<JobWebhooks>> {
+        let started = Instant::now();
+        let Some(callback_url) = sqlx::query_scalar::<_, Option<String>>(
+            "
+            SELECT callback_url
+            FROM processing_data
+            WHERE id = $1
+              AND ($2::bigint IS NULL OR api_key_id = $2)
+            ",
+        )
+        .bind(job_id)
+        .bind(api_key_id)
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            debug!(
+                elapsed_ms = started.elapsed().as_millis(),
+                "Job not found for webhooks"
+            );
+            return Ok(None);
+        };
+
+        let deliveries = sqlx::query_as::<_, WebhookDeliveryRow>(
+            "
+            SELECT
+                id,
+                callback_url,
+                status,
+                attempts,
+                last_status_code,
+                last_error,
+                next_attempt_at,
+                created_at,
+                delivered_at
+            FROM webhook_deliveries
+            WHERE job_id = $1
+            ORDER BY id ASC
+            ",
+        )
+        .bind(job_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        debug!(
+            deliveries = deliveries.len(),
+            elapsed_ms = started.elapsed().as_millis(),
+            "Job webhooks fetched"
+        );
+
+        Ok(Some(JobWebhooks {
+            callback_url,
+            deliveries,
+        }))
+    }
+}
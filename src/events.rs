@@ -0,0 +1,117 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tokio::sync::{Notify, broadcast, futures::Notified};
+use tracing::{debug, info, warn};
+
+use crate::db::{Database, JOB_QUEUE_CHANNEL};
+use crate::shutdown::Shutdown;
+
+const JOB_EVENTS_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct JobEvents {
+    sender: broadcast::Sender<i64>,
+    job_queued: Arc<Notify>,
+    listening: Arc<AtomicBool>,
+}
+
+impl Default for JobEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(JOB_EVENTS_CAPACITY);
+        Self {
+            sender,
+            job_queued: Arc::new(Notify::new()),
+            listening: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<i64> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, job_id: i64) {
+        let _ = self.sender.send(job_id);
+    }
+
+    pub fn job_queued(&self) -> Notified<'_> {
+        self.job_queued.notified()
+    }
+
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::Acquire)
+    }
+
+    fn set_listening(&self, listening: bool) {
+        if self.listening.swap(listening, Ordering::AcqRel) != listening {
+            // Notifications sent while the connection was down are lost, so let idle
+            // workers re-check the queue whenever the listener state flips.
+            self.job_queued.notify_waiters();
+        }
+    }
+
+    pub async fn listen(self, database: &Database, shutdown: Shutdown) {
+        let mut listener = loop {
+            match database.job_events_listener().await {
+                Ok(listener) => break listener,
+                Err(err) => {
+                    warn!(%err, "Failed to subscribe to job events, retrying");
+                    tokio::select! {
+                        () = tokio::time::sleep(Duration::from_secs(5)) => {}
+                        () = shutdown.triggered() => return,
+                    }
+                }
+            }
+        };
+        self.set_listening(true);
+        info!("Listening for job events");
+
+        loop {
+            let notification = tokio::select! {
+                notification = listener.try_recv() => notification,
+                () = shutdown.triggered() => break,
+            };
+
+            match notification {
+                Ok(Some(notification)) => {
+                    self.set_listening(true);
+                    if notification.channel() == JOB_QUEUE_CHANNEL {
+                        debug!(payload = notification.payload(), "Job queued");
+                        self.job_queued.notify_waiters();
+                        continue;
+                    }
+
+                    match notification.payload().parse::<i64>() {
+                        Ok(job_id) => {
+                            debug!(job_id, "Job event received");
+                            self.publish(job_id);
+                        }
+                        Err(err) => warn!(
+                            %err,
+                            payload = notification.payload(),
+                            "Ignoring malformed job event"
+                        ),
+                    }
+                }
+                Ok(None) => {
+                    warn!("Job events connection lost, workers fall back to polling");
+                    self.set_listening(false);
+                }
+                Err(err) => {
+                    warn!(%err, "Job events listener failed, reconnecting");
+                    self.set_listening(false);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+
+        self.set_listening(false);
+    }
+}
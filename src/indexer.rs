@@ -1,34 +1,79 @@
 use crate::{
-    AppState, logging,
-    types::{ClaimedJob, JobInfo},
+    AppState, logging, metrics,
+    requests::{SignatureRange, TransactionFetchError},
+    shutdown::ShutdownRequested,
+    types::{ClaimedJob, JobInfo, SignatureCoverage},
 };
 
-use anyhow::Result;
-use bigdecimal::{ToPrimitive, Zero};
-use std::time::Instant;
+use anyhow::{Context, Result};
+use bigdecimal::Zero;
+use std::time::{Duration, Instant};
 use tracing::{Instrument, debug, info, warn};
 
+const JOB_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+const SIGNATURE_RETRY_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct JobCancelled;
+
+impl std::fmt::Display for JobCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("job cancelled")
+    }
+}
+
+impl std::error::Error for JobCancelled {}
+
+/// The job's lease no longer belongs to this worker, usually because the reaper requeued it.
+#[derive(Debug)]
+struct LeaseLost;
+
+impl std::fmt::Display for LeaseLost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("job lease lost")
+    }
+}
+
+impl std::error::Error for LeaseLost {}
+
 pub async fn process_claimed_job(app_state: &AppState, worker_id: u32, claimed_job: ClaimedJob) {
     let started = Instant::now();
     let job_id = claimed_job.job_id;
+    let range = claimed_job.signature_range();
     let address = claimed_job.address;
-    let requested_hours = claimed_job.requested_hours;
     let tx_limit = claimed_job.tx_limit;
 
-    let processing_result: Result<()> = async {
-        fetch_signatures(
-            app_state,
-            &address,
-            tx_limit.to_usize().unwrap_or(1000),
-            requested_hours,
-        )
-        .await?;
-        process_unprocessed_signatures(app_state, &address).await?;
+    let processing = async {
+        let tx_limit = match tx_limit {
+            Some(tx_limit) => {
+                usize::try_from(tx_limit).with_context(|| format!("invalid tx_limit {tx_limit}"))?
+            }
+            None => usize::MAX,
+        };
+        fetch_signatures(app_state, job_id, &address, tx_limit, range).await?;
+        process_unprocessed_signatures(app_state, job_id, &address).await?;
         Ok(())
-    }
-    .await;
+    };
+
+    let processing_result: Result<()> = tokio::select! {
+        result = processing => result,
+        lost = heartbeat_loop(app_state, job_id, worker_id) => Err(lost.into()),
+        () = app_state.shutdown.deadline() => Err(ShutdownRequested.into()),
+    };
 
     match processing_result {
+        Err(err) if err.is::<ShutdownRequested>() => {
+            release_job(app_state, job_id, worker_id).await;
+        }
+        Err(err) if err.is::<JobCancelled>() => {
+            finish_cancelled_job(app_state, job_id, worker_id).await;
+        }
+        Err(err) if err.is::<LeaseLost>() => {
+            warn!(
+                job_id,
+                worker_id, "Job lease was reaped; abandoning job for {}", &address
+            );
+        }
         Ok(()) => {
             match app_state
                 .database
@@ -36,17 +81,20 @@ pub async fn process_claimed_job(app_state: &AppState, worker_id: u32, claimed_j
                 .await
             {
                 Ok(1) => {
+                    metrics::record_job_finished("ready");
                     info!(
                         elapsed_ms = started.elapsed().as_millis(),
                         worker_id, job_id, "Indexer finished for {}", &address
                     );
                 }
                 Ok(0) => {
-                    warn!(
-                        job_id,
-                        worker_id, "Ready status update was blocked by unprocessed signatures"
-                    );
-                    mark_job_error(app_state, job_id, worker_id).await;
+                    if !finish_late_cancellation(app_state, job_id, worker_id).await {
+                        warn!(
+                            job_id,
+                            worker_id, "Ready status update was blocked by unprocessed signatures"
+                        );
+                        mark_job_error(app_state, job_id, worker_id).await;
+                    }
                 }
                 Ok(updated) => {
                     warn!(
@@ -69,13 +117,109 @@ pub async fn process_claimed_job(app_state: &AppState, worker_id: u32, claimed_j
     }
 }
 
+/// Keeps the job's lease alive and only returns once the lease is gone, so the caller stops
+/// working on a job another worker may already own.
+async fn heartbeat_loop(app_state: &AppState, job_id: i64, worker_id: u32) -> LeaseLost {
+    let mut ticker = tokio::time::interval(JOB_HEARTBEAT_INTERVAL);
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+        app_state.worker_liveness.beat(worker_id);
+        match app_state.database.record_heartbeat(job_id, worker_id).await {
+            Ok(0) => {
+                warn!(
+                    job_id,
+                    worker_id, "Heartbeat matched no indexing job; lease was reaped"
+                );
+                return LeaseLost;
+            }
+            Ok(_) => debug!(job_id, worker_id, "Job heartbeat recorded"),
+            Err(err) => warn!(%err, job_id, worker_id, "Failed to record job heartbeat"),
+        }
+    }
+}
+
+async fn release_job(app_state: &AppState, job_id: i64, worker_id: u32) {
+    match app_state.database.release_job(job_id, worker_id).await {
+        Ok(1) => info!(
+            job_id,
+            worker_id, "Job released back to pending for shutdown"
+        ),
+        Ok(released) => warn!(
+            released,
+            job_id, worker_id, "Unexpected number of jobs released to pending"
+        ),
+        Err(err) => warn!(
+            %err,
+            job_id,
+            worker_id,
+            "Failed to release job on shutdown; reaper will requeue it"
+        ),
+    }
+}
+
+async fn finish_cancelled_job(app_state: &AppState, job_id: i64, worker_id: u32) {
+    match app_state
+        .database
+        .finish_cancelled_job(job_id, worker_id)
+        .await
+    {
+        Ok(1) => {
+            metrics::record_job_finished("cancelled");
+            info!(job_id, worker_id, "Job cancelled");
+        }
+        Ok(cancelled) => warn!(
+            cancelled,
+            job_id, worker_id, "Unexpected number of jobs moved to cancelled"
+        ),
+        Err(err) => warn!(%err, job_id, worker_id, "Failed to finish cancelled job"),
+    }
+}
+
+/// A cancel can land after the last `ensure_not_interrupted`, leaving the job `cancelling`
+/// so the final status update matches nothing. Finishes the cancellation in that case and
+/// reports whether it did.
+async fn finish_late_cancellation(app_state: &AppState, job_id: i64, worker_id: u32) -> bool {
+    match app_state.database.is_cancellation_requested(job_id).await {
+        Ok(true) => {
+            finish_cancelled_job(app_state, job_id, worker_id).await;
+            true
+        }
+        Ok(false) => false,
+        Err(err) => {
+            warn!(%err, job_id, worker_id, "Failed to check for a late cancellation");
+            false
+        }
+    }
+}
+
+async fn notify_progress(app_state: &AppState, job_id: i64) {
+    if let Err(err) = app_state.database.notify_job_progress(job_id).await {
+        warn!(%err, job_id, "Failed to publish job progress event");
+    }
+}
+
+async fn ensure_not_interrupted(app_state: &AppState, job_id: i64) -> Result<()> {
+    if app_state.shutdown.is_triggered() {
+        return Err(ShutdownRequested.into());
+    }
+
+    if app_state.database.is_cancellation_requested(job_id).await? {
+        return Err(JobCancelled.into());
+    }
+
+    Ok(())
+}
+
 async fn mark_job_error(app_state: &AppState, job_id: i64, worker_id: u32) {
     match app_state
         .database
         .update_processing_status_by_job_id(job_id, "error")
         .await
     {
-        Ok(1) => {}
+        Ok(1) => metrics::record_job_finished("error"),
+        Ok(0) if finish_late_cancellation(app_state, job_id, worker_id).await => {}
         Ok(updated) => warn!(
             updated,
             job_id, worker_id, "Unexpected number of jobs updated to error"
@@ -100,66 +244,106 @@ pub async fn process_pending_job_once(
     let job_id = claimed_job.job_id;
     process_claimed_job(app_state, worker_id, claimed_job).await;
 
-    app_state.database.get_job_info(job_id).await
+    app_state.database.get_job_info(job_id, None).await
 }
 
 async fn fetch_signatures(
     app_state: &AppState,
+    job_id: i64,
     address: &str,
     tx_limit: usize,
-    requested_hours: i16,
+    range: SignatureRange,
 ) -> Result<()> {
     let database = &app_state.database;
-    let helius_api = &app_state.helius_api;
     let masked_address = logging::mask_addr(address);
     let run_span = tracing::info_span!("indexer_run", address = %masked_address);
 
     async {
-        let mut cur_last_signature: Option<String> = None;
-        let mut sum: usize = 0;
         info!("Fetching signatures started");
-
         let sync_started = Instant::now();
-        loop {
-            debug!(before = ?cur_last_signature, "Fetching signatures page");
-            let page_started = Instant::now();
-            let signatures_page = helius_api
-                .get_signatures(address, cur_last_signature, requested_hours)
-                .await?;
-
-            let res_len = signatures_page.response.result.len();
-            sum += res_len;
 
+        let Some(coverage) = database.get_signature_coverage(address).await? else {
+            let walk =
+                walk_signatures(app_state, job_id, address, None, None, tx_limit, range).await?;
+            if range.to_ts.is_none()
+                && let Some(coverage) = walk.coverage()
+            {
+                database.save_signature_coverage(address, &coverage).await?;
+            }
             info!(
-                raw_page_len = signatures_page.raw_count,
-                page_len = res_len,
-                total = sum,
-                reached_cutoff = signatures_page.reached_cutoff,
-                elapsed_ms = page_started.elapsed().as_millis(),
-                "Signatures page received"
+                total = walk.total,
+                elapsed_ms = sync_started.elapsed().as_millis(),
+                "Signature sync finished"
             );
+            return Ok(());
+        };
 
-            let inserted = database
-                .write_signatures(&signatures_page.response, address)
-                .await?;
-            debug!(inserted, "Signatures saved");
+        debug!(
+            newest = %coverage.newest_signature,
+            oldest = %coverage.oldest_signature,
+            oldest_block_time = coverage.oldest_block_time,
+            "Resuming from stored signatures"
+        );
 
-            if signatures_page.reached_cutoff
-                || signatures_page.last_signature.is_none()
-                || signatures_page.raw_count < 1000
-                || sum >= tx_limit
-            {
-                info!("No more signatures available");
-                break;
+        let newer = walk_signatures(
+            app_state,
+            job_id,
+            address,
+            None,
+            Some(&coverage.newest_signature),
+            tx_limit,
+            range,
+        )
+        .await?;
+
+        let mut extended = coverage.clone();
+        if newer.reached_end {
+            if let Some((newest, _)) = &newer.newest {
+                extended.newest_signature.clone_from(newest);
             }
+        } else if newer.total > 0 {
+            // Stopping short of `until` leaves a gap above the stored span, so the span is
+            // kept as is and the next job walks down to it again.
+            info!(
+                newer = newer.total,
+                "Newer signatures walk stopped before stored history"
+            );
+        }
 
-            cur_last_signature = signatures_page.last_signature;
+        let cutoff_ts = range.from_ts;
+        let older = if newer.total < tx_limit && coverage.oldest_block_time > cutoff_ts {
+            info!(
+                oldest_block_time = coverage.oldest_block_time,
+                cutoff_ts, "Requested window exceeds stored history, extending backwards"
+            );
+            let older = walk_signatures(
+                app_state,
+                job_id,
+                address,
+                Some(coverage.oldest_signature.clone()),
+                None,
+                tx_limit - newer.total,
+                range,
+            )
+            .await?;
+            if let Some((oldest, oldest_block_time)) = &older.oldest {
+                extended.oldest_signature.clone_from(oldest);
+                extended.oldest_block_time = *oldest_block_time;
+            }
+            older.total
+        } else {
+            0
+        };
+
+        if extended != coverage {
+            database.save_signature_coverage(address, &extended).await?;
         }
 
         info!(
-            total = sum,
+            newer = newer.total,
+            older,
             elapsed_ms = sync_started.elapsed().as_millis(),
-            "Signature sync finished"
+            "Incremental signature sync finished"
         );
 
         Ok(())
@@ -168,21 +352,123 @@ async fn fetch_signatures(
     .await
 }
 
-async fn process_unprocessed_signatures(app_state: &AppState, address: &str) -> Result<()> {
+/// What one `walk_signatures` call stored: the newest and oldest signatures it wrote, and
+/// whether it ran until `until` or the end of history rather than stopping at the window
+/// start or `tx_limit`.
+#[derive(Debug, Default)]
+struct SignatureWalk {
+    total: usize,
+    newest: Option<(String, i64)>,
+    oldest: Option<(String, i64)>,
+    reached_end: bool,
+}
+
+impl SignatureWalk {
+    fn coverage(&self) -> Option<SignatureCoverage> {
+        let (newest_signature, _) = self.newest.clone()?;
+        let (oldest_signature, oldest_block_time) = self.oldest.clone()?;
+        Some(SignatureCoverage {
+            newest_signature,
+            oldest_signature,
+            oldest_block_time,
+        })
+    }
+}
+
+async fn walk_signatures(
+    app_state: &AppState,
+    job_id: i64,
+    address: &str,
+    before: Option<String>,
+    until: Option<&str>,
+    tx_limit: usize,
+    range: SignatureRange,
+) -> Result<SignatureWalk> {
+    let database = &app_state.database;
+    let rpc = &app_state.rpc;
+    let mut cur_last_signature = before;
+    let mut walk = SignatureWalk::default();
+
+    loop {
+        ensure_not_interrupted(app_state, job_id).await?;
+
+        debug!(before = ?cur_last_signature, until = ?until, "Fetching signatures page");
+        let page_started = Instant::now();
+        let signatures_page = rpc
+            .get_signatures(address, cur_last_signature, until, range)
+            .await?;
+
+        let res_len = signatures_page.response.result.len();
+        walk.total += res_len;
+        let stored = || {
+            signatures_page
+                .response
+                .result
+                .iter()
+                .filter_map(|sig| Some((sig.signature.clone(), sig.block_time?)))
+        };
+        if walk.newest.is_none() {
+            walk.newest = stored().next();
+        }
+        if let Some(oldest) = stored().next_back() {
+            walk.oldest = Some(oldest);
+        }
+
+        info!(
+            raw_page_len = signatures_page.raw_count,
+            page_len = res_len,
+            total = walk.total,
+            reached_cutoff = signatures_page.reached_cutoff,
+            elapsed_ms = page_started.elapsed().as_millis(),
+            "Signatures page received"
+        );
+
+        let inserted = database
+            .write_signatures(&signatures_page.response, address)
+            .await?;
+        debug!(inserted, "Signatures saved");
+        notify_progress(app_state, job_id).await;
+
+        let exhausted =
+            signatures_page.last_signature.is_none() || signatures_page.raw_count < 1000;
+        if signatures_page.reached_cutoff || exhausted || walk.total >= tx_limit {
+            walk.reached_end = exhausted && !signatures_page.reached_cutoff;
+            info!("No more signatures available");
+            break;
+        }
+
+        cur_last_signature = signatures_page.last_signature;
+    }
+
+    Ok(walk)
+}
+
+async fn process_unprocessed_signatures(
+    app_state: &AppState,
+    job_id: i64,
+    address: &str,
+) -> Result<()> {
     let database = &app_state.database;
-    let helius_api = &app_state.helius_api;
+    let rpc = &app_state.rpc;
 
     loop {
+        ensure_not_interrupted(app_state, job_id).await?;
+
         let signatures = database.get_unprocessed_signatures(address, 100).await?;
         info!(count = signatures.len(), "Fetched unprocessed signatures");
 
         if signatures.len().is_zero() {
+            if let Some(retry_at) = database.get_next_signature_retry_at(address).await? {
+                wait_for_signature_retry(app_state, retry_at).await;
+                continue;
+            }
             info!("No more signatures available");
             break;
         }
 
+        let batch_started = Instant::now();
         let tx_fetch_started = Instant::now();
-        let transaction_batch = helius_api.get_transaction(&signatures).await?;
+        let transaction_batch = rpc.get_transaction(&signatures).await?;
         info!(
             count = transaction_batch.transactions.len(),
             failed = transaction_batch.failed_signatures.len(),
@@ -192,31 +478,273 @@ async fn process_unprocessed_signatures(app_state: &AppState, address: &str) ->
 
         let save_started = Instant::now();
         let save_stats = database
-            .save_transaction_data(&transaction_batch.transactions, address)
-            .await?;
-        let marked_processed = database
-            .mark_signatures_processed(address, &transaction_batch.processed_signatures)
+            .save_transaction_batch(
+                &transaction_batch.transactions,
+                address,
+                &transaction_batch.processed_signatures,
+                app_state.raw_payloads.enabled,
+            )
             .await?;
 
         info!(
             transactions_saved = save_stats.transactions,
             token_transfers_saved = save_stats.token_transfers,
-            signatures_marked_processed = marked_processed,
+            raw_payloads_saved = save_stats.raw_payloads,
+            signatures_marked_processed = save_stats.signatures_processed,
             elapsed_ms = save_started.elapsed().as_millis(),
             "Transaction data saved"
         );
+        metrics::record_signature_batch(signatures.len(), batch_started.elapsed());
+        notify_progress(app_state, job_id).await;
 
         if !transaction_batch.errors.is_empty() {
-            warn!(
-                errors = transaction_batch.errors.len(),
-                "Some transaction requests failed in this batch"
-            );
-            return Err(anyhow::anyhow!(
-                "{} transaction request(s) failed",
-                transaction_batch.errors.len()
-            ));
+            record_signature_failures(app_state, address, &transaction_batch.errors).await?;
         }
     }
 
     Ok(())
 }
+
+async fn record_signature_failures(
+    app_state: &AppState,
+    address: &str,
+    errors: &[TransactionFetchError],
+) -> Result<()> {
+    let settings = &app_state.signature_retries;
+    let failures = app_state
+        .database
+        .record_signature_failures(
+            address,
+            errors,
+            i32::try_from(settings.max_attempts).unwrap_or(i32::MAX),
+            Duration::from_millis(settings.base_delay_ms),
+            Duration::from_millis(settings.max_delay_ms),
+        )
+        .await?;
+
+    let dead = failures.iter().filter(|failure| failure.is_dead).count();
+    metrics::record_signature_failures("dead", dead);
+    metrics::record_signature_failures("retry", failures.len() - dead);
+
+    for failure in failures.iter().filter(|failure| failure.is_dead) {
+        warn!(
+            signature = %failure.signature,
+            attempts = failure.attempts,
+            "Signature fetch attempts exhausted, dead-lettered"
+        );
+    }
+    warn!(
+        errors = errors.len(),
+        retrying = failures.len() - dead,
+        dead,
+        "Some transaction requests failed in this batch"
+    );
+
+    Ok(())
+}
+
+/// Sleeps until the next failed signature is due, waking early on shutdown and at least
+/// every poll interval so cancellation is still noticed.
+async fn wait_for_signature_retry(app_state: &AppState, retry_at: chrono::DateTime<chrono::Utc>) {
+    let delay = (retry_at - chrono::Utc::now())
+        .to_std()
+        .unwrap_or_default()
+        .min(SIGNATURE_RETRY_POLL_INTERVAL);
+    debug!(?delay, %retry_at, "Waiting for failed signatures to become due");
+
+    tokio::select! {
+        () = tokio::time::sleep(delay) => {}
+        () = app_state.shutdown.triggered() => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auth::ApiKeyRateLimiters,
+        db::Database,
+        events::JobEvents,
+        liveness::WorkerLiveness,
+        requests::InMemoryRpc,
+        settings::{
+            DeduplicationSettings, RawPayloadSettings, SignatureRetrySettings, WebhookSettings,
+        },
+        shutdown::Shutdown,
+    };
+    use chrono::Utc;
+    use pretty_assertions::assert_eq;
+    use serde_json::{Value, json};
+    use sqlx::postgres::PgPool;
+
+    const OWNER: &str = "FzkNRHqeGZDmqjf1pgYM2G5iNMnDgfYAZ1KyhEH8R3TL";
+    const WORKER_ID: u32 = 1;
+
+    fn transaction_payload(signature: &str) -> Result<Value> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/helius/transactions/success.json");
+        let mut value: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        value["result"]["transaction"]["signatures"] = json!([signature]);
+        Ok(value["result"].take())
+    }
+
+    fn app_state(pool: PgPool, rpc: InMemoryRpc) -> AppState {
+        AppState {
+            database: Database::from_pool(pool),
+            rpc: Box::new(rpc),
+            shutdown: Shutdown::new(Duration::from_secs(30)),
+            worker_liveness: WorkerLiveness::new(Duration::from_mins(5)),
+            job_events: JobEvents::new(),
+            api_key_rate_limiters: ApiKeyRateLimiters::new(),
+            deduplication: DeduplicationSettings {
+                enabled: true,
+                ready_ttl_secs: 900,
+                idempotency_key_ttl_secs: 86_400,
+            },
+            signature_retries: SignatureRetrySettings {
+                max_attempts: 2,
+                base_delay_ms: 1,
+                max_delay_ms: 5,
+            },
+            raw_payloads: RawPayloadSettings { enabled: false },
+            webhooks: WebhookSettings {
+                secret: String::new(),
+                max_attempts: 1,
+                poll_interval_secs: 1,
+                request_timeout_secs: 1,
+            },
+        }
+    }
+
+    async fn run_job(app_state: &AppState, tx_limit: i32) -> Result<JobInfo> {
+        let job_id = app_state
+            .database
+            .create_processing_job(OWNER, tx_limit, 24, None, 0)
+            .await?
+            .expect("processing job should be created");
+        let claimed_job = app_state
+            .database
+            .claim_pending_job(WORKER_ID)
+            .await?
+            .expect("pending job should be claimed");
+
+        process_claimed_job(app_state, WORKER_ID, claimed_job).await;
+
+        Ok(app_state
+            .database
+            .get_job_info(job_id, None)
+            .await?
+            .expect("job should exist"))
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_index_signatures_inside_range_and_mark_job_ready(pool: PgPool) -> Result<()> {
+        let now = Utc::now().timestamp() - 60;
+        let rpc = InMemoryRpc::new();
+        rpc.add_signature(OWNER, "sig-new", Some(now));
+        rpc.add_signature(OWNER, "sig-mid", Some(now - 60));
+        rpc.add_signature(OWNER, "sig-old", Some(now - 48 * 3600));
+        rpc.add_transaction("sig-new", transaction_payload("sig-new")?);
+        rpc.add_transaction("sig-mid", transaction_payload("sig-mid")?);
+        let app_state = app_state(pool, rpc);
+
+        let job_info = run_job(&app_state, 1000).await?;
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.total_transactions, 2);
+        assert_eq!(job_info.processed_transactions, 2);
+        assert_eq!(job_info.dead_transactions, 0);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_retry_failed_transaction_and_mark_job_ready_when_retry_succeeds(
+        pool: PgPool,
+    ) -> Result<()> {
+        let now = Utc::now().timestamp() - 60;
+        let rpc = InMemoryRpc::new();
+        rpc.add_signature(OWNER, "sig-flaky", Some(now));
+        rpc.add_transaction("sig-flaky", transaction_payload("sig-flaky")?);
+        rpc.fail_transaction(
+            TransactionFetchError {
+                signature: String::from("sig-flaky"),
+                status_code: Some(500),
+                rpc_code: None,
+                message: String::from("internal error"),
+            },
+            1,
+        );
+        let app_state = app_state(pool, rpc);
+
+        let job_info = run_job(&app_state, 1000).await?;
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.processed_transactions, 1);
+        assert_eq!(job_info.dead_transactions, 0);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_dead_letter_missing_transaction_and_mark_job_ready(pool: PgPool) -> Result<()> {
+        let now = Utc::now().timestamp() - 60;
+        let rpc = InMemoryRpc::new();
+        rpc.add_signature(OWNER, "sig-found", Some(now));
+        rpc.add_signature(OWNER, "sig-missing", Some(now - 60));
+        rpc.add_transaction("sig-found", transaction_payload("sig-found")?);
+        let app_state = app_state(pool, rpc);
+
+        let job_info = run_job(&app_state, 1000).await?;
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.processed_transactions, 1);
+        assert_eq!(job_info.dead_transactions, 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_keep_coverage_until_newer_walk_reaches_stored_history(
+        pool: PgPool,
+    ) -> Result<()> {
+        let now = Utc::now().timestamp() - 3_600;
+        let rpc = InMemoryRpc::new();
+        rpc.add_signature(OWNER, "sig-stored", Some(now - 3_600));
+        rpc.add_transaction("sig-stored", transaction_payload("sig-stored")?);
+        let app_state = app_state(pool, rpc.clone());
+
+        run_job(&app_state, 1000).await?;
+        let stored = app_state
+            .database
+            .get_signature_coverage(OWNER)
+            .await?
+            .expect("first job should record coverage");
+        assert_eq!(stored.newest_signature, "sig-stored");
+
+        for index in 0..1001 {
+            let signature = format!("sig-new-{index:04}");
+            rpc.add_signature(OWNER, &signature, Some(now + index));
+            rpc.add_transaction(&signature, transaction_payload(&signature)?);
+        }
+
+        let job_info = run_job(&app_state, 1000).await?;
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(
+            app_state.database.get_signature_coverage(OWNER).await?,
+            Some(stored.clone())
+        );
+
+        let job_info = run_job(&app_state, 2000).await?;
+        assert_eq!(job_info.total_transactions, 1002);
+        let coverage = app_state
+            .database
+            .get_signature_coverage(OWNER)
+            .await?
+            .expect("coverage should still exist");
+        assert_eq!(coverage.newest_signature, "sig-new-1000");
+        assert_eq!(coverage.oldest_signature, "sig-stored");
+
+        Ok(())
+    }
+}
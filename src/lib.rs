@@ -1,20 +1,42 @@
 #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
 #![allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
 
+pub mod auth;
 pub mod backoff;
 pub mod db;
+pub mod events;
 pub mod indexer;
+pub mod liveness;
 pub mod logging;
+pub mod metrics;
+pub mod reparse;
 pub mod requests;
 pub mod server;
 pub mod settings;
+pub mod shutdown;
 pub mod telemetry;
 pub mod types;
+pub mod webhooks;
 
+use crate::auth::ApiKeyRateLimiters;
 use crate::db::Database;
-use crate::requests::HeliusApi;
+use crate::events::JobEvents;
+use crate::liveness::WorkerLiveness;
+use crate::requests::SolanaRpc;
+use crate::settings::{
+    DeduplicationSettings, RawPayloadSettings, SignatureRetrySettings, WebhookSettings,
+};
+use crate::shutdown::Shutdown;
 
 pub struct AppState {
     pub database: Database,
-    pub helius_api: HeliusApi,
+    pub rpc: Box<dyn SolanaRpc>,
+    pub shutdown: Shutdown,
+    pub worker_liveness: WorkerLiveness,
+    pub job_events: JobEvents,
+    pub api_key_rate_limiters: ApiKeyRateLimiters,
+    pub deduplication: DeduplicationSettings,
+    pub signature_retries: SignatureRetrySettings,
+    pub raw_payloads: RawPayloadSettings,
+    pub webhooks: WebhookSettings,
 }
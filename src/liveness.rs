@@ -0,0 +1,82 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Serialize)]
+pub struct WorkerLivenessReport {
+    pub worker_id: u32,
+    pub last_seen_ms_ago: u128,
+    pub alive: bool,
+}
+
+#[derive(Debug)]
+pub struct WorkerLiveness {
+    last_seen: Mutex<BTreeMap<u32, Instant>>,
+    stale_after: Duration,
+}
+
+impl WorkerLiveness {
+    pub const fn new(stale_after: Duration) -> Self {
+        Self {
+            last_seen: Mutex::new(BTreeMap::new()),
+            stale_after,
+        }
+    }
+
+    pub fn beat(&self, worker_id: u32) {
+        self.last_seen
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(worker_id, Instant::now());
+    }
+
+    pub fn report(&self) -> Vec<WorkerLivenessReport> {
+        self.last_seen
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(&worker_id, last_seen)| {
+                let elapsed = last_seen.elapsed();
+                WorkerLivenessReport {
+                    worker_id,
+                    last_seen_ms_ago: elapsed.as_millis(),
+                    alive: elapsed <= self.stale_after,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_report_workers_in_id_order_when_they_beat() {
+        let liveness = WorkerLiveness::new(Duration::from_mins(1));
+        liveness.beat(2);
+        liveness.beat(1);
+
+        let report = liveness.report();
+
+        assert_eq!(
+            report
+                .iter()
+                .map(|worker| worker.worker_id)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert!(report.iter().all(|worker| worker.alive));
+    }
+
+    #[test]
+    fn should_mark_worker_stale_when_it_stops_beating() {
+        let liveness = WorkerLiveness::new(Duration::ZERO);
+        liveness.beat(1);
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert!(!liveness.report()[0].alive);
+    }
+}
@@ -1,56 +1,279 @@
 use on_chain_event_indexer::{
-    AppState, backoff, db, indexer, requests, server, settings, telemetry,
+    AppState, auth, backoff, db, events, indexer, liveness, metrics, reparse, requests, server,
+    settings, shutdown, telemetry, webhooks,
 };
 
-use crate::settings::Settings;
-use anyhow::Result;
+use crate::settings::{RpcProvider, RpcSettings, Settings};
+use anyhow::{Result, bail};
 use backoff::WorkerBackoff;
+use clap::{Parser, Subcommand};
+use events::JobEvents;
 use indexer::process_claimed_job;
-use requests::HeliusApi;
-use server::create_server;
+use liveness::WorkerLiveness;
+use requests::{HeliusApi, JsonRpcApi, SolanaRpc};
+use server::{AddressProcessing, create_ops_server, create_server};
+use shutdown::Shutdown;
 use std::sync::Arc;
 use tokio::task::JoinHandle;
 use tokio::time::{Duration, sleep};
-use tracing::warn;
+use tracing::{info, warn};
+use webhooks::WebhookDispatcher;
+
+const SHUTDOWN_RELEASE_MARGIN: Duration = Duration::from_secs(5);
+const IDLE_WORKER_WAKEUP_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Parser)]
+#[command(about = "Index Solana address history into PostgreSQL")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the HTTP API and the indexing workers in one process (the default).
+    All,
+    /// Run only the HTTP API.
+    Serve,
+    /// Run only the indexing workers, the lease reaper and the webhook dispatcher.
+    Work,
+    /// Apply database migrations, then exit.
+    Migrate,
+    /// Queue a job for an address and print its id.
+    Enqueue {
+        address: String,
+        #[arg(long, default_value_t = 24)]
+        hours: i32,
+        #[arg(long, default_value_t = 1000)]
+        limit: i32,
+        #[arg(long, default_value_t = 0)]
+        priority: i16,
+    },
+    /// Print a job's status and progress as JSON.
+    Status { job_id: i64 },
+    /// Rebuild transactions and token transfers from stored raw payloads.
+    Reparse {
+        /// Address to reparse; repeat for several. Defaults to every address with stored payloads.
+        #[arg(long)]
+        address: Vec<String>,
+        #[arg(long, default_value_t = 500)]
+        batch_size: i64,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Role {
+    All,
+    Serve,
+    Work,
+}
+
+impl Role {
+    const fn serves_api(self) -> bool {
+        matches!(self, Self::All | Self::Serve)
+    }
+
+    const fn runs_workers(self) -> bool {
+        matches!(self, Self::All | Self::Work)
+    }
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
     let settings = Settings::load()?;
+
+    match cli.command.unwrap_or(Command::All) {
+        Command::All => run(settings, Role::All).await,
+        Command::Serve => run(settings, Role::Serve).await,
+        Command::Work => run(settings, Role::Work).await,
+        Command::Migrate => {
+            connect(&settings).await?.migrate().await?;
+            println!("Database migrated");
+            Ok(())
+        }
+        Command::Enqueue {
+            address,
+            hours,
+            limit,
+            priority,
+        } => {
+            let request = AddressProcessing {
+                address,
+                requested_hours: Some(hours),
+                from: None,
+                to: None,
+                full_history: false,
+                tx_limit: Some(limit),
+                callback_url: None,
+                priority,
+            };
+            request.validate()?;
+
+            let database = connect_migrated(&settings).await?;
+            let Some(job_id) = database
+                .create_processing_job(&request.address, limit, hours, None, priority)
+                .await?
+            else {
+                bail!("job for {} was not created", request.address);
+            };
+            println!("{job_id}");
+            Ok(())
+        }
+        Command::Status { job_id } => {
+            let database = connect_migrated(&settings).await?;
+            let Some(job_info) = database.get_job_info(job_id, None).await? else {
+                bail!("job {job_id} not found");
+            };
+            println!("{}", serde_json::to_string_pretty(&job_info)?);
+            Ok(())
+        }
+        Command::Reparse {
+            address,
+            batch_size,
+        } => {
+            let database = connect_migrated(&settings).await?;
+            let addresses = if address.is_empty() {
+                database.get_raw_transaction_owners().await?
+            } else {
+                address
+            };
+
+            for address in addresses {
+                let stats = reparse::reparse_address(&database, &address, batch_size).await?;
+                println!(
+                    "Reparsed {address}: {} transactions, {} token transfers, {} undecodable payloads",
+                    stats.transactions, stats.token_transfers, stats.failed_payloads
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Opens a single-connection pool for one-shot commands. Only `migrate` changes the schema;
+/// the other commands refuse to run against a database that is behind.
+async fn connect(settings: &Settings) -> Result<db::Database> {
+    db::Database::new(settings.database.url.clone(), 1).await
+}
+
+async fn connect_migrated(settings: &Settings) -> Result<db::Database> {
+    let database = connect(settings).await?;
+    database.ensure_migrated().await?;
+    Ok(database)
+}
+
+fn rpc_client(settings: &RpcSettings) -> Result<Box<dyn SolanaRpc>> {
+    Ok(match settings.provider {
+        RpcProvider::Helius => Box::new(HeliusApi::new(
+            settings.rps,
+            settings.max_concurrent,
+            settings.max_rate_limit_retries,
+            &settings.url,
+            &settings.api_key,
+        )?),
+        RpcProvider::JsonRpc => Box::new(JsonRpcApi::new(
+            settings.rps,
+            settings.max_concurrent,
+            settings.max_rate_limit_retries,
+            settings.url.clone(),
+        )?),
+    })
+}
+
+async fn run(settings: Settings, role: Role) -> Result<()> {
     telemetry::init(&settings.logging.level, &settings.logging.dir)?;
     settings.log_loaded_settings();
+    metrics::init()?;
+    info!(?role, "Starting indexer");
+
+    let shutdown = Shutdown::new(Duration::from_secs(settings.workers.shutdown_grace_secs));
+    tokio::spawn(shutdown.clone().listen_for_signals());
 
     let app_state = Arc::new(AppState {
-        helius_api: HeliusApi::new(
-            settings.rpc.rps,
-            settings.rpc.max_concurrent,
-            settings.rpc.max_rate_limit_retries,
-            settings.rpc_endpoint(),
-        )?,
+        rpc: rpc_client(&settings.rpc)?,
         database: db::Database::new(settings.database.url, settings.database.max_connections)
             .await?,
+        shutdown: shutdown.clone(),
+        worker_liveness: WorkerLiveness::new(Duration::from_secs(
+            settings.workers.lease_timeout_secs,
+        )),
+        job_events: JobEvents::new(),
+        api_key_rate_limiters: auth::ApiKeyRateLimiters::new(),
+        deduplication: settings.deduplication.clone(),
+        signature_retries: settings.signature_retries.clone(),
+        raw_payloads: settings.raw_payloads.clone(),
+        webhooks: settings.webhooks.clone(),
     });
 
     app_state.database.migrate().await?;
 
-    let server_handle = tokio::spawn(create_server(
-        Arc::clone(&app_state),
-        settings.server.bind,
-        settings.server.cors_allowed_origins,
-    ));
+    tokio::spawn({
+        let state = Arc::clone(&app_state);
+        async move {
+            state
+                .job_events
+                .clone()
+                .listen(&state.database, state.shutdown.clone())
+                .await;
+        }
+    });
+
+    let server_handle = {
+        let server_shutdown = shutdown.clone();
+        let app_state = Arc::clone(&app_state);
+        let bind = settings.server.bind;
+        let cors_allowed_origins = settings.server.cors_allowed_origins;
+        tokio::spawn(async move {
+            let res = if role.serves_api() {
+                create_server(app_state, bind, cors_allowed_origins).await
+            } else {
+                create_ops_server(app_state, bind).await
+            };
+            server_shutdown.trigger();
+            res
+        })
+    };
 
     let mut worker_handles: Vec<JoinHandle<Result<()>>> = Vec::new();
-    for worker_id in 1..=settings.workers.count {
-        let state = Arc::clone(&app_state);
-        worker_handles.push(tokio::spawn(worker_loop(state, worker_id)));
-        sleep(Duration::from_millis(700)).await;
+    if role.runs_workers() {
+        if settings.webhooks.signing_enabled() {
+            tokio::spawn(WebhookDispatcher::new(&settings.webhooks)?.run(Arc::clone(&app_state)));
+        } else {
+            warn!("Webhook dispatcher not started because webhooks.secret is empty");
+        }
+
+        tokio::spawn(reaper_loop(
+            Arc::clone(&app_state),
+            Duration::from_secs(settings.workers.reaper_interval_secs),
+            settings.workers.lease_timeout_secs,
+            settings.workers.max_attempts,
+            Duration::from_secs(settings.deduplication.idempotency_key_ttl_secs),
+        ));
+
+        for worker_id in 1..=settings.workers.count {
+            let state = Arc::clone(&app_state);
+            worker_handles.push(tokio::spawn(worker_loop(state, worker_id)));
+            sleep(Duration::from_millis(700)).await;
+        }
     }
 
+    let drained = async {
+        let server_res = server_handle.await;
+        futures::future::join_all(worker_handles).await;
+        server_res
+    };
+    let hard_deadline = async {
+        shutdown.deadline().await;
+        sleep(SHUTDOWN_RELEASE_MARGIN).await;
+    };
+
     tokio::select! {
-        res = server_handle => {
-            warn!("API server exited: {:?}", res);
+        server_res = drained => {
+            info!(?server_res, "API server and workers stopped");
         }
-        _ = futures::future::join_all(worker_handles) => {
-            warn!("All workers exited");
+        () = hard_deadline => {
+            warn!("Shutdown deadline exceeded, exiting with workers still running");
         }
     }
 
@@ -62,6 +285,16 @@ async fn worker_loop(app_state: Arc<AppState>, worker_id: u32) -> Result<()> {
     let mut worker_backoff = WorkerBackoff::new(200.0, 2000.0, 2.0);
     loop {
         let claimed_job = loop {
+            app_state.worker_liveness.beat(worker_id);
+            if app_state.shutdown.is_triggered() {
+                info!(worker_id, "Worker stopped");
+                return Ok(());
+            }
+
+            let job_queued = app_state.job_events.job_queued();
+            tokio::pin!(job_queued);
+            job_queued.as_mut().enable();
+
             let claimed_job = app_state.database.claim_pending_job(worker_id).await;
 
             match claimed_job {
@@ -69,9 +302,21 @@ async fn worker_loop(app_state: Arc<AppState>, worker_id: u32) -> Result<()> {
                     worker_backoff.reset();
                     break job;
                 }
+                Ok(None) if app_state.job_events.is_listening() => {
+                    // The periodic wakeup only keeps the liveness beat fresh while idle.
+                    worker_backoff.reset();
+                    tokio::select! {
+                        () = job_queued => {}
+                        () = sleep(IDLE_WORKER_WAKEUP_INTERVAL) => {}
+                        () = app_state.shutdown.triggered() => {}
+                    }
+                }
                 Ok(None) => {
                     let delay = worker_backoff.step_and_get_sleep_duration();
-                    sleep(delay).await;
+                    tokio::select! {
+                        () = sleep(delay) => {}
+                        () = app_state.shutdown.triggered() => {}
+                    }
                 }
                 Err(err) => {
                     tracing::warn!(%err, worker_id, "Failed to claim pending job");
@@ -84,3 +329,51 @@ async fn worker_loop(app_state: Arc<AppState>, worker_id: u32) -> Result<()> {
         process_claimed_job(&app_state, worker_id, claimed_job).await;
     }
 }
+
+#[tracing::instrument(skip(app_state))]
+async fn reaper_loop(
+    app_state: Arc<AppState>,
+    interval: Duration,
+    lease_timeout_secs: u64,
+    max_attempts: u32,
+    idempotency_key_ttl: Duration,
+) {
+    let lease_timeout_secs = i64::try_from(lease_timeout_secs).unwrap_or(i64::MAX);
+    let max_attempts = i32::try_from(max_attempts).unwrap_or(i32::MAX);
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            () = app_state.shutdown.triggered() => break,
+        }
+
+        match app_state
+            .database
+            .reap_expired_jobs(lease_timeout_secs, max_attempts)
+            .await
+        {
+            Ok(reaped) => {
+                for job in reaped {
+                    warn!(
+                        job_id = job.job_id,
+                        status = %job.status,
+                        attempts = job.attempts,
+                        "Reaped job with expired lease"
+                    );
+                }
+            }
+            Err(err) => warn!(%err, "Failed to reap expired jobs"),
+        }
+
+        match app_state
+            .database
+            .purge_expired_idempotency_keys(idempotency_key_ttl)
+            .await
+        {
+            Ok(0) => {}
+            Ok(purged) => info!(purged, "Purged expired idempotency keys"),
+            Err(err) => warn!(%err, "Failed to purge expired idempotency keys"),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+static PROMETHEUS_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+pub fn init() -> Result<()> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix(String::from("_seconds")), LATENCY_BUCKETS)?
+        .install_recorder()?;
+    let _ = PROMETHEUS_HANDLE.set(handle);
+
+    tracing::info!("Metrics recorder installed");
+    Ok(())
+}
+
+pub fn render() -> Option<String> {
+    PROMETHEUS_HANDLE.get().map(PrometheusHandle::render)
+}
+
+pub fn record_rpc_request(
+    provider: &'static str,
+    method: &'static str,
+    status: String,
+    elapsed: Duration,
+) {
+    ::metrics::counter!(
+        "rpc_requests_total",
+        "provider" => provider,
+        "method" => method,
+        "status" => status
+    )
+    .increment(1);
+    ::metrics::histogram!(
+        "rpc_request_duration_seconds",
+        "provider" => provider,
+        "method" => method
+    )
+    .record(elapsed.as_secs_f64());
+}
+
+pub fn record_rate_limit(provider: &'static str) {
+    ::metrics::counter!("rpc_rate_limits_total", "provider" => provider).increment(1);
+}
+
+pub fn record_rpc_retry(provider: &'static str, method: &'static str) {
+    ::metrics::counter!("rpc_retries_total", "provider" => provider, "method" => method)
+        .increment(1);
+}
+
+pub fn record_job_claimed() {
+    ::metrics::counter!("indexer_jobs_claimed_total").increment(1);
+}
+
+pub fn record_job_finished(outcome: &'static str) {
+    ::metrics::counter!("indexer_jobs_finished_total", "outcome" => outcome).increment(1);
+}
+
+pub fn record_signature_batch(signatures: usize, elapsed: Duration) {
+    ::metrics::counter!("indexer_signatures_processed_total").increment(signatures as u64);
+    ::metrics::histogram!("indexer_batch_duration_seconds").record(elapsed.as_secs_f64());
+}
+
+pub fn record_auth_rejection(reason: &'static str) {
+    ::metrics::counter!("api_auth_rejections_total", "reason" => reason).increment(1);
+}
+
+pub fn record_webhook_delivery(outcome: &'static str) {
+    ::metrics::counter!("indexer_webhook_deliveries_total", "outcome" => outcome).increment(1);
+}
+
+pub fn record_signature_failures(outcome: &'static str, count: usize) {
+    ::metrics::counter!("indexer_signature_failures_total", "outcome" => outcome)
+        .increment(count as u64);
+}
+
+pub async fn observe_db_query<T, F>(
+    module: &'static str,
+    method: &'static str,
+    query: F,
+) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    let started = Instant::now();
+    let result = query.await;
+
+    ::metrics::histogram!("db_query_duration_seconds", "module" => module, "method" => method)
+        .record(started.elapsed().as_secs_f64());
+    if result.is_err() {
+        ::metrics::counter!("db_query_errors_total", "module" => module, "method" => method)
+            .increment(1);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_rpc_and_job_metrics_when_recorded() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+
+        ::metrics::with_local_recorder(&recorder, || {
+            record_rpc_request(
+                "helius",
+                "getTransaction",
+                String::from("429"),
+                Duration::from_millis(20),
+            );
+            record_rpc_retry("helius", "getTransaction");
+            record_job_finished("ready");
+            record_signature_batch(100, Duration::from_secs(1));
+        });
+
+        let rendered = handle.render();
+        assert!(rendered.contains(
+            r#"rpc_requests_total{provider="helius",method="getTransaction",status="429"} 1"#
+        ));
+        assert!(
+            rendered.contains(r#"rpc_retries_total{provider="helius",method="getTransaction"} 1"#)
+        );
+        assert!(rendered.contains(r#"indexer_jobs_finished_total{outcome="ready"} 1"#));
+        assert!(rendered.contains("indexer_signatures_processed_total 100"));
+    }
+}
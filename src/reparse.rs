@@ -0,0 +1,69 @@
+use crate::db::Database;
+use crate::logging::mask_addr;
+use crate::requests::TransactionResult;
+
+use anyhow::Result;
+use std::time::Instant;
+use tracing::{info, instrument, warn};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReparseStats {
+    pub transactions: u64,
+    pub token_transfers: u64,
+    pub failed_payloads: u64,
+}
+
+/// Rebuilds an address's `transactions` and `token_transfers` rows from stored raw payloads.
+///
+/// Uses the current parser and makes no RPC calls. Signatures processed before payloads
+/// were stored are left as they are.
+#[instrument(skip(database), fields(address = %mask_addr(address), batch_size))]
+pub async fn reparse_address(
+    database: &Database,
+    address: &str,
+    batch_size: i64,
+) -> Result<ReparseStats> {
+    let started = Instant::now();
+    let mut stats = ReparseStats::default();
+    let mut after_signature: Option<String> = None;
+
+    loop {
+        let raw_transactions = database
+            .get_raw_transactions(address, after_signature.as_deref(), batch_size)
+            .await?;
+        let Some(last) = raw_transactions.last() else {
+            break;
+        };
+        after_signature = Some(last.signature.clone());
+
+        let mut transactions = Vec::with_capacity(raw_transactions.len());
+        for raw in raw_transactions {
+            match TransactionResult::from_payload(raw.payload) {
+                Ok(mut transaction) => {
+                    transaction.calculate_token_transfer();
+                    transactions.push(transaction);
+                }
+                Err(err) => {
+                    stats.failed_payloads += 1;
+                    warn!(%err, signature = %raw.signature, "Failed to decode stored payload");
+                }
+            }
+        }
+
+        let save_stats = database
+            .replace_transaction_data(&transactions, address)
+            .await?;
+        stats.transactions += save_stats.transactions;
+        stats.token_transfers += save_stats.token_transfers;
+    }
+
+    info!(
+        transactions = stats.transactions,
+        token_transfers = stats.token_transfers,
+        failed_payloads = stats.failed_payloads,
+        elapsed_ms = started.elapsed().as_millis(),
+        "Address reparsed from stored payloads"
+    );
+
+    Ok(stats)
+}
@@ -1,5 +1,13 @@
 pub mod client;
+pub mod helius;
+pub mod memory;
+pub mod rpc;
 pub mod types;
 
-pub use client::HeliusApi;
-pub use types::{RpcResponse, TokenTransferChange, TransactionInfo, TransactionResult};
+pub use client::{JsonRpcApi, SignatureRange, SignaturesPage};
+pub use helius::HeliusApi;
+pub use memory::InMemoryRpc;
+pub use rpc::SolanaRpc;
+pub use types::{
+    RpcResponse, TokenTransferChange, TransactionFetchError, TransactionInfo, TransactionResult,
+};
@@ -2,7 +2,7 @@ use std::time::{Duration, Instant};
 
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Utc};
-use futures::{StreamExt, stream};
+use futures::{StreamExt, future::BoxFuture, stream};
 use governor::{
     Quota, RateLimiter,
     clock::DefaultClock,
@@ -21,10 +21,12 @@ use tracing::{Instrument, debug, info, instrument, warn};
 
 use crate::backoff::WorkerBackoff;
 use crate::logging::mask_addr;
+use crate::metrics;
 
+use super::rpc::SolanaRpc;
 use super::types::{
     ResponseField, RpcEnvelope, RpcResponse, Signature, TransactionBatch, TransactionFetchError,
-    TransactionInfo, TransactionResult,
+    TransactionResult,
 };
 
 struct RpcHttpResponse {
@@ -42,13 +44,37 @@ enum TransactionFetchOutcome {
 }
 
 enum FetchAttempt {
-    Success(TransactionInfo, StatusCode),
+    Success(Box<TransactionResult>, StatusCode),
     RateLimited(TransactionFetchError, Option<Duration>),
     Fatal(TransactionFetchError),
 }
 
+/// Largest page `getSignaturesForAddress` returns.
+pub(super) const SIGNATURES_PAGE_LIMIT: usize = 1000;
+
 type GlobalRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>;
 
+/// Block-time bounds, in unix seconds, for the signatures a job keeps. `to_ts` is
+/// `None` for jobs that run up to the newest signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureRange {
+    pub from_ts: i64,
+    pub to_ts: Option<i64>,
+}
+
+impl SignatureRange {
+    pub fn last_hours(hours: i32) -> Self {
+        Self::last_hours_at(hours, Utc::now().timestamp())
+    }
+
+    pub fn last_hours_at(hours: i32, now_ts: i64) -> Self {
+        Self {
+            from_ts: now_ts - i64::from(hours.max(0)) * 3600,
+            to_ts: None,
+        }
+    }
+}
+
 pub struct SignaturesPage {
     pub response: RpcResponse,
     pub last_signature: Option<String>,
@@ -56,13 +82,19 @@ pub struct SignaturesPage {
     pub reached_cutoff: bool,
 }
 
-struct RecentSignaturesResult {
-    signatures: Vec<Signature>,
-    reached_cutoff: bool,
+pub(super) struct RangeSignaturesResult {
+    pub(super) signatures: Vec<Signature>,
+    pub(super) reached_cutoff: bool,
     skipped_null_block_time: usize,
+    skipped_after_range: usize,
 }
 
-pub struct HeliusApi {
+/// Client for any standard Solana JSON-RPC endpoint.
+///
+/// Applies RPS, concurrency and rate-limit cooldown control. Works with Triton, `QuickNode`,
+/// a local validator, or Helius through [`super::HeliusApi`].
+pub struct JsonRpcApi {
+    provider: &'static str,
     rpc_endpoint: String,
     client: Client,
     rate_limiter: Arc<GlobalRateLimiter>,
@@ -73,7 +105,7 @@ pub struct HeliusApi {
     max_rate_limit_retries: usize,
 }
 
-impl HeliusApi {
+impl JsonRpcApi {
     pub fn new(
         rps: u32,
         max_concurrent: usize,
@@ -93,6 +125,7 @@ impl HeliusApi {
         let client = Client::new();
 
         Ok(Self {
+            provider: "json_rpc",
             rpc_endpoint,
             client,
             rate_limiter,
@@ -103,13 +136,22 @@ impl HeliusApi {
             max_rate_limit_retries,
         })
     }
+
+    /// Sets the `provider` label on this client's RPC metrics.
+    #[must_use]
+    pub const fn with_provider(mut self, provider: &'static str) -> Self {
+        self.provider = provider;
+        self
+    }
+
     #[allow(clippy::too_many_lines)]
-    #[instrument(target = "client", skip(self), fields(address = %mask_addr(address), before = ?last_signature))]
+    #[instrument(target = "client", skip(self), fields(address = %mask_addr(address), before = ?last_signature, until = ?until))]
     pub async fn get_signatures(
         &self,
         address: &str,
         last_signature: Option<String>,
-        requested_hours: i16,
+        until: Option<&str>,
+        range: SignatureRange,
     ) -> Result<SignaturesPage> {
         let body = json!({
             "jsonrpc": "2.0",
@@ -119,7 +161,8 @@ impl HeliusApi {
                 address,
                 {
                     "before": last_signature.as_deref(),
-                    "limit": 1000,
+                    "until": until,
+                    "limit": SIGNATURES_PAGE_LIMIT,
                 }
             ]
         });
@@ -147,6 +190,7 @@ impl HeliusApi {
                             sleep_ms = delay.as_millis(),
                             "Rate limit detected on getSignaturesForAddress, retrying"
                         );
+                        metrics::record_rpc_retry(self.provider, "getSignaturesForAddress");
                         continue;
                     }
 
@@ -169,6 +213,7 @@ impl HeliusApi {
                         sleep_ms = delay.as_millis(),
                         "Rate limit detected on getSignaturesForAddress, retrying"
                     );
+                    metrics::record_rpc_retry(self.provider, "getSignaturesForAddress");
                     continue;
                 }
 
@@ -196,16 +241,17 @@ impl HeliusApi {
             };
             let raw_count = result.len();
             let last_signature = result.last().map(|last| last.signature.clone());
-            let recent_signatures = take_recent_signatures(&result, requested_hours);
-            let filtered_count = recent_signatures.signatures.len();
+            let range_signatures = take_signatures_in_range(&result, range);
+            let filtered_count = range_signatures.signatures.len();
 
             debug!(
                 target: "client",
                 status = ?status,
                 raw_count,
                 filtered_count,
-                reached_cutoff = recent_signatures.reached_cutoff,
-                skipped_null_block_time = recent_signatures.skipped_null_block_time,
+                reached_cutoff = range_signatures.reached_cutoff,
+                skipped_null_block_time = range_signatures.skipped_null_block_time,
+                skipped_after_range = range_signatures.skipped_after_range,
                 elapsed_ms = request_started.elapsed().as_millis(),
                 "Signatures response received"
             );
@@ -218,11 +264,11 @@ impl HeliusApi {
 
             return Ok(SignaturesPage {
                 response: RpcResponse {
-                    result: recent_signatures.signatures,
+                    result: range_signatures.signatures,
                 },
                 last_signature,
                 raw_count,
-                reached_cutoff: recent_signatures.reached_cutoff,
+                reached_cutoff: range_signatures.reached_cutoff,
             });
         }
 
@@ -387,7 +433,7 @@ impl HeliusApi {
             let request_started = Instant::now();
 
             match self.try_fetch_transaction_once(&signature, &body).await {
-                FetchAttempt::Success(tx_info, status) => {
+                FetchAttempt::Success(transaction, status) => {
                     debug!(
                         target: "client",
                         status = ?status,
@@ -398,10 +444,7 @@ impl HeliusApi {
 
                     return TransactionFetchOutcome::Success {
                         signature,
-                        transaction: Box::new(TransactionResult {
-                            result: tx_info,
-                            token_transfer_changes: Vec::new(),
-                        }),
+                        transaction,
                     };
                 }
                 FetchAttempt::RateLimited(fetch_error, retry_after) => {
@@ -418,6 +461,7 @@ impl HeliusApi {
                             sleep_ms = delay.as_millis(),
                             "Rate limit detected on getTransaction, retrying"
                         );
+                        metrics::record_rpc_retry(self.provider, "getTransaction");
                         continue;
                     }
                     return TransactionFetchOutcome::Failed(fetch_error);
@@ -512,8 +556,8 @@ impl HeliusApi {
             }
         };
 
-        let tx_info: TransactionInfo = match serde_json::from_value(result_value) {
-            Ok(tx_info) => tx_info,
+        let transaction = match TransactionResult::from_payload(result_value) {
+            Ok(transaction) => transaction,
             Err(error) => {
                 return FetchAttempt::Fatal(TransactionFetchError {
                     signature: signature.to_string(),
@@ -524,20 +568,34 @@ impl HeliusApi {
             }
         };
 
-        FetchAttempt::Success(tx_info, status)
+        FetchAttempt::Success(Box::new(transaction), status)
     }
 
     async fn send_rpc_request(&self, body: &Value) -> Result<RpcHttpResponse> {
         let _permit = self.acquire_request_slot().await?;
-        let response = self
-            .client
-            .post(&self.rpc_endpoint)
-            .json(body)
-            .send()
-            .await?;
+        let method = Self::rpc_method_label(body);
+        let request_started = Instant::now();
+        let response = match self.client.post(&self.rpc_endpoint).json(body).send().await {
+            Ok(response) => response,
+            Err(err) => {
+                metrics::record_rpc_request(
+                    self.provider,
+                    method,
+                    String::from("error"),
+                    request_started.elapsed(),
+                );
+                return Err(err.into());
+            }
+        };
         let status = response.status();
         let retry_after = Self::parse_retry_after(response.headers());
         let body_text = response.text().await?;
+        metrics::record_rpc_request(
+            self.provider,
+            method,
+            status.as_u16().to_string(),
+            request_started.elapsed(),
+        );
 
         Ok(RpcHttpResponse {
             status,
@@ -546,6 +604,19 @@ impl HeliusApi {
         })
     }
 
+    pub async fn rate_limit_cooldown_remaining(&self) -> Option<Duration> {
+        let rate_limit_until = self.rate_limit_until.lock().await;
+        rate_limit_until.and_then(|until| until.checked_duration_since(Instant::now()))
+    }
+
+    fn rpc_method_label(body: &Value) -> &'static str {
+        match body["method"].as_str() {
+            Some("getSignaturesForAddress") => "getSignaturesForAddress",
+            Some("getTransaction") => "getTransaction",
+            _ => "other",
+        }
+    }
+
     async fn acquire_request_slot(&self) -> Result<OwnedSemaphorePermit> {
         self.wait_for_rate_limit_cooldown().await;
         let permit = self.semaphore.clone().acquire_owned().await?;
@@ -578,6 +649,7 @@ impl HeliusApi {
     }
 
     async fn register_rate_limit(&self, retry_after: Option<Duration>) -> Duration {
+        metrics::record_rate_limit(self.provider);
         let delay = if let Some(delay) = retry_after.filter(|delay| !delay.is_zero()) {
             delay
         } else {
@@ -664,26 +736,53 @@ impl HeliusApi {
     }
 }
 
-fn take_recent_signatures(
-    signatures: &[Signature],
-    requested_hours: i16,
-) -> RecentSignaturesResult {
-    take_recent_signatures_at(signatures, requested_hours, Utc::now().timestamp())
+impl SolanaRpc for JsonRpcApi {
+    fn get_signatures<'a>(
+        &'a self,
+        address: &'a str,
+        last_signature: Option<String>,
+        until: Option<&'a str>,
+        range: SignatureRange,
+    ) -> BoxFuture<'a, Result<SignaturesPage>> {
+        Box::pin(Self::get_signatures(
+            self,
+            address,
+            last_signature,
+            until,
+            range,
+        ))
+    }
+
+    fn get_transaction<'a>(
+        &'a self,
+        signatures: &'a [String],
+    ) -> BoxFuture<'a, Result<TransactionBatch>> {
+        Box::pin(Self::get_transaction(self, signatures))
+    }
+
+    fn rate_limit_cooldown_remaining(&self) -> BoxFuture<'_, Option<Duration>> {
+        Box::pin(Self::rate_limit_cooldown_remaining(self))
+    }
 }
 
-fn take_recent_signatures_at(
+/// Keeps the newest-first `signatures` that fall inside `range`, skipping those newer
+/// than its upper bound and stopping at the first one older than its lower bound.
+pub(super) fn take_signatures_in_range(
     signatures: &[Signature],
-    requested_hours: i16,
-    now_ts: i64,
-) -> RecentSignaturesResult {
-    let cutoff_ts = now_ts - i64::from(requested_hours.max(0)) * 3600;
+    range: SignatureRange,
+) -> RangeSignaturesResult {
+    let cutoff_ts = range.from_ts;
     let input_count = signatures.len();
     let mut filtered = Vec::with_capacity(signatures.len());
     let mut skipped_null_block_time = 0usize;
+    let mut skipped_after_range = 0usize;
     let mut reached_cutoff = false;
 
     for sig in signatures {
         match sig.block_time {
+            Some(ts) if range.to_ts.is_some_and(|to_ts| ts > to_ts) => {
+                skipped_after_range += 1;
+            }
             Some(ts) if ts >= cutoff_ts => filtered.push(Signature {
                 block_time: sig.block_time,
                 signature: sig.signature.clone(),
@@ -700,19 +799,21 @@ fn take_recent_signatures_at(
 
     debug!(
         target: "client",
-        requested_hours,
         cutoff_ts,
+        to_ts = ?range.to_ts,
         input_count,
         filtered_count = filtered.len(),
         skipped_null_block_time,
+        skipped_after_range,
         reached_cutoff,
         "Filtered signatures by requested time window"
     );
 
-    RecentSignaturesResult {
+    RangeSignaturesResult {
         signatures: filtered,
         reached_cutoff,
         skipped_null_block_time,
+        skipped_after_range,
     }
 }
 
@@ -736,7 +837,7 @@ mod tests {
             create_signature("sig-2", Some(now - 60)),
         ];
 
-        let result = take_recent_signatures_at(&signatures, 1, now);
+        let result = take_signatures_in_range(&signatures, SignatureRange::last_hours_at(1, now));
 
         assert_eq!(result.signatures.len(), 2);
         assert_eq!(result.signatures[0].signature, "sig-1");
@@ -755,7 +856,7 @@ mod tests {
             create_signature("sig-after-old", Some(now)),
         ];
 
-        let result = take_recent_signatures_at(&signatures, 1, now);
+        let result = take_signatures_in_range(&signatures, SignatureRange::last_hours_at(1, now));
 
         assert_eq!(result.signatures.len(), 2);
         assert_eq!(result.signatures[0].signature, "sig-1");
@@ -774,7 +875,7 @@ mod tests {
             create_signature("sig-fresh-2", Some(now - 120)),
         ];
 
-        let result = take_recent_signatures_at(&signatures, 1, now);
+        let result = take_signatures_in_range(&signatures, SignatureRange::last_hours_at(1, now));
 
         assert_eq!(result.signatures.len(), 2);
         assert_eq!(result.signatures[0].signature, "sig-fresh");
@@ -787,7 +888,8 @@ mod tests {
     fn should_return_empty_result_for_empty_input() {
         let signatures = [];
 
-        let result = take_recent_signatures_at(&signatures, 1, 10_000);
+        let result =
+            take_signatures_in_range(&signatures, SignatureRange::last_hours_at(1, 10_000));
 
         assert!(result.signatures.is_empty());
         assert!(!result.reached_cutoff);
@@ -801,8 +903,10 @@ mod tests {
         let previous_second = create_signature("sig-prev", Some(now - 1));
         let signatures = [current_second, previous_second];
 
-        let zero_hours = take_recent_signatures_at(&signatures, 0, now);
-        let negative_hours = take_recent_signatures_at(&signatures, -1, now);
+        let zero_hours =
+            take_signatures_in_range(&signatures, SignatureRange::last_hours_at(0, now));
+        let negative_hours =
+            take_signatures_in_range(&signatures, SignatureRange::last_hours_at(-1, now));
 
         assert_eq!(zero_hours.signatures.len(), 1);
         assert_eq!(zero_hours.signatures[0].signature, "sig-now");
@@ -813,10 +917,39 @@ mod tests {
         assert!(negative_hours.reached_cutoff);
     }
 
+    #[test]
+    fn should_skip_signatures_newer_than_range_end_and_stop_at_range_start() {
+        let signatures = [
+            create_signature("sig-after", Some(5_000)),
+            create_signature("sig-end", Some(4_000)),
+            create_signature("sig-inside", Some(3_000)),
+            create_signature("sig-start", Some(2_000)),
+            create_signature("sig-before", Some(1_999)),
+            create_signature("sig-after-before", Some(3_000)),
+        ];
+        let range = SignatureRange {
+            from_ts: 2_000,
+            to_ts: Some(4_000),
+        };
+
+        let result = take_signatures_in_range(&signatures, range);
+
+        assert_eq!(
+            result
+                .signatures
+                .iter()
+                .map(|sig| sig.signature.as_str())
+                .collect::<Vec<_>>(),
+            vec!["sig-end", "sig-inside", "sig-start"]
+        );
+        assert!(result.reached_cutoff);
+        assert_eq!(result.skipped_after_range, 1);
+    }
+
     #[test]
     fn should_truncate_text_to_max_length_and_add_ellipsis() {
         let text = "X".repeat(250);
-        let result = HeliusApi::body_snippet(&text);
+        let result = JsonRpcApi::body_snippet(&text);
 
         assert_eq!(result.len(), 203, "Incorrect length");
         assert!(result.ends_with("..."), "No ellipsis ");
@@ -825,7 +958,7 @@ mod tests {
     #[test]
     fn should_remove_special_ch() {
         let text = "text\n\ntext\r\rtext\n123\n\r";
-        let result = HeliusApi::body_snippet(text);
+        let result = JsonRpcApi::body_snippet(text);
 
         assert_eq!(
             result, "text  text  text 123  ",
@@ -838,7 +971,7 @@ mod tests {
     #[test]
     fn should_not_change_the_short_text() {
         let text = "Short text";
-        let result = HeliusApi::body_snippet(text);
+        let result = JsonRpcApi::body_snippet(text);
 
         assert_eq!(result, "Short text", "The text has been cut off too short");
     }
@@ -846,7 +979,7 @@ mod tests {
     #[test]
     fn should_handle_exactly_limit_length_without_ellipsis() {
         let text = "X".repeat(200);
-        let result = HeliusApi::body_snippet(&text);
+        let result = JsonRpcApi::body_snippet(&text);
 
         assert!(!result.ends_with("..."));
     }
@@ -854,7 +987,7 @@ mod tests {
     #[test]
     fn should_return_none_if_there_no_title() {
         let map = HeaderMap::new();
-        let result = HeliusApi::parse_retry_after(&map);
+        let result = JsonRpcApi::parse_retry_after(&map);
 
         assert!(result.is_none());
     }
@@ -864,7 +997,7 @@ mod tests {
         let mut map = HeaderMap::new();
         map.insert(RETRY_AFTER, "20".parse()?);
 
-        let result = HeliusApi::parse_retry_after(&map);
+        let result = JsonRpcApi::parse_retry_after(&map);
         let correct_result = Duration::new(20, 0);
 
         assert!(result.is_some());
@@ -880,7 +1013,7 @@ mod tests {
         map.insert(RETRY_AFTER, retry_after.parse()?);
 
         let now_ts = DateTime::parse_from_rfc2822("Sat, 10 Apr 2027 09:20:10 GMT")?.timestamp();
-        let result = HeliusApi::parse_retry_after_at(&map, now_ts);
+        let result = JsonRpcApi::parse_retry_after_at(&map, now_ts);
 
         assert_eq!(result, Some(Duration::from_secs(10)));
 
@@ -892,7 +1025,7 @@ mod tests {
         let mut map = HeaderMap::new();
         map.insert(RETRY_AFTER, "Aorstwymt 66654".parse()?);
 
-        let result = HeliusApi::parse_retry_after(&map);
+        let result = JsonRpcApi::parse_retry_after(&map);
 
         assert!(result.is_none());
 
@@ -904,7 +1037,7 @@ mod tests {
         let mut map = HeaderMap::new();
         map.insert(RETRY_AFTER, "".parse()?);
 
-        let result = HeliusApi::parse_retry_after(&map);
+        let result = JsonRpcApi::parse_retry_after(&map);
 
         assert!(result.is_none());
 
@@ -916,7 +1049,7 @@ mod tests {
         let mut map = HeaderMap::new();
         map.insert(RETRY_AFTER, "-55".parse()?);
 
-        let result = HeliusApi::parse_retry_after(&map);
+        let result = JsonRpcApi::parse_retry_after(&map);
 
         assert!(result.is_none());
 
@@ -928,7 +1061,7 @@ mod tests {
         let mut map = HeaderMap::new();
         map.insert(RETRY_AFTER, "9999999999999999999999999999".parse()?);
 
-        let result = HeliusApi::parse_retry_after(&map);
+        let result = JsonRpcApi::parse_retry_after(&map);
 
         assert!(result.is_none());
 
@@ -940,7 +1073,7 @@ mod tests {
         let mut map = HeaderMap::new();
         map.insert(RETRY_AFTER, "24.46".parse()?);
 
-        let result = HeliusApi::parse_retry_after(&map);
+        let result = JsonRpcApi::parse_retry_after(&map);
 
         assert!(result.is_none());
 
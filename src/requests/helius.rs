@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+
+use super::client::{JsonRpcApi, SignatureRange, SignaturesPage};
+use super::rpc::SolanaRpc;
+use super::types::TransactionBatch;
+
+/// Helius RPC. Helius serves standard Solana JSON-RPC and takes the API key as the tail of
+/// the endpoint URL, e.g. `https://mainnet.helius-rpc.com/?api-key=<key>`.
+pub struct HeliusApi {
+    rpc: JsonRpcApi,
+}
+
+impl HeliusApi {
+    pub fn new(
+        rps: u32,
+        max_concurrent: usize,
+        max_rate_limit_retries: usize,
+        url: &str,
+        api_key: &str,
+    ) -> Result<Self> {
+        Ok(Self {
+            rpc: JsonRpcApi::new(
+                rps,
+                max_concurrent,
+                max_rate_limit_retries,
+                format!("{url}{api_key}"),
+            )?
+            .with_provider("helius"),
+        })
+    }
+}
+
+impl SolanaRpc for HeliusApi {
+    fn get_signatures<'a>(
+        &'a self,
+        address: &'a str,
+        last_signature: Option<String>,
+        until: Option<&'a str>,
+        range: SignatureRange,
+    ) -> BoxFuture<'a, Result<SignaturesPage>> {
+        Box::pin(
+            self.rpc
+                .get_signatures(address, last_signature, until, range),
+        )
+    }
+
+    fn get_transaction<'a>(
+        &'a self,
+        signatures: &'a [String],
+    ) -> BoxFuture<'a, Result<TransactionBatch>> {
+        Box::pin(self.rpc.get_transaction(signatures))
+    }
+
+    fn rate_limit_cooldown_remaining(&self) -> BoxFuture<'_, Option<Duration>> {
+        Box::pin(self.rpc.rate_limit_cooldown_remaining())
+    }
+}
@@ -0,0 +1,177 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use anyhow::Result;
+use futures::future::{self, BoxFuture};
+use serde_json::Value;
+
+use super::client::{
+    SIGNATURES_PAGE_LIMIT, SignatureRange, SignaturesPage, take_signatures_in_range,
+};
+use super::rpc::SolanaRpc;
+use super::types::{
+    RpcResponse, Signature, TransactionBatch, TransactionFetchError, TransactionResult,
+};
+
+/// Serves signatures and transactions from memory so the indexer can be exercised
+/// without an RPC endpoint.
+///
+/// Clones share their data, so a test can keep adding history after handing one to `AppState`.
+#[derive(Clone, Default)]
+pub struct InMemoryRpc {
+    state: Arc<Mutex<InMemoryState>>,
+}
+
+#[derive(Default)]
+struct InMemoryState {
+    /// Newest first per address, like `getSignaturesForAddress`.
+    signatures: HashMap<String, Vec<(String, Option<i64>)>>,
+    transactions: HashMap<String, Value>,
+    failures: HashMap<String, (TransactionFetchError, u32)>,
+}
+
+impl InMemoryRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_signature(&self, address: &str, signature: &str, block_time: Option<i64>) {
+        let mut state = self.state();
+        let signatures = state.signatures.entry(address.to_owned()).or_default();
+        signatures.push((signature.to_owned(), block_time));
+        signatures.sort_by_key(|(_, block_time)| Reverse(*block_time));
+        drop(state);
+    }
+
+    /// Stores a `getTransaction` result to be returned for `signature`.
+    pub fn add_transaction(&self, signature: &str, payload: Value) {
+        self.state()
+            .transactions
+            .insert(signature.to_owned(), payload);
+    }
+
+    /// Makes the next `times` fetches of `error.signature` fail with `error`.
+    pub fn fail_transaction(&self, error: TransactionFetchError, times: u32) {
+        self.state()
+            .failures
+            .insert(error.signature.clone(), (error, times));
+    }
+
+    fn state(&self) -> MutexGuard<'_, InMemoryState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn signatures_page(
+        &self,
+        address: &str,
+        last_signature: Option<&str>,
+        until: Option<&str>,
+        range: SignatureRange,
+    ) -> SignaturesPage {
+        let state = self.state();
+        let all = state.signatures.get(address).map_or(&[][..], Vec::as_slice);
+        let start = last_signature.map_or(0, |before| {
+            all.iter()
+                .position(|(signature, _)| signature == before)
+                .map_or(all.len(), |index| index + 1)
+        });
+
+        let page: Vec<Signature> = all[start..]
+            .iter()
+            .take_while(|(signature, _)| until != Some(signature.as_str()))
+            .take(SIGNATURES_PAGE_LIMIT)
+            .map(|(signature, block_time)| Signature {
+                block_time: *block_time,
+                signature: signature.clone(),
+            })
+            .collect();
+        drop(state);
+        let range_signatures = take_signatures_in_range(&page, range);
+
+        SignaturesPage {
+            response: RpcResponse {
+                result: range_signatures.signatures,
+            },
+            last_signature: page.last().map(|last| last.signature.clone()),
+            raw_count: page.len(),
+            reached_cutoff: range_signatures.reached_cutoff,
+        }
+    }
+
+    /// Returns the stored payload for `signature`, or the failure queued for it.
+    fn next_response(&self, signature: &str) -> Result<Value, TransactionFetchError> {
+        let mut state = self.state();
+        if let Some((error, remaining)) = state.failures.get_mut(signature)
+            && *remaining > 0
+        {
+            *remaining -= 1;
+            return Err(error.clone());
+        }
+
+        state
+            .transactions
+            .get(signature)
+            .cloned()
+            .ok_or_else(|| fetch_error(signature, "rpc result is null"))
+    }
+
+    fn transaction_batch(&self, signatures: &[String]) -> TransactionBatch {
+        let mut batch = TransactionBatch {
+            transactions: Vec::new(),
+            processed_signatures: Vec::new(),
+            failed_signatures: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        for signature in signatures {
+            let fetched = self.next_response(signature).and_then(|payload| {
+                TransactionResult::from_payload(payload)
+                    .map_err(|err| fetch_error(signature, &err.to_string()))
+            });
+
+            match fetched {
+                Ok(mut transaction) => {
+                    transaction.calculate_token_transfer();
+                    batch.transactions.push(transaction);
+                    batch.processed_signatures.push(signature.clone());
+                }
+                Err(error) => {
+                    batch.failed_signatures.push(signature.clone());
+                    batch.errors.push(error);
+                }
+            }
+        }
+
+        batch
+    }
+}
+
+fn fetch_error(signature: &str, message: &str) -> TransactionFetchError {
+    TransactionFetchError {
+        signature: signature.to_owned(),
+        status_code: None,
+        rpc_code: None,
+        message: message.to_owned(),
+    }
+}
+
+impl SolanaRpc for InMemoryRpc {
+    fn get_signatures<'a>(
+        &'a self,
+        address: &'a str,
+        last_signature: Option<String>,
+        until: Option<&'a str>,
+        range: SignatureRange,
+    ) -> BoxFuture<'a, Result<SignaturesPage>> {
+        let page = self.signatures_page(address, last_signature.as_deref(), until, range);
+        Box::pin(future::ready(Ok(page)))
+    }
+
+    fn get_transaction<'a>(
+        &'a self,
+        signatures: &'a [String],
+    ) -> BoxFuture<'a, Result<TransactionBatch>> {
+        Box::pin(future::ready(Ok(self.transaction_batch(signatures))))
+    }
+}
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+
+use super::client::{SignatureRange, SignaturesPage};
+use super::types::TransactionBatch;
+
+/// The RPC calls the indexer needs from a Solana provider.
+///
+/// Methods return boxed futures so `AppState` can hold any provider as a trait object.
+pub trait SolanaRpc: Send + Sync {
+    /// Fetches one `getSignaturesForAddress` page older than `last_signature` and newer than
+    /// `until`, keeping only the signatures inside `range`.
+    fn get_signatures<'a>(
+        &'a self,
+        address: &'a str,
+        last_signature: Option<String>,
+        until: Option<&'a str>,
+        range: SignatureRange,
+    ) -> BoxFuture<'a, Result<SignaturesPage>>;
+
+    /// Fetches the given transactions. Per-signature failures are reported in the batch
+    /// rather than as an `Err`.
+    fn get_transaction<'a>(
+        &'a self,
+        signatures: &'a [String],
+    ) -> BoxFuture<'a, Result<TransactionBatch>>;
+
+    /// Time left in the provider's rate-limit cooldown, if one is active.
+    fn rate_limit_cooldown_remaining(&self) -> BoxFuture<'_, Option<Duration>> {
+        Box::pin(async { None })
+    }
+}
@@ -83,6 +83,10 @@ pub struct TransactionResult {
 
     #[serde(skip)]
     pub token_transfer_changes: Vec<TokenTransferChange>,
+
+    /// The untouched `getTransaction` result, kept so it can be stored for reparsing.
+    #[serde(skip)]
+    pub raw_payload: Option<Value>,
 }
 
 #[derive(Debug, Clone)]
@@ -274,13 +278,54 @@ pub struct TokenTransferChange {
     pub decimals: Option<u8>,
     pub transfer_type: String,
     pub asset_type: String,
-    pub direction: String,
     pub authority: Option<String>,
     pub instruction_idx: Option<i32>,
     pub inner_idx: Option<i32>,
 }
 
+impl TokenTransferChange {
+    pub fn direction_for(&self, tracked_owner: &str) -> &'static str {
+        let is_tracked = |owner: Option<&String>, token_account: Option<&String>| {
+            owner.is_some_and(|owner| owner == tracked_owner)
+                || token_account.is_some_and(|account| account == tracked_owner)
+        };
+        let from_tracked = is_tracked(
+            self.source_owner.as_ref(),
+            self.source_token_account.as_ref(),
+        );
+        let to_tracked = is_tracked(
+            self.destination_owner.as_ref(),
+            self.destination_token_account.as_ref(),
+        );
+
+        match (from_tracked, to_tracked) {
+            (true, true) => "self",
+            (true, false) => "out",
+            (false, true) => "in",
+            (false, false) => "external",
+        }
+    }
+}
+
 impl TransactionResult {
+    /// Decodes a `getTransaction` result while keeping the raw JSON alongside it.
+    pub fn from_payload(payload: Value) -> serde_json::Result<Self> {
+        Ok(Self {
+            result: TransactionInfo::deserialize(&payload)?,
+            token_transfer_changes: Vec::new(),
+            raw_payload: Some(payload),
+        })
+    }
+
+    pub fn signature(&self) -> &str {
+        self.result
+            .transaction
+            .signatures
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
     pub fn num_signers(&self) -> i32 {
         if let Some(header) = &self.result.transaction.message.header {
             return i32::from(header.required_signatures);
@@ -428,7 +473,6 @@ impl TransactionResult {
             decimals: Some(9),
             transfer_type,
             asset_type: String::from("native"),
-            direction: String::from("unknown"),
             authority: info.authority.clone(),
             instruction_idx: Some(instruction_idx),
             inner_idx,
@@ -522,7 +566,6 @@ impl TransactionResult {
             decimals,
             transfer_type,
             asset_type: String::from("spl"),
-            direction: String::from("unknown"),
             authority: info.authority.clone(),
             instruction_idx: Some(instruction_idx),
             inner_idx,
@@ -929,4 +972,58 @@ mod tests {
 
         assert!(matches!(error.classify(), Category::Syntax | Category::Eof));
     }
+
+    fn transfer_change(
+        source_owner: Option<&str>,
+        destination_owner: Option<&str>,
+        source_token_account: Option<&str>,
+        destination_token_account: Option<&str>,
+    ) -> TokenTransferChange {
+        TokenTransferChange {
+            token_mint: None,
+            token_program: None,
+            source_owner: source_owner.map(str::to_string),
+            destination_owner: destination_owner.map(str::to_string),
+            source_token_account: source_token_account.map(str::to_string),
+            destination_token_account: destination_token_account.map(str::to_string),
+            amount_raw: 1,
+            amount_ui: None,
+            decimals: None,
+            transfer_type: String::from("transfer"),
+            asset_type: String::from("spl"),
+            authority: None,
+            instruction_idx: Some(0),
+            inner_idx: None,
+        }
+    }
+
+    #[test]
+    fn should_classify_direction_relative_to_tracked_owner() {
+        let change = transfer_change(
+            Some("alice"),
+            Some("bob"),
+            Some("alice-ata"),
+            Some("bob-ata"),
+        );
+
+        assert_eq!(change.direction_for("alice"), "out");
+        assert_eq!(change.direction_for("bob"), "in");
+        assert_eq!(change.direction_for("carol"), "external");
+    }
+
+    #[test]
+    fn should_classify_direction_as_self_when_both_sides_belong_to_tracked_owner() {
+        let change = transfer_change(Some("alice"), Some("alice"), Some("ata-1"), Some("ata-2"));
+
+        assert_eq!(change.direction_for("alice"), "self");
+    }
+
+    #[test]
+    fn should_match_token_accounts_when_owners_are_unknown() {
+        let change = transfer_change(None, None, Some("source-ata"), Some("destination-ata"));
+
+        assert_eq!(change.direction_for("source-ata"), "out");
+        assert_eq!(change.direction_for("destination-ata"), "in");
+        assert_eq!(change.direction_for("other"), "external");
+    }
 }
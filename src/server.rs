@@ -3,26 +3,415 @@ use std::sync::Arc;
 use anyhow::Result;
 use axum::{
     Router,
-    extract::{Json, Path, State},
-    http,
-    response::IntoResponse,
+    extract::{Extension, Json, Path, Query, State, rejection::JsonRejection},
+    http::{self, HeaderMap},
+    middleware,
+    response::{
+        IntoResponse,
+        sse::{Event, KeepAlive, Sse},
+    },
     routing::{get, post},
 };
+use chrono::{DateTime, Utc};
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::net::SocketAddr;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
 use tower_http::cors::{Any, CorsLayer};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::AppState;
+use crate::auth::require_api_key;
+use crate::db::jobs::{ConcurrentJobLimitExceeded, IdempotencyKeyMismatch};
 use crate::logging::mask_addr;
+use crate::settings::{DeduplicationSettings, WebhookSettings};
+use crate::types::JobInfo;
+use crate::types::{
+    ApiKey, ApiKeyLimits, AssetTypeFilter, ChartBucket, CreatedJob, JobDedup, JobTransition,
+    JobWindow, NewJob, TransactionCursor, TransactionFilter, TransactionStatusFilter,
+    TransferCursor, TransferFilter, TransferTypeFilter,
+};
+use crate::webhooks::is_internal_ip;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AddressProcessing {
     pub address: String,
-    pub requested_hours: i16,
-    #[serde(rename = "txLimit")]
-    pub tx_limit: i16,
+    #[serde(default)]
+    pub requested_hours: Option<i32>,
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    /// Indexes the address's entire history; `txLimit` may then be omitted for no cap.
+    #[serde(default)]
+    pub full_history: bool,
+    #[serde(rename = "txLimit", default)]
+    pub tx_limit: Option<i32>,
+    #[serde(default)]
+    pub callback_url: Option<String>,
+    #[serde(default)]
+    pub priority: i16,
+}
+
+pub const MAX_REQUESTED_HOURS: i32 = 24 * 365 * 10;
+pub const MAX_TX_LIMIT: i32 = 10_000_000;
+pub const MAX_PRIORITY: i16 = 9;
+pub const MAX_BATCH_SIZE: usize = 500;
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl AddressProcessing {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Err(err) = Pubkey::from_str(&self.address) {
+            return Err(ValidationError {
+                field: "address",
+                message: format!("must be a base58-encoded 32-byte public key ({err})"),
+            });
+        }
+
+        self.validate_window()?;
+
+        match self.tx_limit {
+            Some(tx_limit) if !(1..=MAX_TX_LIMIT).contains(&tx_limit) => {
+                return Err(ValidationError {
+                    field: "txLimit",
+                    message: format!("must be between 1 and {MAX_TX_LIMIT}"),
+                });
+            }
+            None if !self.full_history => {
+                return Err(ValidationError {
+                    field: "txLimit",
+                    message: String::from("is required unless full_history is set"),
+                });
+            }
+            _ => {}
+        }
+
+        if !(0..=MAX_PRIORITY).contains(&self.priority) {
+            return Err(ValidationError {
+                field: "priority",
+                message: format!("must be between 0 and {MAX_PRIORITY}"),
+            });
+        }
+
+        if let Some(callback_url) = &self.callback_url {
+            validate_callback_url(callback_url)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rejects `callback_url` when the server has no secret to sign deliveries with.
+    pub fn validate_callback_allowed(&self, webhooks_enabled: bool) -> Result<(), ValidationError> {
+        if self.callback_url.is_some() && !webhooks_enabled {
+            return Err(ValidationError {
+                field: "callback_url",
+                message: String::from("is not accepted because webhooks are disabled"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Accepts either `requested_hours`, a closed `from`/`to` range in the past, or
+    /// `full_history` on its own.
+    fn validate_window(&self) -> Result<(), ValidationError> {
+        if self.full_history {
+            if self.requested_hours.is_some() || self.from.is_some() || self.to.is_some() {
+                return Err(ValidationError {
+                    field: "full_history",
+                    message: String::from("cannot be combined with requested_hours, from or to"),
+                });
+            }
+            return Ok(());
+        }
+
+        let (field, message) = match (self.requested_hours, self.from, self.to) {
+            (Some(hours), None, None) if (1..=MAX_REQUESTED_HOURS).contains(&hours) => {
+                return Ok(());
+            }
+            (Some(_), None, None) => (
+                "requested_hours",
+                format!("must be between 1 and {MAX_REQUESTED_HOURS}"),
+            ),
+            (None, Some(from), Some(to)) if from >= to => {
+                ("to", String::from("must be after from"))
+            }
+            (None, Some(_), Some(to)) if to > Utc::now() => {
+                ("to", String::from("must not be in the future"))
+            }
+            (None, Some(_), Some(_)) => return Ok(()),
+            (Some(_), _, _) => (
+                "requested_hours",
+                String::from("cannot be combined with from and to"),
+            ),
+            (None, Some(_), None) => ("to", String::from("is required together with from")),
+            (None, None, Some(_)) => ("from", String::from("is required together with to")),
+            (None, None, None) => (
+                "requested_hours",
+                String::from("is required unless from and to are given"),
+            ),
+        };
+
+        Err(ValidationError { field, message })
+    }
+
+    /// The window this request indexes; only meaningful once [`Self::validate`] passed.
+    pub fn window(&self) -> JobWindow {
+        match (self.from, self.to) {
+            _ if self.full_history => JobWindow::FullHistory,
+            (Some(from), Some(to)) => JobWindow::Range { from, to },
+            _ => JobWindow::Recent {
+                hours: self.requested_hours.unwrap_or_default(),
+            },
+        }
+    }
+
+    pub fn validate_limits(&self, limits: &ApiKeyLimits) -> Result<(), ValidationError> {
+        if (self.full_history || self.tx_limit.is_none()) && !limits.allow_full_history {
+            return Err(ValidationError {
+                field: "full_history",
+                message: String::from("is not allowed for this API key"),
+            });
+        }
+
+        let max_window_secs = i64::from(limits.max_requested_hours) * 3600;
+        let exceeds_window = match self.window() {
+            JobWindow::Recent { hours } => hours > limits.max_requested_hours,
+            JobWindow::Range { from, to } => (to - from).num_seconds() > max_window_secs,
+            JobWindow::FullHistory => false,
+        };
+        if exceeds_window {
+            return Err(ValidationError {
+                field: if self.from.is_some() {
+                    "from"
+                } else {
+                    "requested_hours"
+                },
+                message: format!(
+                    "exceeds the API key limit of {} hours",
+                    limits.max_requested_hours
+                ),
+            });
+        }
+
+        if self
+            .tx_limit
+            .is_some_and(|tx_limit| tx_limit > limits.max_tx_limit)
+        {
+            return Err(ValidationError {
+                field: "txLimit",
+                message: format!("exceeds the API key limit of {}", limits.max_tx_limit),
+            });
+        }
+
+        if self.priority > limits.max_priority {
+            return Err(ValidationError {
+                field: "priority",
+                message: format!("exceeds the API key limit of {}", limits.max_priority),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the optional `Idempotency-Key` header, which must be visible ASCII of at
+/// most [`MAX_IDEMPOTENCY_KEY_LEN`] characters when present.
+pub fn idempotency_key(headers: &HeaderMap) -> Result<Option<&str>, ValidationError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    match value.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => Ok(Some(key)),
+        _ => Err(ValidationError {
+            field: IDEMPOTENCY_KEY_HEADER,
+            message: format!(
+                "must be between 1 and {MAX_IDEMPOTENCY_KEY_LEN} visible ASCII characters"
+            ),
+        }),
+    }
+}
+
+/// Batches always create their own member jobs, so a batch cannot be replayed with an
+/// `Idempotency-Key` and the header is rejected rather than silently ignored.
+pub fn reject_batch_idempotency_key(headers: &HeaderMap) -> Result<(), ValidationError> {
+    if headers.contains_key(IDEMPOTENCY_KEY_HEADER) {
+        return Err(ValidationError {
+            field: IDEMPOTENCY_KEY_HEADER,
+            message: String::from("is not supported for batch submissions"),
+        });
+    }
+
+    Ok(())
+}
+
+fn job_dedup<'a>(
+    settings: &DeduplicationSettings,
+    idempotency_key: Option<&'a str>,
+) -> JobDedup<'a> {
+    JobDedup {
+        idempotency_key,
+        idempotency_ttl: Duration::from_secs(settings.idempotency_key_ttl_secs),
+        reuse_ready_within: settings
+            .enabled
+            .then(|| Duration::from_secs(settings.ready_ttl_secs)),
+    }
+}
+
+impl From<AddressProcessing> for NewJob {
+    fn from(payload: AddressProcessing) -> Self {
+        let window = payload.window();
+        Self {
+            address: payload.address,
+            tx_limit: payload.tx_limit,
+            window,
+            callback_url: payload.callback_url,
+            priority: payload.priority,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchEntryError {
+    pub index: usize,
+    pub address: String,
+    pub field: Option<&'static str>,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchEntryJob {
+    pub index: usize,
+    pub address: String,
+    pub job_id: i64,
+}
+
+/// Accepts absolute http(s) URLs whose host is not loopback, private or link-local, so
+/// callbacks cannot be pointed at the indexer's own network.
+fn validate_callback_url(callback_url: &str) -> Result<(), ValidationError> {
+    let invalid = |message: &str| ValidationError {
+        field: "callback_url",
+        message: message.to_owned(),
+    };
+
+    let url =
+        Url::parse(callback_url).map_err(|_| invalid("must be an absolute http or https URL"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid("must be an absolute http or https URL"));
+    }
+
+    let Some(host) = url.host_str() else {
+        return Err(invalid("must be an absolute http or https URL"));
+    };
+    let is_internal = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .map_or_else(
+            |_| {
+                let domain = host.trim_end_matches('.').to_ascii_lowercase();
+                domain == "localhost" || domain.ends_with(".localhost")
+            },
+            is_internal_ip,
+        );
+    if is_internal {
+        return Err(invalid(
+            "must not point to a loopback, private or link-local host",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Splits batch entries into jobs to create, keeping each entry's position in the
+/// request, and per-entry errors. A repeated address is rejected after its first use.
+pub fn validate_batch(
+    entries: Vec<AddressProcessing>,
+    limits: &ApiKeyLimits,
+    webhooks: &WebhookSettings,
+) -> (Vec<(usize, NewJob)>, Vec<BatchEntryError>) {
+    let mut jobs = Vec::with_capacity(entries.len());
+    let mut errors = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let validation = entry
+            .validate()
+            .and_then(|()| entry.validate_callback_allowed(webhooks.signing_enabled()))
+            .and_then(|()| entry.validate_limits(limits))
+            .and_then(|()| {
+                if seen.insert(entry.address.clone()) {
+                    Ok(())
+                } else {
+                    Err(ValidationError {
+                        field: "address",
+                        message: String::from("appears more than once in the batch"),
+                    })
+                }
+            });
+
+        match validation {
+            Ok(()) => jobs.push((index, NewJob::from(entry))),
+            Err(err) => errors.push(BatchEntryError {
+                index,
+                address: entry.address,
+                field: Some(err.field),
+                error: err.message,
+            }),
+        }
+    }
+
+    (jobs, errors)
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ChartsQuery {
+    #[serde(default)]
+    pub bucket: ChartBucket,
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TransactionsQuery {
+    pub status: Option<TransactionStatusFilter>,
+    pub min_fee: Option<i64>,
+    pub min_compute_units: Option<i32>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TransfersQuery {
+    pub token_mint: Option<String>,
+    pub asset_type: Option<AssetTypeFilter>,
+    pub transfer_type: Option<TransferTypeFilter>,
+    pub counterparty: Option<String>,
+    pub min_amount: Option<u64>,
+    pub max_amount: Option<u64>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
 }
 
 pub async fn create_server(
@@ -32,6 +421,8 @@ pub async fn create_server(
 ) -> Result<()> {
     info!("Starting API server initialization");
 
+    let shutdown = app_state.shutdown.clone();
+
     info!(?cors_allowed_origins, "Configured CORS allowed origins");
 
     let allowed_origins = cors_allowed_origins
@@ -44,10 +435,26 @@ pub async fn create_server(
         .allow_methods(Any)
         .allow_headers(Any);
 
-    let app = Router::new()
-        .route("/health", get(health))
+    let authenticated = Router::new()
         .route("/analyze", post(address_processing))
+        .route("/analyze/batch", post(address_processing_batch))
+        .route("/batches/{id}", get(get_batch_info))
         .route("/jobs/{id}", get(get_job_info))
+        .route("/jobs/{id}/charts", get(get_job_charts))
+        .route("/jobs/{id}/events", get(get_job_events))
+        .route("/jobs/{id}/transactions", get(get_job_transactions))
+        .route("/jobs/{id}/transfers", get(get_job_transfers))
+        .route("/jobs/{id}/cancel", post(cancel_job))
+        .route("/jobs/{id}/retry", post(retry_job))
+        .route("/jobs/{id}/webhooks", get(get_job_webhooks))
+        .route("/jobs/{id}/signatures/dead", get(get_job_dead_signatures))
+        .route_layer(middleware::from_fn_with_state(
+            Arc::clone(&app_state),
+            require_api_key,
+        ));
+
+    let app = ops_routes()
+        .merge(authenticated)
         .layer(cors)
         .with_state(app_state);
 
@@ -55,54 +462,405 @@ pub async fn create_server(
     info!(address = bind.to_string(), "API listener bound");
 
     info!("API server is running");
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { shutdown.triggered().await })
+        .await?;
+    info!("API server stopped");
+
+    Ok(())
+}
+
+/// Serves only `/health`, `/ready` and `/metrics`, for processes that run workers without
+/// the API.
+pub async fn create_ops_server(app_state: Arc<AppState>, bind: SocketAddr) -> Result<()> {
+    let shutdown = app_state.shutdown.clone();
+    let app = ops_routes().with_state(app_state);
+
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    info!(address = bind.to_string(), "Ops listener bound");
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { shutdown.triggered().await })
+        .await?;
+    info!("Ops server stopped");
 
     Ok(())
 }
 
+fn ops_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .route("/metrics", get(metrics))
+}
+
 pub async fn health() -> impl IntoResponse {
     Json(json!({ "status": "ok" }))
 }
 
+const READINESS_DB_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+pub async fn ready(State(app_state): State<Arc<AppState>>) -> impl IntoResponse {
+    let database = match tokio::time::timeout(READINESS_DB_TIMEOUT, app_state.database.ping()).await
+    {
+        Ok(Ok(())) => json!({ "status": "ok" }),
+        Ok(Err(e)) => json!({ "status": "error", "error": e.to_string() }),
+        Err(_) => json!({ "status": "error", "error": "database ping timed out" }),
+    };
+    let database_ready = database["status"] == "ok";
+
+    let cooldown = app_state.rpc.rate_limit_cooldown_remaining().await;
+    let rpc = cooldown.map_or_else(
+        || json!({ "status": "ok" }),
+        |remaining| json!({ "status": "cooldown", "cooldown_remaining_ms": remaining.as_millis() }),
+    );
+
+    let workers = app_state.worker_liveness.report();
+    let workers_ready = workers.iter().all(|worker| worker.alive);
+    let shutting_down = app_state.shutdown.is_triggered();
+
+    let is_ready = database_ready && cooldown.is_none() && workers_ready && !shutting_down;
+    let body = Json(json!({
+        "status": if is_ready { "ok" } else { "unavailable" },
+        "shutting_down": shutting_down,
+        "database": database,
+        "rpc": rpc,
+        "workers": workers,
+    }));
+
+    if is_ready {
+        (axum::http::StatusCode::OK, body).into_response()
+    } else {
+        warn!(
+            database_ready,
+            rpc_cooldown = cooldown.is_some(),
+            workers_ready,
+            shutting_down,
+            "Readiness check failed"
+        );
+        (axum::http::StatusCode::SERVICE_UNAVAILABLE, body).into_response()
+    }
+}
+
+pub async fn metrics() -> impl IntoResponse {
+    let Some(body) = crate::metrics::render() else {
+        return (
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "Metrics recorder is not installed" })),
+        )
+            .into_response();
+    };
+
+    (
+        [(
+            http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        body,
+    )
+        .into_response()
+}
+
+#[allow(clippy::too_many_lines)]
 pub async fn address_processing(
     State(app_state): State<Arc<AppState>>,
-    Json(payload): Json<AddressProcessing>,
+    Extension(api_key): Extension<ApiKey>,
+    headers: HeaderMap,
+    payload: Result<Json<AddressProcessing>, JsonRejection>,
 ) -> impl IntoResponse {
+    let Json(payload) = match payload {
+        Ok(payload) => payload,
+        Err(rejection) => {
+            warn!(error = %rejection.body_text(), "Rejected malformed address processing request");
+            return (
+                rejection.status(),
+                Json(json!({ "error": rejection.body_text() })),
+            )
+                .into_response();
+        }
+    };
+
     info!(
         address = %mask_addr(&payload.address),
+        api_key_id = api_key.id,
         "Received address processing request"
     );
 
+    if let Err(err) = payload
+        .validate()
+        .and_then(|()| payload.validate_callback_allowed(app_state.webhooks.signing_enabled()))
+    {
+        warn!(
+            address = %mask_addr(&payload.address),
+            field = err.field,
+            error = %err.message,
+            "Rejected invalid address processing request"
+        );
+        return (
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": err.message, "field": err.field })),
+        )
+            .into_response();
+    }
+
+    if let Err(err) = payload.validate_limits(&api_key.limits) {
+        warn!(
+            api_key_id = api_key.id,
+            field = err.field,
+            error = %err.message,
+            "Rejected address processing request over API key limits"
+        );
+        return (
+            axum::http::StatusCode::FORBIDDEN,
+            Json(json!({ "error": err.message, "field": err.field })),
+        )
+            .into_response();
+    }
+
+    let idempotency_key = match idempotency_key(&headers) {
+        Ok(key) => key,
+        Err(err) => {
+            warn!(api_key_id = api_key.id, error = %err.message, "Rejected invalid idempotency key");
+            return (
+                axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({ "error": err.message, "field": err.field })),
+            )
+                .into_response();
+        }
+    };
+
+    let dedup = job_dedup(&app_state.deduplication, idempotency_key);
+    let job = NewJob::from(payload);
     let result = app_state
         .database
-        .create_processing_job(&payload.address, payload.tx_limit, payload.requested_hours)
+        .create_processing_job_for_api_key(&api_key, &job, &dedup)
         .await;
 
     match result {
-        Ok(Some(id)) => {
-            info!(job_id = id, "Processing job created");
-            Json(json!({ "status": "ok", "job_id": id })).into_response()
-        }
-        Ok(None) => {
+        Ok(submission) => {
             info!(
-                address = %mask_addr(&payload.address),
-                "Processing job skipped: address already exists"
+                job_id = submission.job_id,
+                address = %mask_addr(&job.address),
+                reused = submission.reused,
+                "Processing job accepted"
             );
-            Json(json!({ "status": "ok", "message": "address already exists" })).into_response()
+            Json(json!({
+                "status": "ok",
+                "job_id": submission.job_id,
+                "reused": submission.reused,
+            }))
+            .into_response()
+        }
+        Err(e) if e.is::<IdempotencyKeyMismatch>() => {
+            warn!(api_key_id = api_key.id, error = %e, "Idempotency key reused for another request");
+            (
+                axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({ "error": e.to_string(), "field": IDEMPOTENCY_KEY_HEADER })),
+            )
+                .into_response()
+        }
+        Err(e) if e.is::<ConcurrentJobLimitExceeded>() => {
+            warn!(api_key_id = api_key.id, error = %e, "Concurrent job limit reached");
+            (
+                axum::http::StatusCode::TOO_MANY_REQUESTS,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
         }
         Err(e) => {
             error!(error = %e, "Failed to create processing job");
-            Json(json!({ "status": "error", "message": e.to_string() })).into_response()
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+pub async fn address_processing_batch(
+    State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
+    headers: HeaderMap,
+    payload: Result<Json<Vec<AddressProcessing>>, JsonRejection>,
+) -> impl IntoResponse {
+    let Json(entries) = match payload {
+        Ok(payload) => payload,
+        Err(rejection) => {
+            warn!(error = %rejection.body_text(), "Rejected malformed batch processing request");
+            return (
+                rejection.status(),
+                Json(json!({ "error": rejection.body_text() })),
+            )
+                .into_response();
+        }
+    };
+
+    info!(
+        api_key_id = api_key.id,
+        entries = entries.len(),
+        "Received batch processing request"
+    );
+
+    if let Err(err) = reject_batch_idempotency_key(&headers) {
+        warn!(api_key_id = api_key.id, error = %err, "Rejected batch with Idempotency-Key");
+        return (
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": err.message, "field": err.field })),
+        )
+            .into_response();
+    }
+
+    if !(1..=MAX_BATCH_SIZE).contains(&entries.len()) {
+        warn!(entries = entries.len(), "Rejected batch with invalid size");
+        return (
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({
+                "error": format!("batch must contain between 1 and {MAX_BATCH_SIZE} entries")
+            })),
+        )
+            .into_response();
+    }
+
+    let (jobs, mut errors) = validate_batch(entries, &api_key.limits, &app_state.webhooks);
+    if jobs.is_empty() {
+        warn!(
+            api_key_id = api_key.id,
+            rejected = errors.len(),
+            "Rejected batch without valid entries"
+        );
+        return (
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": "Batch has no valid entries", "errors": errors })),
+        )
+            .into_response();
+    }
+
+    let (indexes, new_jobs): (Vec<_>, Vec<_>) = jobs.into_iter().unzip();
+    let result = app_state
+        .database
+        .create_job_batch(&api_key, &new_jobs)
+        .await;
+
+    match result {
+        Ok(batch) => {
+            let created = match_batch_entries(
+                indexes,
+                new_jobs,
+                batch.jobs,
+                api_key.limits.max_concurrent_jobs,
+                &mut errors,
+            );
+
+            info!(
+                batch_id = batch.batch_id,
+                created = created.len(),
+                rejected = errors.len(),
+                "Processing batch created"
+            );
+            Json(json!({ "batch_id": batch.batch_id, "jobs": created, "errors": errors }))
+                .into_response()
+        }
+        Err(e) if e.is::<ConcurrentJobLimitExceeded>() => {
+            warn!(api_key_id = api_key.id, error = %e, "Concurrent job limit reached");
+            (
+                axum::http::StatusCode::TOO_MANY_REQUESTS,
+                Json(json!({ "error": e.to_string(), "errors": errors })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create processing batch");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Pairs each submitted entry with the job inserted for it. Entries without a job were cut
+/// off by the key's concurrent-job limit and are reported in `errors`.
+fn match_batch_entries(
+    indexes: Vec<usize>,
+    new_jobs: Vec<NewJob>,
+    inserted: Vec<CreatedJob>,
+    max_concurrent_jobs: i32,
+    errors: &mut Vec<BatchEntryError>,
+) -> Vec<BatchEntryJob> {
+    let mut job_ids = inserted
+        .into_iter()
+        .map(|job| (job.address, job.job_id))
+        .collect::<HashMap<_, _>>();
+    let mut created = Vec::with_capacity(job_ids.len());
+    for (index, job) in indexes.into_iter().zip(new_jobs) {
+        match job_ids.remove(&job.address) {
+            Some(job_id) => created.push(BatchEntryJob {
+                index,
+                address: job.address,
+                job_id,
+            }),
+            None => errors.push(BatchEntryError {
+                index,
+                address: job.address,
+                field: None,
+                error: format!("API key already has {max_concurrent_jobs} active jobs"),
+            }),
+        }
+    }
+    errors.sort_by_key(|err| err.index);
+
+    created
+}
+
+pub async fn get_batch_info(
+    State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    info!(batch_id = id, "Received batch info request");
+    let result = app_state
+        .database
+        .get_batch_info(id, Some(api_key.id))
+        .await;
+
+    match result {
+        Ok(Some(batch_info)) => {
+            info!(
+                batch_id = id,
+                status = batch_info.status,
+                total_jobs = batch_info.total_jobs,
+                "Batch info returned"
+            );
+            Json(batch_info).into_response()
+        }
+        Ok(None) => {
+            warn!(batch_id = id, "Batch not found");
+            (
+                axum::http::StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Batch not found" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(batch_id = id, error = %e, "Failed to fetch batch info");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
         }
     }
 }
 
 pub async fn get_job_info(
     State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
     Path(id): Path<i64>,
 ) -> impl IntoResponse {
     info!(job_id = id, "Received job info request");
-    let result = app_state.database.get_job_info(id).await;
+    let result = app_state.database.get_job_info(id, Some(api_key.id)).await;
 
     match result {
         Ok(Some(job_info)) => {
@@ -112,6 +870,7 @@ pub async fn get_job_info(
                 total_transactions = job_info.total_transactions,
                 processed_transactions = job_info.processed_transactions,
                 remaining_transactions = job_info.remaining_transactions,
+                dead_transactions = job_info.dead_transactions,
                 "Job info returned"
             );
             Json(job_info).into_response()
@@ -134,3 +893,785 @@ pub async fn get_job_info(
         }
     }
 }
+
+const TERMINAL_JOB_STATUSES: &[&str] = &["ready", "error", "cancelled"];
+
+struct JobEventStream {
+    app_state: Arc<AppState>,
+    job_id: i64,
+    api_key_id: i64,
+    receiver: tokio::sync::broadcast::Receiver<i64>,
+    pending: Option<JobInfo>,
+    last_status: Option<String>,
+    finished: bool,
+}
+
+impl JobEventStream {
+    async fn next_job_info(&mut self) -> Option<JobInfo> {
+        if let Some(job_info) = self.pending.take() {
+            return Some(job_info);
+        }
+
+        loop {
+            let received = tokio::select! {
+                received = self.receiver.recv() => received,
+                () = self.app_state.shutdown.triggered() => return None,
+            };
+
+            match received {
+                Ok(job_id) if job_id == self.job_id => break,
+                Ok(_) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!(job_id = self.job_id, skipped, "Job events stream lagged");
+                    break;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+
+        match self
+            .app_state
+            .database
+            .get_job_info(self.job_id, Some(self.api_key_id))
+            .await
+        {
+            Ok(job_info) => job_info,
+            Err(e) => {
+                warn!(job_id = self.job_id, error = %e, "Failed to fetch job info for events stream");
+                None
+            }
+        }
+    }
+
+    async fn next_event(mut self) -> Option<(Result<Event, axum::Error>, Self)> {
+        if self.finished {
+            return None;
+        }
+
+        let job_info = self.next_job_info().await?;
+        let event_name = if self.last_status.as_deref() == Some(job_info.status.as_str()) {
+            "progress"
+        } else {
+            "status"
+        };
+        self.last_status = Some(job_info.status.clone());
+        self.finished = TERMINAL_JOB_STATUSES.contains(&job_info.status.as_str());
+
+        let event = Event::default().event(event_name).json_data(&job_info);
+        Some((event, self))
+    }
+}
+
+pub async fn get_job_events(
+    State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    info!(job_id = id, "Received job events request");
+    let receiver = app_state.job_events.subscribe();
+    let result = app_state.database.get_job_info(id, Some(api_key.id)).await;
+
+    match result {
+        Ok(Some(job_info)) => {
+            let stream = futures::stream::unfold(
+                JobEventStream {
+                    app_state,
+                    job_id: id,
+                    api_key_id: api_key.id,
+                    receiver,
+                    pending: Some(job_info),
+                    last_status: None,
+                    finished: false,
+                },
+                JobEventStream::next_event,
+            );
+            Sse::new(stream)
+                .keep_alive(KeepAlive::default())
+                .into_response()
+        }
+        Ok(None) => {
+            warn!(job_id = id, "Job not found");
+            (
+                axum::http::StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Job not found" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(job_id = id, error = %e, "Failed to fetch job info");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+pub async fn get_job_charts(
+    State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
+    Path(id): Path<i64>,
+    Query(query): Query<ChartsQuery>,
+) -> impl IntoResponse {
+    info!(
+        job_id = id,
+        bucket = query.bucket.as_str(),
+        "Received job charts request"
+    );
+    let result = app_state
+        .database
+        .get_job_charts(id, Some(api_key.id), query.bucket)
+        .await;
+
+    match result {
+        Ok(Some(charts)) => {
+            info!(
+                job_id = id,
+                points = charts.tx_time_line.len(),
+                success_count = charts.success_count,
+                failed_count = charts.failed_count,
+                "Job charts returned"
+            );
+            Json(charts).into_response()
+        }
+        Ok(None) => {
+            warn!(job_id = id, "Job not found");
+            (
+                axum::http::StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Job not found" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(job_id = id, error = %e, "Failed to fetch job charts");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+pub async fn get_job_transactions(
+    State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
+    Path(id): Path<i64>,
+    Query(query): Query<TransactionsQuery>,
+) -> impl IntoResponse {
+    info!(job_id = id, ?query, "Received job transactions request");
+
+    let cursor = match query
+        .cursor
+        .as_deref()
+        .map(str::parse::<TransactionCursor>)
+        .transpose()
+    {
+        Ok(cursor) => cursor,
+        Err(e) => {
+            warn!(job_id = id, error = %e, "Invalid transactions cursor");
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("Invalid cursor: {e}") })),
+            )
+                .into_response();
+        }
+    };
+
+    let filter = TransactionFilter {
+        status: query.status,
+        min_fee: query.min_fee,
+        min_compute_units: query.min_compute_units,
+        cursor,
+        limit: query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE),
+    };
+    let result = app_state
+        .database
+        .get_job_transactions(id, Some(api_key.id), &filter)
+        .await;
+
+    match result {
+        Ok(Some(page)) => {
+            info!(
+                job_id = id,
+                returned = page.transactions.len(),
+                has_more = page.next_cursor.is_some(),
+                "Job transactions returned"
+            );
+            Json(page).into_response()
+        }
+        Ok(None) => {
+            warn!(job_id = id, "Job not found");
+            (
+                axum::http::StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Job not found" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(job_id = id, error = %e, "Failed to fetch job transactions");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+pub async fn get_job_transfers(
+    State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
+    Path(id): Path<i64>,
+    Query(query): Query<TransfersQuery>,
+) -> impl IntoResponse {
+    info!(job_id = id, ?query, "Received job transfers request");
+
+    let cursor = match query
+        .cursor
+        .as_deref()
+        .map(str::parse::<TransferCursor>)
+        .transpose()
+    {
+        Ok(cursor) => cursor,
+        Err(e) => {
+            warn!(job_id = id, error = %e, "Invalid transfers cursor");
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("Invalid cursor: {e}") })),
+            )
+                .into_response();
+        }
+    };
+
+    let filter = TransferFilter {
+        token_mint: query.token_mint,
+        asset_type: query.asset_type,
+        transfer_type: query.transfer_type,
+        counterparty: query.counterparty,
+        min_amount: query.min_amount,
+        max_amount: query.max_amount,
+        cursor,
+        limit: query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE),
+    };
+    let result = app_state
+        .database
+        .get_job_transfers(id, Some(api_key.id), &filter)
+        .await;
+
+    match result {
+        Ok(Some(page)) => {
+            info!(
+                job_id = id,
+                returned = page.transfers.len(),
+                has_more = page.next_cursor.is_some(),
+                "Job transfers returned"
+            );
+            Json(page).into_response()
+        }
+        Ok(None) => {
+            warn!(job_id = id, "Job not found");
+            (
+                axum::http::StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Job not found" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(job_id = id, error = %e, "Failed to fetch job transfers");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+pub async fn cancel_job(
+    State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    info!(job_id = id, "Received job cancel request");
+    let result = app_state.database.cancel_job(id, Some(api_key.id)).await;
+    job_transition_response(id, "cancel", result)
+}
+
+pub async fn retry_job(
+    State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    info!(job_id = id, "Received job retry request");
+    let result = app_state.database.retry_job(id, Some(&api_key)).await;
+    job_transition_response(id, "retry", result)
+}
+
+pub async fn get_job_webhooks(
+    State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    info!(job_id = id, "Received job webhooks request");
+    let result = app_state
+        .database
+        .get_job_webhooks(id, Some(api_key.id))
+        .await;
+
+    match result {
+        Ok(Some(webhooks)) => {
+            info!(
+                job_id = id,
+                deliveries = webhooks.deliveries.len(),
+                "Job webhooks returned"
+            );
+            Json(webhooks).into_response()
+        }
+        Ok(None) => {
+            warn!(job_id = id, "Job not found");
+            (
+                axum::http::StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Job not found" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(job_id = id, error = %e, "Failed to fetch job webhooks");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+pub async fn get_job_dead_signatures(
+    State(app_state): State<Arc<AppState>>,
+    Extension(api_key): Extension<ApiKey>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    info!(job_id = id, "Received job dead signatures request");
+    let result = app_state
+        .database
+        .get_job_dead_signatures(id, Some(api_key.id))
+        .await;
+
+    match result {
+        Ok(Some(dead)) => {
+            info!(
+                job_id = id,
+                signatures = dead.signatures.len(),
+                "Job dead signatures returned"
+            );
+            Json(dead).into_response()
+        }
+        Ok(None) => {
+            warn!(job_id = id, "Job not found");
+            (
+                axum::http::StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Job not found" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(job_id = id, error = %e, "Failed to fetch job dead signatures");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+fn job_transition_response(
+    id: i64,
+    action: &str,
+    result: Result<Option<JobTransition>>,
+) -> axum::response::Response {
+    match result {
+        Ok(Some(JobTransition {
+            previous_status,
+            status: Some(status),
+        })) => {
+            info!(job_id = id, action, %previous_status, %status, "Job status changed");
+            Json(json!({ "status": status })).into_response()
+        }
+        Ok(Some(JobTransition {
+            previous_status,
+            status: None,
+        })) => {
+            warn!(job_id = id, action, %previous_status, "Job status transition rejected");
+            (
+                axum::http::StatusCode::CONFLICT,
+                Json(json!({
+                    "error": format!("Cannot {action} job with status {previous_status}")
+                })),
+            )
+                .into_response()
+        }
+        Ok(None) => {
+            warn!(job_id = id, "Job not found");
+            (
+                axum::http::StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Job not found" })),
+            )
+                .into_response()
+        }
+        Err(e) if e.is::<ConcurrentJobLimitExceeded>() => {
+            warn!(job_id = id, action, error = %e, "Concurrent job limit reached");
+            (
+                axum::http::StatusCode::TOO_MANY_REQUESTS,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(job_id = id, action, error = %e, "Failed to change job status");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const VALID_ADDRESS: &str = "8TPACXaKotSZ7WXktfmKDRhgoypyGXNzo1ctr2YBzxLc";
+
+    fn request(address: &str, requested_hours: i32, tx_limit: i32) -> AddressProcessing {
+        AddressProcessing {
+            address: address.to_string(),
+            requested_hours: Some(requested_hours),
+            from: None,
+            to: None,
+            full_history: false,
+            tx_limit: Some(tx_limit),
+            callback_url: None,
+            priority: 0,
+        }
+    }
+
+    fn rejected_field(payload: &AddressProcessing) -> Option<&'static str> {
+        payload.validate().err().map(|err| err.field)
+    }
+
+    #[test]
+    fn should_accept_request_when_address_and_bounds_are_valid() {
+        assert_eq!(rejected_field(&request(VALID_ADDRESS, 24, 1000)), None);
+        assert_eq!(
+            rejected_field(&request(VALID_ADDRESS, MAX_REQUESTED_HOURS, MAX_TX_LIMIT)),
+            None
+        );
+    }
+
+    #[test]
+    fn should_reject_address_when_not_a_base58_pubkey() {
+        assert_eq!(
+            rejected_field(&request("not-a-pubkey", 24, 1000)),
+            Some("address")
+        );
+        assert_eq!(rejected_field(&request("", 24, 1000)), Some("address"));
+        assert_eq!(
+            rejected_field(&request("3yZe7d", 24, 1000)),
+            Some("address")
+        );
+    }
+
+    #[test]
+    fn should_reject_numbers_when_out_of_bounds() {
+        assert_eq!(
+            rejected_field(&request(VALID_ADDRESS, 0, 1000)),
+            Some("requested_hours")
+        );
+        assert_eq!(
+            rejected_field(&request(VALID_ADDRESS, MAX_REQUESTED_HOURS + 1, 1000)),
+            Some("requested_hours")
+        );
+        assert_eq!(
+            rejected_field(&request(VALID_ADDRESS, 24, -5)),
+            Some("txLimit")
+        );
+        assert_eq!(
+            rejected_field(&request(VALID_ADDRESS, 24, MAX_TX_LIMIT + 1)),
+            Some("txLimit")
+        );
+
+        let mut prioritized = request(VALID_ADDRESS, 24, 1000);
+        prioritized.priority = MAX_PRIORITY + 1;
+        assert_eq!(rejected_field(&prioritized), Some("priority"));
+        prioritized.priority = -1;
+        assert_eq!(rejected_field(&prioritized), Some("priority"));
+    }
+
+    #[test]
+    fn should_reject_numbers_when_above_api_key_limits() {
+        let limits = ApiKeyLimits {
+            max_concurrent_jobs: 1,
+            max_tx_limit: 500,
+            max_requested_hours: 48,
+            max_priority: 2,
+            requests_per_minute: 60,
+            allow_full_history: false,
+        };
+        let limit_rejection = |payload: &AddressProcessing| {
+            payload.validate_limits(&limits).err().map(|err| err.field)
+        };
+
+        assert_eq!(limit_rejection(&request(VALID_ADDRESS, 48, 500)), None);
+        assert_eq!(
+            limit_rejection(&request(VALID_ADDRESS, 49, 500)),
+            Some("requested_hours")
+        );
+        assert_eq!(
+            limit_rejection(&request(VALID_ADDRESS, 48, 501)),
+            Some("txLimit")
+        );
+
+        let mut prioritized = request(VALID_ADDRESS, 24, 100);
+        prioritized.priority = 2;
+        assert_eq!(limit_rejection(&prioritized), None);
+        prioritized.priority = 3;
+        assert_eq!(limit_rejection(&prioritized), Some("priority"));
+    }
+
+    #[test]
+    fn should_report_invalid_and_duplicate_entries_when_validating_batch() {
+        let limits = ApiKeyLimits {
+            max_concurrent_jobs: 10,
+            max_tx_limit: 1000,
+            max_requested_hours: 24,
+            max_priority: 0,
+            requests_per_minute: 60,
+            allow_full_history: false,
+        };
+        let other_address = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+        let entries = vec![
+            request(VALID_ADDRESS, 24, 1000),
+            request("not-a-pubkey", 24, 1000),
+            request(VALID_ADDRESS, 12, 500),
+            request(other_address, 48, 1000),
+            request(other_address, 24, 100),
+        ];
+
+        let webhooks = WebhookSettings {
+            secret: String::from("secret"),
+            max_attempts: 1,
+            poll_interval_secs: 1,
+            request_timeout_secs: 1,
+        };
+        let (jobs, errors) = validate_batch(entries, &limits, &webhooks);
+
+        assert_eq!(
+            jobs.iter()
+                .map(|(index, job)| (*index, job.address.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(0, VALID_ADDRESS), (4, other_address)]
+        );
+        assert_eq!(
+            errors
+                .iter()
+                .map(|err| (err.index, err.field))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, Some("address")),
+                (2, Some("address")),
+                (3, Some("requested_hours")),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reject_callback_url_when_not_an_http_url() {
+        let mut payload = request(VALID_ADDRESS, 24, 1000);
+
+        payload.callback_url = Some(String::from("https://hooks.example.com/indexer"));
+        assert_eq!(rejected_field(&payload), None);
+
+        for callback_url in ["ftp://hooks.example.com", "not a url", "/relative/path"] {
+            payload.callback_url = Some(callback_url.to_string());
+            assert_eq!(rejected_field(&payload), Some("callback_url"));
+        }
+    }
+
+    #[test]
+    fn should_reject_callback_url_when_host_is_internal() {
+        let mut payload = request(VALID_ADDRESS, 24, 1000);
+
+        for callback_url in [
+            "http://localhost:8080/hook",
+            "http://api.localhost/hook",
+            "http://127.0.0.1/hook",
+            "http://10.0.0.5/hook",
+            "http://192.168.1.10/hook",
+            "http://172.16.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://0.0.0.0/hook",
+            "http://[::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+        ] {
+            payload.callback_url = Some(callback_url.to_string());
+            assert_eq!(
+                rejected_field(&payload),
+                Some("callback_url"),
+                "{callback_url}"
+            );
+        }
+
+        payload.callback_url = Some(String::from("http://93.184.216.34/hook"));
+        assert_eq!(rejected_field(&payload), None);
+    }
+
+    #[test]
+    fn should_reject_callback_url_when_webhooks_are_disabled() {
+        let mut payload = request(VALID_ADDRESS, 24, 1000);
+        assert!(payload.validate_callback_allowed(false).is_ok());
+
+        payload.callback_url = Some(String::from("https://hooks.example.com/indexer"));
+        assert!(payload.validate_callback_allowed(true).is_ok());
+        assert_eq!(
+            payload
+                .validate_callback_allowed(false)
+                .err()
+                .map(|err| err.field),
+            Some("callback_url")
+        );
+    }
+
+    #[test]
+    fn should_accept_closed_past_range_instead_of_requested_hours() -> Result<()> {
+        let limits = ApiKeyLimits {
+            max_concurrent_jobs: 1,
+            max_tx_limit: 1000,
+            max_requested_hours: 24 * 31,
+            max_priority: 0,
+            requests_per_minute: 60,
+            allow_full_history: false,
+        };
+        let mut payload = request(VALID_ADDRESS, 24, 1000);
+        payload.requested_hours = None;
+        payload.from = Some("2025-03-01T00:00:00Z".parse()?);
+        payload.to = Some("2025-04-01T00:00:00Z".parse()?);
+
+        assert_eq!(rejected_field(&payload), None);
+        assert_eq!(payload.validate_limits(&limits), Ok(()));
+        assert_eq!(
+            payload.window(),
+            JobWindow::Range {
+                from: "2025-03-01T00:00:00Z".parse()?,
+                to: "2025-04-01T00:00:00Z".parse()?,
+            }
+        );
+
+        let mut one_hour_more = limits;
+        one_hour_more.max_requested_hours = 24 * 31 - 1;
+        assert_eq!(
+            payload
+                .validate_limits(&one_hour_more)
+                .err()
+                .map(|err| err.field),
+            Some("from")
+        );
+
+        payload.requested_hours = Some(24);
+        assert_eq!(rejected_field(&payload), Some("requested_hours"));
+        payload.requested_hours = None;
+
+        payload.to = payload.from;
+        assert_eq!(rejected_field(&payload), Some("to"));
+        payload.to = Some(Utc::now() + chrono::Duration::hours(1));
+        assert_eq!(rejected_field(&payload), Some("to"));
+        payload.to = None;
+        assert_eq!(rejected_field(&payload), Some("to"));
+        payload.from = None;
+        assert_eq!(rejected_field(&payload), Some("requested_hours"));
+        Ok(())
+    }
+
+    #[test]
+    fn should_require_key_permission_when_requesting_full_history() {
+        let mut limits = ApiKeyLimits {
+            max_concurrent_jobs: 1,
+            max_tx_limit: 1000,
+            max_requested_hours: 24,
+            max_priority: 0,
+            requests_per_minute: 60,
+            allow_full_history: false,
+        };
+        let mut payload = request(VALID_ADDRESS, 24, 1000);
+        payload.requested_hours = None;
+        payload.tx_limit = None;
+        payload.full_history = true;
+
+        assert_eq!(rejected_field(&payload), None);
+        assert_eq!(payload.window(), JobWindow::FullHistory);
+        assert_eq!(
+            payload.validate_limits(&limits).err().map(|err| err.field),
+            Some("full_history")
+        );
+        limits.allow_full_history = true;
+        assert_eq!(payload.validate_limits(&limits), Ok(()));
+
+        payload.tx_limit = Some(1001);
+        assert_eq!(
+            payload.validate_limits(&limits).err().map(|err| err.field),
+            Some("txLimit")
+        );
+
+        payload.requested_hours = Some(24);
+        assert_eq!(rejected_field(&payload), Some("full_history"));
+
+        payload.full_history = false;
+        payload.tx_limit = None;
+        assert_eq!(rejected_field(&payload), Some("txLimit"));
+    }
+
+    #[test]
+    fn should_validate_idempotency_key_header_when_present() -> Result<()> {
+        let mut headers = HeaderMap::new();
+        assert_eq!(idempotency_key(&headers), Ok(None));
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "order-42".parse()?);
+        assert_eq!(idempotency_key(&headers), Ok(Some("order-42")));
+
+        for invalid in [String::new(), "k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1)] {
+            headers.insert(IDEMPOTENCY_KEY_HEADER, invalid.parse()?);
+            assert_eq!(
+                idempotency_key(&headers).err().map(|err| err.field),
+                Some(IDEMPOTENCY_KEY_HEADER)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_idempotency_key_on_batch_submissions() -> Result<()> {
+        let mut headers = HeaderMap::new();
+        assert_eq!(reject_batch_idempotency_key(&headers), Ok(()));
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "order-42".parse()?);
+        assert_eq!(
+            reject_batch_idempotency_key(&headers)
+                .err()
+                .map(|err| err.field),
+            Some(IDEMPOTENCY_KEY_HEADER)
+        );
+        Ok(())
+    }
+}
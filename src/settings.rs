@@ -10,6 +10,10 @@ pub struct Settings {
     pub rpc: RpcSettings,
     pub server: ServerSettings,
     pub workers: WorkerSettings,
+    pub deduplication: DeduplicationSettings,
+    pub signature_retries: SignatureRetrySettings,
+    pub raw_payloads: RawPayloadSettings,
+    pub webhooks: WebhookSettings,
     pub logging: LoggingSettings,
 }
 
@@ -31,18 +35,40 @@ impl Settings {
             .build()?;
 
         let settings: Self = config.try_deserialize()?;
+        settings.validate()?;
 
         Ok(settings)
     }
 
-    pub fn rpc_endpoint(&self) -> String {
-        format!("{}{}", self.rpc.url, self.rpc.api_key)
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.workers.reaper_interval_secs == 0 {
+            return Err(ConfigError::Message(
+                "workers.reaper_interval_secs must be greater than 0".to_owned(),
+            ));
+        }
+
+        Ok(())
     }
 
     pub fn log_loaded_settings(&self) {
         info!(
             server_bind = %self.server.bind,
             worker_count = self.workers.count,
+            worker_lease_timeout_secs = self.workers.lease_timeout_secs,
+            worker_reaper_interval_secs = self.workers.reaper_interval_secs,
+            worker_max_attempts = self.workers.max_attempts,
+            worker_shutdown_grace_secs = self.workers.shutdown_grace_secs,
+            deduplication_enabled = self.deduplication.enabled,
+            deduplication_ready_ttl_secs = self.deduplication.ready_ttl_secs,
+            idempotency_key_ttl_secs = self.deduplication.idempotency_key_ttl_secs,
+            signature_max_attempts = self.signature_retries.max_attempts,
+            signature_retry_base_ms = self.signature_retries.base_delay_ms,
+            signature_retry_max_ms = self.signature_retries.max_delay_ms,
+            raw_payloads_enabled = self.raw_payloads.enabled,
+            webhook_max_attempts = self.webhooks.max_attempts,
+            webhook_poll_interval_secs = self.webhooks.poll_interval_secs,
+            webhook_request_timeout_secs = self.webhooks.request_timeout_secs,
+            rpc_provider = ?self.rpc.provider,
             rpc_rps = self.rpc.rps,
             rpc_max_concurrent = self.rpc.max_concurrent,
             rpc_max_rate_limit_retries = self.rpc.max_rate_limit_retries,
@@ -57,6 +83,7 @@ impl Settings {
             database_url_configured = !self.database.url.is_empty(),
             rpc_url_configured = !self.rpc.url.is_empty(),
             rpc_api_key_configured = !self.rpc.api_key.is_empty(),
+            webhook_secret_configured = !self.webhooks.secret.is_empty(),
             "Sensitive settings presence checked"
         );
 
@@ -64,9 +91,15 @@ impl Settings {
             warn!("Database URL is empty");
         }
 
-        if self.rpc.api_key.is_empty() {
+        if self.rpc.provider == RpcProvider::Helius && self.rpc.api_key.is_empty() {
             warn!("RPC API key is empty");
         }
+
+        if !self.webhooks.signing_enabled() {
+            warn!(
+                "Webhook signing secret is empty; callback_url is rejected and webhooks are not sent"
+            );
+        }
     }
 }
 
@@ -78,6 +111,7 @@ pub struct DatabaseSettings {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct RpcSettings {
+    pub provider: RpcProvider,
     pub api_key: String,
     pub url: String,
     pub rps: u32,
@@ -85,6 +119,16 @@ pub struct RpcSettings {
     pub max_rate_limit_retries: usize,
 }
 
+/// Which `SolanaRpc` implementation talks to `rpc.url`.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcProvider {
+    /// Helius; `rpc.api_key` is appended to `rpc.url`.
+    Helius,
+    /// Any standard Solana JSON-RPC endpoint, used as is.
+    JsonRpc,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ServerSettings {
     pub bind: SocketAddr,
@@ -94,6 +138,44 @@ pub struct ServerSettings {
 #[derive(Debug, Deserialize, Clone)]
 pub struct WorkerSettings {
     pub count: u32,
+    pub lease_timeout_secs: u64,
+    pub reaper_interval_secs: u64,
+    pub max_attempts: u32,
+    pub shutdown_grace_secs: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeduplicationSettings {
+    pub enabled: bool,
+    pub ready_ttl_secs: u64,
+    pub idempotency_key_ttl_secs: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SignatureRetrySettings {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RawPayloadSettings {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookSettings {
+    pub secret: String,
+    pub max_attempts: u32,
+    pub poll_interval_secs: u64,
+    pub request_timeout_secs: u64,
+}
+
+impl WebhookSettings {
+    /// Webhooks are only accepted and sent when there is a secret to sign them with.
+    pub const fn signing_enabled(&self) -> bool {
+        !self.secret.is_empty()
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+#[derive(Debug)]
+pub struct ShutdownRequested;
+
+impl std::fmt::Display for ShutdownRequested {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("shutdown requested")
+    }
+}
+
+impl std::error::Error for ShutdownRequested {}
+
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    token: CancellationToken,
+    grace_period: Duration,
+}
+
+impl Shutdown {
+    pub fn new(grace_period: Duration) -> Self {
+        Self {
+            token: CancellationToken::new(),
+            grace_period,
+        }
+    }
+
+    pub const fn grace_period(&self) -> Duration {
+        self.grace_period
+    }
+
+    pub fn trigger(&self) {
+        self.token.cancel();
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    pub async fn triggered(&self) {
+        self.token.cancelled().await;
+    }
+
+    pub async fn deadline(&self) {
+        self.triggered().await;
+        tokio::time::sleep(self.grace_period).await;
+    }
+
+    pub async fn listen_for_signals(self) {
+        let ctrl_c = async {
+            if let Err(err) = tokio::signal::ctrl_c().await {
+                warn!(%err, "Failed to listen for Ctrl+C");
+                std::future::pending::<()>().await;
+            }
+        };
+
+        #[cfg(unix)]
+        let terminate = async {
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(mut signal) => {
+                    signal.recv().await;
+                }
+                Err(err) => {
+                    warn!(%err, "Failed to listen for SIGTERM");
+                    std::future::pending::<()>().await;
+                }
+            }
+        };
+
+        #[cfg(not(unix))]
+        let terminate = std::future::pending::<()>();
+
+        tokio::select! {
+            () = ctrl_c => info!("Received Ctrl+C"),
+            () = terminate => info!("Received SIGTERM"),
+            () = self.triggered() => return,
+        }
+
+        info!(
+            grace_period_ms = self.grace_period.as_millis(),
+            "Shutdown requested, draining workers"
+        );
+        self.trigger();
+    }
+}
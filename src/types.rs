@@ -1,24 +1,554 @@
-use serde::Serialize;
+use crate::requests::SignatureRange;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::FromRow;
+use std::time::Duration;
 
 #[derive(Serialize, FromRow)]
 pub struct JobInfo {
     pub status: String,
+    pub priority: i16,
+    pub range_start: chrono::DateTime<chrono::Utc>,
+    pub range_end: Option<chrono::DateTime<chrono::Utc>>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub total_transactions: i64,
     pub processed_transactions: i64,
     pub remaining_transactions: i64,
+    pub dead_transactions: i64,
 }
 
 #[derive(Debug, FromRow)]
 pub struct ClaimedJob {
     pub job_id: i64,
     pub address: String,
-    pub requested_hours: i16,
-    pub tx_limit: i16,
+    pub range_start: chrono::DateTime<chrono::Utc>,
+    pub range_end: Option<chrono::DateTime<chrono::Utc>>,
+    pub tx_limit: Option<i32>,
+}
+
+impl ClaimedJob {
+    pub fn signature_range(&self) -> SignatureRange {
+        SignatureRange {
+            from_ts: self.range_start.timestamp(),
+            to_ts: self.range_end.map(|range_end| range_end.timestamp()),
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct ReapedJob {
+    pub job_id: i64,
+    pub status: String,
+    pub attempts: i32,
+}
+
+#[derive(Debug, FromRow)]
+pub struct JobTransition {
+    pub previous_status: String,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct SignatureCoverage {
+    pub newest_signature: String,
+    pub oldest_signature: String,
+    pub oldest_block_time: i64,
 }
 
 pub struct SaveStats {
     pub transactions: u64,
     pub token_transfers: u64,
+    pub signatures_processed: u64,
+    pub raw_payloads: u64,
+}
+
+#[derive(Debug, FromRow)]
+pub struct RawTransaction {
+    pub signature: String,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartBucket {
+    Minute,
+    #[default]
+    Hour,
+    Day,
+}
+
+impl ChartBucket {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Minute => "minute",
+            Self::Hour => "hour",
+            Self::Day => "day",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct TxTimePoint {
+    pub bucket: chrono::DateTime<chrono::Utc>,
+    pub transactions: i64,
+    pub successful: i64,
+    pub failed: i64,
+    pub fee: i64,
+}
+
+#[derive(Debug, FromRow)]
+pub struct ChartsSummary {
+    pub success_count: i64,
+    pub failed_count: i64,
+    pub total_fee: i64,
+    pub native_transfer_volume: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobCharts {
+    pub bucket: ChartBucket,
+    pub tx_time_line: Vec<TxTimePoint>,
+    pub success_count: i64,
+    pub failed_count: i64,
+    pub total_fee: i64,
+    pub native_transfer_volume: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionStatusFilter {
+    Success,
+    Failed,
+}
+
+impl TransactionStatusFilter {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionCursor {
+    pub block_time: i64,
+    pub signature: String,
+}
+
+impl std::fmt::Display for TransactionCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.block_time, self.signature)
+    }
+}
+
+impl std::str::FromStr for TransactionCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (block_time, signature) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("cursor must be <block_time>:<signature>"))?;
+        if signature.is_empty() {
+            anyhow::bail!("cursor signature is empty");
+        }
+
+        Ok(Self {
+            block_time: block_time.parse()?,
+            signature: signature.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransactionFilter {
+    pub status: Option<TransactionStatusFilter>,
+    pub min_fee: Option<i64>,
+    pub min_compute_units: Option<i32>,
+    pub cursor: Option<TransactionCursor>,
+    pub limit: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct TransactionRow {
+    pub signature: String,
+    pub slot: i64,
+    pub block_time: i64,
+    pub err: Option<serde_json::Value>,
+    pub fee: i64,
+    pub compute_units: Option<i32>,
+    pub num_signers: Option<i32>,
+    pub num_instructions: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobTransactions {
+    pub transactions: Vec<TransactionRow>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetTypeFilter {
+    Native,
+    Spl,
+}
+
+impl AssetTypeFilter {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Spl => "spl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferTypeFilter {
+    Transfer,
+    Mint,
+    Burn,
+}
+
+impl TransferTypeFilter {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Transfer => "transfer",
+            Self::Mint => "mint",
+            Self::Burn => "burn",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferCursor {
+    pub block_time: i64,
+    pub id: uuid::Uuid,
+}
+
+impl std::fmt::Display for TransferCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.block_time, self.id)
+    }
+}
+
+impl std::str::FromStr for TransferCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (block_time, id) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("cursor must be <block_time>:<id>"))?;
+
+        Ok(Self {
+            block_time: block_time.parse()?,
+            id: id.parse()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferFilter {
+    pub token_mint: Option<String>,
+    pub asset_type: Option<AssetTypeFilter>,
+    pub transfer_type: Option<TransferTypeFilter>,
+    pub counterparty: Option<String>,
+    pub min_amount: Option<u64>,
+    pub max_amount: Option<u64>,
+    pub cursor: Option<TransferCursor>,
+    pub limit: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct TransferRow {
+    pub id: uuid::Uuid,
+    pub signature: String,
+    pub slot: i64,
+    pub block_time: i64,
+    pub token_mint: Option<String>,
+    pub token_program: Option<String>,
+    pub source_owner: Option<String>,
+    pub destination_owner: Option<String>,
+    pub source_token_account: Option<String>,
+    pub destination_token_account: Option<String>,
+    pub amount_raw: String,
+    pub amount_ui: Option<f64>,
+    pub decimals: Option<i32>,
+    pub transfer_type: String,
+    pub asset_type: String,
+    pub direction: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobTransfers {
+    pub transfers: Vec<TransferRow>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct ClaimedWebhookDelivery {
+    pub id: i64,
+    pub job_id: i64,
+    pub callback_url: String,
+    pub payload: String,
+    pub attempts: i32,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct WebhookDeliveryRow {
+    pub id: i64,
+    pub callback_url: String,
+    pub status: String,
+    pub attempts: i32,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobWebhooks {
+    pub callback_url: Option<String>,
+    pub deliveries: Vec<WebhookDeliveryRow>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct SignatureFailure {
+    pub signature: String,
+    pub attempts: i32,
+    pub is_dead: bool,
+    pub next_attempt_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct DeadSignatureRow {
+    pub signature: String,
+    pub block_time: Option<i64>,
+    pub attempts: i32,
+    pub last_status_code: Option<i32>,
+    pub last_rpc_code: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_attempt_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobDeadSignatures {
+    pub job_id: i64,
+    pub signatures: Vec<DeadSignatureRow>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromRow)]
+pub struct ApiKeyLimits {
+    pub max_concurrent_jobs: i32,
+    pub max_tx_limit: i32,
+    pub max_requested_hours: i32,
+    pub max_priority: i16,
+    pub requests_per_minute: i32,
+    pub allow_full_history: bool,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiKey {
+    pub id: i64,
+    pub name: String,
+    #[sqlx(flatten)]
+    pub limits: ApiKeyLimits,
+}
+
+/// The period a job indexes: the last `hours` before submission, a fixed range, or the
+/// address's entire history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobWindow {
+    Recent {
+        hours: i32,
+    },
+    Range {
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    },
+    FullHistory,
+}
+
+impl JobWindow {
+    pub const fn requested_hours(self) -> Option<i32> {
+        match self {
+            Self::Recent { hours } => Some(hours),
+            Self::Range { .. } | Self::FullHistory => None,
+        }
+    }
+
+    /// Full-history jobs start at the unix epoch, well before any Solana block.
+    pub const fn range_start(self) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            Self::Recent { .. } => None,
+            Self::Range { from, .. } => Some(from),
+            Self::FullHistory => Some(chrono::DateTime::UNIX_EPOCH),
+        }
+    }
+
+    pub const fn range_end(self) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            Self::Recent { .. } | Self::FullHistory => None,
+            Self::Range { to, .. } => Some(to),
+        }
+    }
+}
+
+impl std::fmt::Display for JobWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Recent { hours } => write!(f, "{hours}h"),
+            Self::Range { from, to } => write!(f, "{}..{}", from.to_rfc3339(), to.to_rfc3339()),
+            Self::FullHistory => f.write_str("full"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewJob {
+    pub address: String,
+    /// `None` indexes every signature in the window.
+    pub tx_limit: Option<i32>,
+    pub window: JobWindow,
+    pub callback_url: Option<String>,
+    pub priority: i16,
+}
+
+impl NewJob {
+    /// Identifies the request parameters behind an `Idempotency-Key`, so a replayed key
+    /// with a different payload can be told apart from a retry.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            self.address.as_str(),
+            &self
+                .tx_limit
+                .map(|tx_limit| tx_limit.to_string())
+                .unwrap_or_default(),
+            &self.window.to_string(),
+            self.callback_url.as_deref().unwrap_or_default(),
+            &self.priority.to_string(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JobDedup<'a> {
+    pub idempotency_key: Option<&'a str>,
+    pub idempotency_ttl: Duration,
+    /// Enables reusing an unfinished job, or a `ready` one updated within this long,
+    /// whose window already covers the request.
+    pub reuse_ready_within: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobSubmission {
+    pub job_id: i64,
+    pub reused: bool,
+}
+
+#[derive(Debug, FromRow)]
+pub struct CreatedJob {
+    pub job_id: i64,
+    pub address: String,
+}
+
+#[derive(Debug)]
+pub struct CreatedBatch {
+    pub batch_id: i64,
+    pub jobs: Vec<CreatedJob>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct BatchJobInfo {
+    pub job_id: i64,
+    pub address: String,
+    pub status: String,
+    pub priority: i16,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub total_transactions: i64,
+    pub processed_transactions: i64,
+    pub remaining_transactions: i64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct BatchStatusCounts {
+    pub pending: i64,
+    pub indexing: i64,
+    pub ready: i64,
+    pub error: i64,
+    pub cancelled: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchInfo {
+    pub batch_id: i64,
+    pub status: &'static str,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub total_jobs: i64,
+    pub jobs_by_status: BatchStatusCounts,
+    pub total_transactions: i64,
+    pub processed_transactions: i64,
+    pub remaining_transactions: i64,
+    pub jobs: Vec<BatchJobInfo>,
+}
+
+impl BatchInfo {
+    /// Rolls member jobs up into one status: `pending` until any job starts, `indexing`
+    /// while any job is unfinished, then `ready`, `failed` or `partial` depending on how
+    /// many jobs ended `ready`.
+    pub fn from_jobs(
+        batch_id: i64,
+        created_at: chrono::DateTime<chrono::Utc>,
+        jobs: Vec<BatchJobInfo>,
+    ) -> Self {
+        let mut jobs_by_status = BatchStatusCounts::default();
+        let (mut total_transactions, mut processed_transactions, mut remaining_transactions) =
+            (0, 0, 0);
+        for job in &jobs {
+            match job.status.as_str() {
+                "pending" => jobs_by_status.pending += 1,
+                "ready" => jobs_by_status.ready += 1,
+                "error" => jobs_by_status.error += 1,
+                "cancelled" => jobs_by_status.cancelled += 1,
+                _ => jobs_by_status.indexing += 1,
+            }
+            total_transactions += job.total_transactions;
+            processed_transactions += job.processed_transactions;
+            remaining_transactions += job.remaining_transactions;
+        }
+
+        let total_jobs = i64::try_from(jobs.len()).unwrap_or(i64::MAX);
+        let finished = jobs_by_status.ready + jobs_by_status.error + jobs_by_status.cancelled;
+        let status = if jobs_by_status.pending == total_jobs {
+            "pending"
+        } else if finished < total_jobs {
+            "indexing"
+        } else if jobs_by_status.ready == total_jobs {
+            "ready"
+        } else if jobs_by_status.ready == 0 {
+            "failed"
+        } else {
+            "partial"
+        };
+
+        Self {
+            batch_id,
+            status,
+            created_at,
+            total_jobs,
+            jobs_by_status,
+            total_transactions,
+            processed_transactions,
+            remaining_transactions,
+            jobs,
+        }
+    }
 }
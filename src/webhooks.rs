@@ -0,0 +1,344 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Result, ensure};
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, Url, redirect};
+use sha2::Sha256;
+use tracing::{debug, info, instrument, warn};
+
+use crate::AppState;
+use crate::backoff::WorkerBackoff;
+use crate::db::Database;
+use crate::metrics;
+use crate::settings::WebhookSettings;
+use crate::types::ClaimedWebhookDelivery;
+
+pub const SIGNATURE_HEADER: &str = "X-Indexer-Signature";
+pub const DELIVERY_HEADER: &str = "X-Indexer-Delivery";
+
+const DELIVERY_BATCH_SIZE: i64 = 16;
+const DELIVERY_LEASE_MARGIN: Duration = Duration::from_secs(30);
+const RETRY_MIN_DELAY_MS: f64 = 30_000.0;
+const RETRY_MAX_DELAY_MS: f64 = 3_600_000.0;
+const MAX_ERROR_LEN: usize = 512;
+
+/// Returns the `sha256=<hex>` HMAC of `body` that receivers compare against
+/// the signature header.
+pub fn sign_payload(secret: &str, body: &str) -> Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+    mac.update(body.as_bytes());
+    Ok(format!(
+        "sha256={}",
+        hex::encode(mac.finalize().into_bytes())
+    ))
+}
+
+/// Returns whether `ip` is loopback, private, link-local or otherwise not a public
+/// address a callback may be delivered to.
+pub fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_internal_ipv4(ip),
+        IpAddr::V6(ip) => ip.to_ipv4_mapped().map_or_else(
+            || {
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+            },
+            is_internal_ipv4,
+        ),
+    }
+}
+
+const fn is_internal_ipv4(ip: Ipv4Addr) -> bool {
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+}
+
+/// Resolves callback hosts at delivery time and drops internal addresses, so a host
+/// that passed validation cannot later be pointed at an internal service.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| !is_internal_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(
+                    format!("{} resolves only to internal addresses", name.as_str()).into(),
+                );
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// IP literals bypass the resolver, so their hosts are checked before sending.
+fn has_internal_ip_host(callback_url: &str) -> bool {
+    Url::parse(callback_url)
+        .ok()
+        .and_then(|url| {
+            url.host_str()?
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse()
+                .ok()
+        })
+        .is_some_and(is_internal_ip)
+}
+
+pub fn retry_delay(attempts: i32) -> Duration {
+    let mut backoff = WorkerBackoff::new(RETRY_MIN_DELAY_MS, RETRY_MAX_DELAY_MS, 2.0);
+    let mut delay = backoff.step_and_get_sleep_duration();
+    for _ in 1..attempts {
+        delay = backoff.step_and_get_sleep_duration();
+    }
+    delay
+}
+
+pub struct WebhookDispatcher {
+    client: Client,
+    secret: String,
+    max_attempts: i32,
+    poll_interval: Duration,
+    lease: Duration,
+    allow_internal_hosts: bool,
+}
+
+enum DeliveryOutcome {
+    Delivered(u16),
+    Failed(Option<u16>, String),
+}
+
+impl WebhookDispatcher {
+    pub fn new(settings: &WebhookSettings) -> Result<Self> {
+        Self::build(settings, false)
+    }
+
+    /// Builds a dispatcher that may deliver to internal addresses, for tests that
+    /// receive callbacks on a local server.
+    pub fn with_internal_hosts(settings: &WebhookSettings) -> Result<Self> {
+        Self::build(settings, true)
+    }
+
+    fn build(settings: &WebhookSettings, allow_internal_hosts: bool) -> Result<Self> {
+        ensure!(
+            settings.signing_enabled(),
+            "webhooks.secret must be set to sign webhook deliveries"
+        );
+        let request_timeout = Duration::from_secs(settings.request_timeout_secs);
+        let mut client = Client::builder()
+            .timeout(request_timeout)
+            .redirect(redirect::Policy::none());
+        if !allow_internal_hosts {
+            client = client.dns_resolver(Arc::new(PublicResolver));
+        }
+        Ok(Self {
+            client: client.build()?,
+            secret: settings.secret.clone(),
+            max_attempts: i32::try_from(settings.max_attempts).unwrap_or(i32::MAX),
+            poll_interval: Duration::from_secs(settings.poll_interval_secs),
+            lease: request_timeout + DELIVERY_LEASE_MARGIN,
+            allow_internal_hosts,
+        })
+    }
+
+    #[instrument(skip_all)]
+    pub async fn run(self, app_state: Arc<AppState>) {
+        let mut ticker = tokio::time::interval(self.poll_interval);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                () = app_state.shutdown.triggered() => break,
+            }
+
+            if let Err(err) = self.deliver_due(&app_state.database).await {
+                warn!(%err, "Failed to deliver due webhooks");
+            }
+        }
+
+        info!("Webhook dispatcher stopped");
+    }
+
+    pub async fn deliver_due(&self, database: &Database) -> Result<usize> {
+        let deliveries = database
+            .claim_due_webhooks(DELIVERY_BATCH_SIZE, self.lease)
+            .await?;
+        let claimed = deliveries.len();
+
+        futures::future::join_all(
+            deliveries
+                .into_iter()
+                .map(|delivery| self.deliver(database, delivery)),
+        )
+        .await;
+
+        Ok(claimed)
+    }
+
+    #[instrument(skip_all, fields(delivery_id = delivery.id, job_id = delivery.job_id, attempt = delivery.attempts))]
+    async fn deliver(&self, database: &Database, delivery: ClaimedWebhookDelivery) {
+        let outcome = self.send(&delivery).await;
+
+        let result = match outcome {
+            DeliveryOutcome::Delivered(status_code) => {
+                metrics::record_webhook_delivery("delivered");
+                info!(status_code, "Webhook delivered");
+                database
+                    .mark_webhook_delivered(delivery.id, i32::from(status_code))
+                    .await
+                    .map(|_| ())
+            }
+            DeliveryOutcome::Failed(status_code, error) => {
+                let retry_in = retry_delay(delivery.attempts);
+                let status = database
+                    .mark_webhook_failed(
+                        delivery.id,
+                        status_code.map(i32::from),
+                        &error,
+                        self.max_attempts,
+                        retry_in,
+                    )
+                    .await;
+
+                if let Ok(Some(status)) = &status {
+                    if status == "dead" {
+                        metrics::record_webhook_delivery("dead");
+                        warn!(?status_code, %error, "Webhook delivery exhausted, dead-lettered");
+                    } else {
+                        metrics::record_webhook_delivery("retry");
+                        warn!(?status_code, %error, ?retry_in, "Webhook delivery failed, retry scheduled");
+                    }
+                }
+                status.map(|_| ())
+            }
+        };
+
+        if let Err(err) = result {
+            warn!(%err, "Failed to record webhook delivery attempt");
+        }
+    }
+
+    async fn send(&self, delivery: &ClaimedWebhookDelivery) -> DeliveryOutcome {
+        debug!(callback_url = %delivery.callback_url, "Sending webhook");
+        if !self.allow_internal_hosts && has_internal_ip_host(&delivery.callback_url) {
+            return DeliveryOutcome::Failed(
+                None,
+                String::from("callback_url points to an internal address"),
+            );
+        }
+        let signature = match sign_payload(&self.secret, &delivery.payload) {
+            Ok(signature) => signature,
+            Err(err) => return DeliveryOutcome::Failed(None, truncate_error(err.to_string())),
+        };
+        let response = self
+            .client
+            .post(&delivery.callback_url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .header(DELIVERY_HEADER, delivery.id)
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => {
+                DeliveryOutcome::Delivered(response.status().as_u16())
+            }
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                DeliveryOutcome::Failed(
+                    Some(status.as_u16()),
+                    truncate_error(format!("HTTP {status}: {body}")),
+                )
+            }
+            Err(err) => DeliveryOutcome::Failed(None, truncate_error(error_chain(&err))),
+        }
+    }
+}
+
+/// Joins an error with its sources, since reqwest hides the cause (such as a
+/// rejected address) behind a generic "error sending request" message.
+fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+fn truncate_error(mut error: String) -> String {
+    if error.len() > MAX_ERROR_LEN {
+        let mut end = MAX_ERROR_LEN;
+        while !error.is_char_boundary(end) {
+            end -= 1;
+        }
+        error.truncate(end);
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_sign_payload_with_hex_hmac_sha256() {
+        // RFC 4231 test case 2.
+        assert_eq!(
+            sign_payload("Jefe", "what do ya want for nothing?").unwrap(),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn should_grow_retry_delay_until_capped_when_attempts_increase() {
+        let first = retry_delay(1);
+        let third = retry_delay(3);
+        let capped = retry_delay(50);
+
+        assert!(first >= Duration::from_secs(15) && first <= Duration::from_secs(30));
+        assert!(third >= Duration::from_mins(1) && third <= Duration::from_mins(2));
+        assert!(capped >= Duration::from_mins(30) && capped <= Duration::from_hours(1));
+    }
+
+    #[test]
+    fn should_flag_internal_ip_hosts_when_callback_uses_ip_literal() {
+        for callback_url in [
+            "http://127.0.0.1:8080/hook",
+            "http://10.0.0.5/hook",
+            "http://169.254.169.254/latest",
+            "http://[::1]/hook",
+            "http://[::ffff:192.168.1.1]/hook",
+        ] {
+            assert!(has_internal_ip_host(callback_url), "{callback_url}");
+        }
+        for callback_url in ["https://example.com/hook", "http://8.8.8.8/hook"] {
+            assert!(!has_internal_ip_host(callback_url), "{callback_url}");
+        }
+    }
+
+    #[test]
+    fn should_truncate_error_on_char_boundary() {
+        let error = truncate_error("é".repeat(MAX_ERROR_LEN));
+
+        assert!(error.len() <= MAX_ERROR_LEN);
+        assert!(error.chars().all(|c| c == 'é'));
+    }
+}
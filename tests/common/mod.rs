@@ -16,7 +16,8 @@ pub fn create_helius_api(mock_server: &MockServer) -> Result<HeliusApi> {
         DEFAULT_RPS,
         DEFAULT_MAX_CONCURRENT,
         DEFAULT_MAX_RATE_LIMIT_RETRIES,
-        mock_server.uri(),
+        &mock_server.uri(),
+        "",
     )
 }
 
@@ -4,6 +4,7 @@ mod jobs {
     use anyhow::{Ok, Result};
     use futures::future::join_all;
     use on_chain_event_indexer::db;
+    use on_chain_event_indexer::types::{ApiKey, ApiKeyLimits, JobDedup, JobWindow, NewJob};
     use pretty_assertions::assert_eq;
     use sqlx::postgres::PgPool;
     use std::collections::BTreeSet;
@@ -16,8 +17,8 @@ mod jobs {
         created_at: chrono::DateTime<chrono::Utc>,
         updated_at: chrono::DateTime<chrono::Utc>,
         worker_id: Option<i16>,
-        tx_limit: i16,
-        requested_hours: i16,
+        tx_limit: Option<i32>,
+        requested_hours: Option<i32>,
     }
 
     async fn get_inserted_job(pool: &PgPool, job_id: i64) -> Result<InsertedJob> {
@@ -55,11 +56,11 @@ mod jobs {
     async fn create_job(
         database: &db::Database,
         address: &str,
-        tx_limit: i16,
-        requested_hours: i16,
+        tx_limit: i32,
+        requested_hours: i32,
     ) -> Result<i64> {
         let job_id = database
-            .create_processing_job(address, tx_limit, requested_hours)
+            .create_processing_job(address, tx_limit, requested_hours, None, 0)
             .await?
             .expect("processing job should be created");
 
@@ -89,8 +90,8 @@ mod jobs {
         assert_eq!(inserted_job.address, "address-123");
         assert_eq!(inserted_job.status, "pending");
         assert_eq!(inserted_job.worker_id, None);
-        assert_eq!(inserted_job.tx_limit, 5000);
-        assert_eq!(inserted_job.requested_hours, 8);
+        assert_eq!(inserted_job.tx_limit, Some(5000));
+        assert_eq!(inserted_job.requested_hours, Some(8));
         assert!(inserted_job.created_at >= before_insert);
         assert!(inserted_job.created_at <= after_insert);
         assert!(inserted_job.updated_at >= before_insert);
@@ -124,22 +125,22 @@ mod jobs {
 
         assert_ne!(job1, job2);
         assert_eq!(inserted_job1.id, job1);
-        assert_eq!(inserted_job1.requested_hours, 24);
+        assert_eq!(inserted_job1.requested_hours, Some(24));
         assert_eq!(inserted_job1.address, "address-1");
         assert_eq!(inserted_job1.status, "pending");
         assert_eq!(inserted_job1.worker_id, None);
-        assert_eq!(inserted_job1.tx_limit, 2500);
+        assert_eq!(inserted_job1.tx_limit, Some(2500));
         assert!(inserted_job1.created_at >= before_insert);
         assert!(inserted_job1.created_at <= after_insert);
         assert!(inserted_job1.updated_at >= before_insert);
         assert!(inserted_job1.updated_at <= after_insert);
 
         assert_eq!(inserted_job2.id, job2);
-        assert_eq!(inserted_job2.requested_hours, 12);
+        assert_eq!(inserted_job2.requested_hours, Some(12));
         assert_eq!(inserted_job2.address, "address-1");
         assert_eq!(inserted_job2.status, "pending");
         assert_eq!(inserted_job2.worker_id, None);
-        assert_eq!(inserted_job2.tx_limit, 5000);
+        assert_eq!(inserted_job2.tx_limit, Some(5000));
         assert!(inserted_job2.created_at >= before_insert);
         assert!(inserted_job2.created_at <= after_insert);
         assert!(inserted_job2.updated_at >= before_insert);
@@ -275,195 +276,138 @@ mod jobs {
 
         Ok(())
     }
-}
-
-mod signatures {
-    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
-
-    use anyhow::{Ok, Result};
-    use chrono::Utc;
-    use futures::future::join_all;
-    use on_chain_event_indexer::{db, requests::RpcResponse};
-    use pretty_assertions::assert_eq;
-    use sqlx::postgres::PgPool;
-    use std::collections::BTreeSet;
-
-    fn signatures_response(names: &[&str]) -> Result<RpcResponse> {
-        let block_time = Utc::now().timestamp() - 60;
-        let result = names
-            .iter()
-            .map(|signature| {
-                serde_json::json!({
-                    "signature": signature,
-                    "blockTime": block_time,
-                })
-            })
-            .collect::<Vec<_>>();
-
-        Ok(serde_json::from_value(
-            serde_json::json!({ "result": result }),
-        )?)
-    }
-
-    #[sqlx::test(migrations = "./migrations")]
-    async fn should_allow_same_signature_for_different_owners_when_using_composite_pk(
-        pool: PgPool,
-    ) -> Result<()> {
-        let database = db::Database::from_pool(pool.clone());
-        let response = signatures_response(&["shared-signature"])?;
-
-        assert_eq!(database.write_signatures(&response, "owner-1").await?, 1);
-        assert_eq!(database.write_signatures(&response, "owner-2").await?, 1);
-        assert_eq!(database.write_signatures(&response, "owner-1").await?, 0);
 
-        let rows = sqlx::query_as::<_, (String, String)>(
+    async fn backdate_heartbeat(pool: &PgPool, job_id: i64, minutes: i32) -> Result<()> {
+        sqlx::query(
             "
-            SELECT owner_address, signature
-            FROM signatures
-            ORDER BY owner_address, signature
+            UPDATE processing_data
+            SET heartbeat_at = NOW() - $2 * INTERVAL '1 minute'
+            WHERE id = $1
             ",
         )
-        .fetch_all(&pool)
+        .bind(job_id)
+        .bind(minutes)
+        .execute(pool)
         .await?;
 
-        assert_eq!(
-            rows,
-            vec![
-                (String::from("owner-1"), String::from("shared-signature")),
-                (String::from("owner-2"), String::from("shared-signature")),
-            ]
-        );
-
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_return_disjoint_batches_and_mark_as_processing_when_claiming_signatures_concurrently(
-        pool: PgPool,
-    ) -> Result<()> {
+    async fn should_requeue_job_and_unblock_address_when_lease_expires(pool: PgPool) -> Result<()> {
         let database = db::Database::from_pool(pool.clone());
-        let signature_names = (0..200)
-            .map(|index| format!("signature-{index:03}"))
-            .collect::<Vec<_>>();
-        let signature_refs = signature_names
-            .iter()
-            .map(String::as_str)
-            .collect::<Vec<_>>();
-        let response = signatures_response(&signature_refs)?;
+        let crashed_id = create_job(&database, "crashed-address", 1000, 24).await?;
+        let queued_id = create_job(&database, "crashed-address", 1000, 24).await?;
 
-        assert_eq!(database.write_signatures(&response, "owner-1").await?, 200);
+        let claimed = database
+            .claim_pending_job(1)
+            .await?
+            .expect("oldest job should be claimed");
+        assert_eq!(claimed.job_id, crashed_id);
+        assert!(database.claim_pending_job(2).await?.is_none());
 
-        let claim_tasks = (0..2).map(|_| {
-            let database = db::Database::from_pool(pool.clone());
-            tokio::spawn(async move { database.get_unprocessed_signatures("owner-1", 100).await })
-        });
+        assert!(database.reap_expired_jobs(300, 3).await?.is_empty());
 
-        let task_results = join_all(claim_tasks).await;
-        let mut claimed = BTreeSet::new();
+        backdate_heartbeat(&pool, crashed_id, 10).await?;
+        let reaped = database.reap_expired_jobs(300, 3).await?;
 
-        for task_result in task_results {
-            let batch = task_result??;
-            assert_eq!(batch.len(), 100);
-            for signature in batch {
-                assert!(claimed.insert(signature));
-            }
-        }
+        assert_eq!(reaped.len(), 1);
+        assert_eq!(reaped[0].job_id, crashed_id);
+        assert_eq!(reaped[0].status, "pending");
+        assert_eq!(reaped[0].attempts, 1);
 
-        assert_eq!(claimed.len(), 200);
+        let requeued = get_inserted_job(&pool, crashed_id).await?;
+        assert_eq!(requeued.status, "pending");
+        assert_eq!(requeued.worker_id, None);
 
-        let rows = sqlx::query_as::<_, (String, bool, Option<chrono::DateTime<chrono::Utc>>)>(
-            "
-            SELECT signature, is_processing, processing_started_at
-            FROM signatures
-            WHERE owner_address = $1
-            ORDER BY signature
-            ",
-        )
-        .bind("owner-1")
-        .fetch_all(&pool)
-        .await?;
+        let reclaimed = database
+            .claim_pending_job(2)
+            .await?
+            .expect("requeued job should be claimable again");
+        assert_eq!(reclaimed.job_id, crashed_id);
+        assert_eq!(get_inserted_job(&pool, queued_id).await?.status, "pending");
 
-        assert_eq!(rows.len(), 200);
-        for (signature, is_processing, processing_started_at) in rows {
-            assert!(claimed.contains(&signature));
-            assert!(is_processing);
-            assert!(processing_started_at.is_some());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_fail_job_when_lease_expires_after_max_attempts(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let job_id = create_job(&database, "flaky-address", 1000, 24).await?;
+
+        for attempt in 1..=2 {
+            assert!(database.claim_pending_job(1).await?.is_some());
+            backdate_heartbeat(&pool, job_id, 10).await?;
+
+            let reaped = database.reap_expired_jobs(300, 2).await?;
+            assert_eq!(reaped.len(), 1);
+            assert_eq!(reaped[0].attempts, attempt);
         }
 
+        let job = get_inserted_job(&pool, job_id).await?;
+        assert_eq!(job.status, "error");
+        assert!(database.claim_pending_job(1).await?.is_none());
+
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_not_block_forever_on_failed_transaction_when_processing_signatures(
+    async fn should_extend_lease_only_for_owning_worker_when_recording_heartbeat(
         pool: PgPool,
     ) -> Result<()> {
         let database = db::Database::from_pool(pool.clone());
-        let response = signatures_response(&["stuck-signature"])?;
-
-        assert_eq!(database.write_signatures(&response, "owner-1").await?, 1);
-        assert_eq!(
-            database.get_unprocessed_signatures("owner-1", 100).await?,
-            vec![String::from("stuck-signature")]
-        );
-        assert!(
-            database
-                .get_unprocessed_signatures("owner-1", 100)
-                .await?
-                .is_empty()
-        );
+        let job_id = create_job(&database, "heartbeat-address", 1000, 24).await?;
 
-        sqlx::query(
-            "
-            UPDATE signatures
-            SET processing_started_at = NOW() - INTERVAL '6 minutes'
-            WHERE owner_address = $1
-              AND signature = $2
-            ",
-        )
-        .bind("owner-1")
-        .bind("stuck-signature")
-        .execute(&pool)
-        .await?;
+        assert!(database.claim_pending_job(4).await?.is_some());
+        backdate_heartbeat(&pool, job_id, 10).await?;
 
-        assert_eq!(
-            database.get_unprocessed_signatures("owner-1", 100).await?,
-            vec![String::from("stuck-signature")]
-        );
+        assert_eq!(database.record_heartbeat(job_id, 5).await?, 0);
+        assert_eq!(database.record_heartbeat(job_id, 4).await?, 1);
+        assert!(database.reap_expired_jobs(300, 3).await?.is_empty());
+        assert_eq!(get_inserted_job(&pool, job_id).await?.status, "indexing");
 
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_not_mark_job_as_ready_when_partial_transaction_failure_occurs(
+    async fn should_requeue_job_and_free_signatures_when_owning_worker_releases_it(
         pool: PgPool,
     ) -> Result<()> {
         let database = db::Database::from_pool(pool.clone());
-        let job_id = database
-            .create_processing_job("owner-1", 1000, 24)
-            .await?
-            .expect("processing job should be created");
-        let response = signatures_response(&["success-signature", "failed-signature"])?;
+        let job_id = create_job(&database, "released-address", 1000, 24).await?;
 
-        assert_eq!(database.write_signatures(&response, "owner-1").await?, 2);
-        assert!(database.claim_pending_job(1).await?.is_some());
+        assert!(database.claim_pending_job(4).await?.is_some());
+        sqlx::query(
+            "
+            INSERT INTO signatures (signature, owner_address, block_time, is_processed, is_processing, processing_started_at)
+            VALUES ('in-flight-signature', 'released-address', 0, FALSE, TRUE, NOW())
+            ",
+        )
+        .execute(&pool)
+        .await?;
 
-        let claimed = database.get_unprocessed_signatures("owner-1", 100).await?;
-        assert_eq!(claimed.len(), 2);
-        assert_eq!(
-            database
-                .mark_signatures_processed("owner-1", &[String::from("success-signature")])
-                .await?,
-            1
-        );
+        assert_eq!(database.release_job(job_id, 5).await?, 0);
+        assert_eq!(get_inserted_job(&pool, job_id).await?.status, "indexing");
 
-        let ready_update = database
-            .update_processing_status_by_job_id(job_id, "ready")
-            .await?;
-        assert_eq!(ready_update, 0);
+        assert_eq!(database.release_job(job_id, 4).await?, 1);
+        let released = get_inserted_job(&pool, job_id).await?;
+        assert_eq!(released.status, "pending");
+        assert_eq!(released.worker_id, None);
 
-        let status = sqlx::query_scalar::<_, String>(
+        let is_processing = sqlx::query_scalar::<_, bool>(
             "
-            SELECT status
+            SELECT is_processing
+            FROM signatures
+            WHERE signature = 'in-flight-signature'
+            ",
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert!(!is_processing);
+
+        let attempts = sqlx::query_scalar::<_, i32>(
+            "
+            SELECT attempts
             FROM processing_data
             WHERE id = $1
             ",
@@ -471,776 +415,3826 @@ mod signatures {
         .bind(job_id)
         .fetch_one(&pool)
         .await?;
-        assert_eq!(status, "indexing");
+        assert_eq!(attempts, 0);
+
+        let reclaimed = database
+            .claim_pending_job(5)
+            .await?
+            .expect("released job should be claimable again");
+        assert_eq!(reclaimed.job_id, job_id);
 
         Ok(())
     }
-}
 
-mod transactions {
-    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_enforce_status_transitions_when_cancelling_jobs(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let pending_id = create_job(&database, "pending-address", 1000, 24).await?;
+        let indexing_id = create_job(&database, "indexing-address", 1000, 24).await?;
 
-    use anyhow::{Ok, Result};
-    use on_chain_event_indexer::{db, requests::TransactionResult};
-    use pretty_assertions::assert_eq;
-    use serde_json::Value;
-    use sqlx::postgres::PgPool;
+        let claimed = database
+            .claim_pending_job(3)
+            .await?
+            .expect("oldest job should be claimed");
+        assert_eq!(claimed.job_id, pending_id);
+        let claimed = database
+            .claim_pending_job(4)
+            .await?
+            .expect("second job should be claimed");
+        assert_eq!(claimed.job_id, indexing_id);
+        database
+            .update_processing_status_by_job_id(pending_id, "error")
+            .await?;
+        let queued_id = create_job(&database, "queued-address", 1000, 24).await?;
 
-    fn load_transaction_fixture(name: &str) -> Result<Value> {
-        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
-            .join("tests/fixtures/helius/transactions")
-            .join(name);
-        let data = std::fs::read_to_string(path)?;
+        let transition = database
+            .cancel_job(queued_id, None)
+            .await?
+            .expect("job should exist");
+        assert_eq!(transition.previous_status, "pending");
+        assert_eq!(transition.status.as_deref(), Some("cancelled"));
 
-        Ok(serde_json::from_str(&data)?)
+        let transition = database
+            .cancel_job(indexing_id, None)
+            .await?
+            .expect("job should exist");
+        assert_eq!(transition.previous_status, "indexing");
+        assert_eq!(transition.status.as_deref(), Some("cancelling"));
+        assert!(database.is_cancellation_requested(indexing_id).await?);
+        assert_eq!(database.record_heartbeat(indexing_id, 4).await?, 1);
+
+        let transition = database
+            .cancel_job(pending_id, None)
+            .await?
+            .expect("job should exist");
+        assert_eq!(transition.previous_status, "error");
+        assert_eq!(transition.status, None);
+        assert!(database.cancel_job(i64::MAX, None).await?.is_none());
+
+        assert_eq!(database.finish_cancelled_job(indexing_id, 5).await?, 0);
+        assert_eq!(database.finish_cancelled_job(indexing_id, 4).await?, 1);
+        let cancelled = get_inserted_job(&pool, indexing_id).await?;
+        assert_eq!(cancelled.status, "cancelled");
+        assert_eq!(cancelled.worker_id, None);
+
+        Ok(())
     }
 
-    fn transaction_result_from_fixture(name: &str) -> Result<TransactionResult> {
-        let mut transaction =
-            serde_json::from_value::<TransactionResult>(load_transaction_fixture(name)?)?;
-        transaction.calculate_token_transfer();
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_requeue_only_errored_jobs_when_retrying(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let failed_id = create_job(&database, "failed-address", 1000, 24).await?;
 
-        Ok(transaction)
+        assert!(database.claim_pending_job(2).await?.is_some());
+        let transition = database
+            .retry_job(failed_id, None)
+            .await?
+            .expect("job should exist");
+        assert_eq!(transition.previous_status, "indexing");
+        assert_eq!(transition.status, None);
+
+        database
+            .update_processing_status_by_job_id(failed_id, "error")
+            .await?;
+        let transition = database
+            .retry_job(failed_id, None)
+            .await?
+            .expect("job should exist");
+        assert_eq!(transition.previous_status, "error");
+        assert_eq!(transition.status.as_deref(), Some("pending"));
+
+        let retried = get_inserted_job(&pool, failed_id).await?;
+        assert_eq!(retried.status, "pending");
+        assert_eq!(retried.worker_id, None);
+        assert!(database.retry_job(i64::MAX, None).await?.is_none());
+
+        let reclaimed = database
+            .claim_pending_job(3)
+            .await?
+            .expect("retried job should be claimable again");
+        assert_eq!(reclaimed.job_id, failed_id);
+
+        Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_not_duplicate_token_transfers_when_save_transaction_data_called_twice(
+    async fn should_notify_listeners_when_job_status_changes_or_progress_is_reported(
         pool: PgPool,
     ) -> Result<()> {
         let database = db::Database::from_pool(pool.clone());
-        let transaction = transaction_result_from_fixture("success.json")?;
-        let first_signature = transaction
-            .result
-            .transaction
-            .signatures
-            .first()
-            .expect("fixture should contain a signature")
-            .clone();
-        let expected_transfers = i64::try_from(transaction.token_transfer_changes.len())?;
+        let job_id = create_job(&database, "events-address", 1000, 24).await?;
+        let mut listener = database.job_events_listener().await?;
 
-        let first_save = database
-            .save_transaction_data(&[transaction], "tracked-owner")
+        assert!(database.claim_pending_job(1).await?.is_some());
+        let notification = listener.recv().await?;
+        assert_eq!(notification.channel(), db::JOB_EVENTS_CHANNEL);
+        assert_eq!(notification.payload(), job_id.to_string());
+
+        database.notify_job_progress(job_id).await?;
+        assert_eq!(listener.recv().await?.payload(), job_id.to_string());
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_notify_job_queue_when_job_becomes_claimable(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let mut listener = database.job_events_listener().await?;
+
+        let job_id = create_job(&database, "queue-address", 1000, 24).await?;
+        let notification = listener.recv().await?;
+        assert_eq!(notification.channel(), db::JOB_QUEUE_CHANNEL);
+        assert_eq!(notification.payload(), job_id.to_string());
+
+        assert!(database.claim_pending_job(1).await?.is_some());
+        let notification = listener.recv().await?;
+        assert_eq!(notification.channel(), db::JOB_EVENTS_CHANNEL);
+
+        database
+            .update_processing_status_by_job_id(job_id, "error")
             .await?;
-        assert_eq!(first_save.transactions, 1);
+        assert_eq!(listener.recv().await?.channel(), db::JOB_EVENTS_CHANNEL);
+
+        database.retry_job(job_id, None).await?;
+        let mut channels = vec![
+            listener.recv().await?.channel().to_string(),
+            listener.recv().await?.channel().to_string(),
+        ];
+        channels.sort();
         assert_eq!(
-            first_save.token_transfers,
-            u64::try_from(expected_transfers)?
+            channels,
+            vec![
+                db::JOB_EVENTS_CHANNEL.to_string(),
+                db::JOB_QUEUE_CHANNEL.to_string()
+            ]
         );
 
-        let duplicate_transaction = transaction_result_from_fixture("success.json")?;
-        let second_save = database
-            .save_transaction_data(&[duplicate_transaction], "tracked-owner")
-            .await?;
-        assert_eq!(second_save.transactions, 0);
-        assert_eq!(second_save.token_transfers, 0);
+        Ok(())
+    }
 
-        let transaction_count = sqlx::query_scalar::<_, i64>(
-            "
-            SELECT COUNT(*)
-            FROM transactions
-            WHERE owner_address = $1
-              AND signature = $2
-            ",
-        )
-        .bind("tracked-owner")
-        .bind(&first_signature)
-        .fetch_one(&pool)
-        .await?;
-        assert_eq!(transaction_count, 1);
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_notify_job_queue_when_same_address_job_stops_indexing(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let job_id = create_job(&database, "blocked-address", 1000, 24).await?;
+        create_job(&database, "blocked-address", 1000, 24).await?;
+        assert!(database.claim_pending_job(1).await?.is_some());
+        assert!(database.claim_pending_job(2).await?.is_none());
 
-        let transfer_count = sqlx::query_scalar::<_, i64>(
-            "
-            SELECT COUNT(*)
-            FROM token_transfers
-            WHERE tracked_owner = $1
-              AND signature = $2
-            ",
-        )
-        .bind("tracked-owner")
-        .bind(&first_signature)
-        .fetch_one(&pool)
-        .await?;
-        assert_eq!(transfer_count, expected_transfers);
+        let mut listener = database.job_events_listener().await?;
+        database
+            .update_processing_status_by_job_id(job_id, "ready")
+            .await?;
+        let mut channels = vec![
+            listener.recv().await?.channel().to_string(),
+            listener.recv().await?.channel().to_string(),
+        ];
+        channels.sort();
+        assert_eq!(
+            channels,
+            vec![
+                db::JOB_EVENTS_CHANNEL.to_string(),
+                db::JOB_QUEUE_CHANNEL.to_string()
+            ]
+        );
 
         Ok(())
     }
-}
 
-mod workflow {
-    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_finish_cancelling_job_when_its_lease_expires(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let job_id = create_job(&database, "stuck-address", 1000, 24).await?;
+        let queued_id = create_job(&database, "stuck-address", 1000, 24).await?;
 
-    use anyhow::{Ok, Result};
-    use chrono::Utc;
-    use on_chain_event_indexer::{AppState, db, indexer, requests::HeliusApi};
-    use pretty_assertions::assert_eq;
-    use serde_json::{Value, json};
-    use sqlx::postgres::PgPool;
-    use wiremock::matchers::{body_partial_json, method, path};
-    use wiremock::{Mock, MockServer, ResponseTemplate};
+        assert!(database.claim_pending_job(1).await?.is_some());
+        database.cancel_job(job_id, None).await?;
+        assert!(database.claim_pending_job(2).await?.is_none());
 
-    const OWNER: &str = "workflow-owner";
-    const SUCCESS_SIGNATURE: &str = "5SgJtP7Z9YvNQ9o4mN4YQk7G9xK8qP6eL3cR2wA1bV0m";
-    const FAILED_SIGNATURE: &str = "failed-signature";
-    const WORKER_ID: u32 = 7;
+        backdate_heartbeat(&pool, job_id, 10).await?;
+        let reaped = database.reap_expired_jobs(300, 3).await?;
+        assert_eq!(reaped.len(), 1);
+        assert_eq!(reaped[0].status, "cancelled");
 
-    struct WorkflowHarness {
-        pool: PgPool,
-        database: db::Database,
-        mock_server: MockServer,
+        let next = database
+            .claim_pending_job(2)
+            .await?
+            .expect("queued job should be claimable once cancellation finishes");
+        assert_eq!(next.job_id, queued_id);
+
+        Ok(())
     }
 
-    impl WorkflowHarness {
-        async fn new(pool: PgPool) -> Result<Self> {
-            let mock_server = MockServer::start().await;
-            let database = db::Database::from_pool(pool.clone());
+    async fn create_submitter(database: &db::Database, name: &str) -> Result<ApiKey> {
+        let key_hash = format!("{name}-hash");
+        database
+            .create_api_key(
+                name,
+                &key_hash,
+                ApiKeyLimits {
+                    max_concurrent_jobs: 100,
+                    max_tx_limit: 1000,
+                    max_requested_hours: 24,
+                    max_priority: 9,
+                    requests_per_minute: 60,
+                    allow_full_history: false,
+                },
+            )
+            .await?;
 
-            Ok(Self {
-                pool,
-                database,
-                mock_server,
-            })
-        }
+        Ok(database
+            .find_active_api_key(&key_hash)
+            .await?
+            .expect("submitter key should be active"))
+    }
 
-        fn app_state(&self) -> Result<AppState> {
-            Ok(AppState {
-                database: db::Database::from_pool(self.pool.clone()),
-                helius_api: HeliusApi::new(100, 10, 4, self.mock_server.uri())?,
-            })
+    async fn submit_jobs(
+        database: &db::Database,
+        api_key: &ApiKey,
+        count: usize,
+        priority: i16,
+    ) -> Result<Vec<i64>> {
+        let mut job_ids = Vec::with_capacity(count);
+        for index in 0..count {
+            let job = NewJob {
+                address: format!("{}-address-{priority}-{index}", api_key.name),
+                tx_limit: Some(1000),
+                window: JobWindow::Recent { hours: 24 },
+                callback_url: None,
+                priority,
+            };
+            job_ids.push(
+                database
+                    .create_processing_job_for_api_key(api_key, &job, &JobDedup::default())
+                    .await?
+                    .job_id,
+            );
         }
 
-        async fn create_job(&self, tx_limit: i16, requested_hours: i16) -> Result<i64> {
-            Ok(self
-                .database
-                .create_processing_job(OWNER, tx_limit, requested_hours)
-                .await?
-                .expect("processing job should be created"))
+        Ok(job_ids)
+    }
+
+    async fn claimed_submitter(pool: &PgPool, job_id: i64) -> Result<String> {
+        let name = sqlx::query_scalar::<_, String>(
+            "
+            SELECT k.name
+            FROM processing_data pd
+            JOIN api_keys k ON k.id = pd.api_key_id
+            WHERE pd.id = $1
+            ",
+        )
+        .bind(job_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(name)
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_claim_wide_and_unlimited_jobs_without_narrowing_limits(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let exchange = create_submitter(&database, "exchange").await?;
+        let jobs = [
+            NewJob {
+                address: String::from("hot-wallet-wide"),
+                tx_limit: Some(250_000),
+                window: JobWindow::Recent {
+                    hours: 24 * 365 * 5,
+                },
+                callback_url: None,
+                priority: 1,
+            },
+            NewJob {
+                address: String::from("hot-wallet-full"),
+                tx_limit: None,
+                window: JobWindow::FullHistory,
+                callback_url: None,
+                priority: 0,
+            },
+        ];
+        for job in &jobs {
+            database
+                .create_processing_job_for_api_key(&exchange, job, &JobDedup::default())
+                .await?;
         }
 
-        async fn create_job_for_address(
-            &self,
-            address: &str,
-            tx_limit: i16,
-            requested_hours: i16,
-        ) -> Result<i64> {
-            Ok(self
-                .database
-                .create_processing_job(address, tx_limit, requested_hours)
+        let wide = database
+            .claim_pending_job(1)
+            .await?
+            .expect("wide job should be claimable");
+        assert_eq!(wide.address, "hot-wallet-wide");
+        assert_eq!(wide.tx_limit, Some(250_000));
+        assert_eq!(wide.range_end, None);
+        assert_eq!(
+            (chrono::Utc::now() - wide.range_start).num_hours(),
+            24 * 365 * 5
+        );
+
+        let full = database
+            .claim_pending_job(2)
+            .await?
+            .expect("full-history job should be claimable");
+        assert_eq!(full.address, "hot-wallet-full");
+        assert_eq!(full.tx_limit, None);
+        assert_eq!(full.range_start, chrono::DateTime::UNIX_EPOCH);
+        assert_eq!(full.range_end, None);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_round_robin_submitters_when_one_key_floods_the_queue(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let flooder = create_submitter(&database, "flooder").await?;
+        let second = create_submitter(&database, "second").await?;
+        let third = create_submitter(&database, "third").await?;
+        submit_jobs(&database, &flooder, 10, 0).await?;
+        submit_jobs(&database, &second, 2, 0).await?;
+        submit_jobs(&database, &third, 2, 0).await?;
+
+        let mut claimed = Vec::new();
+        for worker_id in 1..=6 {
+            let job = database
+                .claim_pending_job(worker_id)
                 .await?
-                .expect("processing job should be created"))
+                .expect("pending job should be claimable");
+            claimed.push(claimed_submitter(&pool, job.job_id).await?);
         }
 
-        async fn process_once(&self) -> Result<Option<on_chain_event_indexer::types::JobInfo>> {
-            let app_state = self.app_state()?;
-            indexer::process_pending_job_once(&app_state, WORKER_ID).await
-        }
+        assert_eq!(
+            claimed,
+            vec!["flooder", "second", "third", "flooder", "second", "third"]
+        );
 
-        async fn job_status(&self, job_id: i64) -> Result<String> {
-            let status = sqlx::query_scalar::<_, String>(
-                "
-                SELECT status
-                FROM processing_data
-                WHERE id = $1
-                ",
-            )
-            .bind(job_id)
-            .fetch_one(&self.pool)
-            .await?;
+        Ok(())
+    }
 
-            Ok(status)
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_claim_higher_priority_tier_before_older_jobs_when_priorities_differ(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let batch = create_submitter(&database, "batch").await?;
+        let urgent = create_submitter(&database, "urgent").await?;
+        let older = submit_jobs(&database, &batch, 3, 0).await?;
+        let prioritized = submit_jobs(&database, &urgent, 2, 5).await?;
+        let unkeyed = create_job(&database, "unkeyed-address", 1000, 24).await?;
+
+        let mut claimed = Vec::new();
+        for worker_id in 1..=6 {
+            claimed.push(
+                database
+                    .claim_pending_job(worker_id)
+                    .await?
+                    .expect("pending job should be claimable")
+                    .job_id,
+            );
         }
 
-        async fn signature_rows(&self) -> Result<Vec<(String, bool, bool)>> {
-            self.signature_rows_for_address(OWNER).await
-        }
+        assert_eq!(claimed[..2], prioritized[..]);
+        assert_eq!(
+            claimed[2..],
+            [older[0], unkeyed, older[1], older[2]],
+            "keyless jobs take turns like any other submitter within a tier"
+        );
 
-        async fn signature_rows_for_address(
-            &self,
-            address: &str,
-        ) -> Result<Vec<(String, bool, bool)>> {
-            Ok(sqlx::query_as::<_, (String, bool, bool)>(
-                "
-                SELECT signature, is_processed, is_processing
-                FROM signatures
-                WHERE owner_address = $1
-                ORDER BY signature
-                ",
-            )
-            .bind(address)
-            .fetch_all(&self.pool)
-            .await?)
-        }
+        Ok(())
+    }
 
-        async fn count_transactions(&self) -> Result<i64> {
-            Ok(sqlx::query_scalar::<_, i64>(
-                "
-                SELECT COUNT(*)
-                FROM transactions
-                WHERE owner_address = $1
-                ",
-            )
-            .bind(OWNER)
-            .fetch_one(&self.pool)
-            .await?)
-        }
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_not_starve_small_submitters_when_workers_claim_concurrently(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let flooder = create_submitter(&database, "flooder").await?;
+        let second = create_submitter(&database, "second").await?;
+        let third = create_submitter(&database, "third").await?;
+        submit_jobs(&database, &flooder, 30, 0).await?;
+        submit_jobs(&database, &second, 3, 0).await?;
+        submit_jobs(&database, &third, 3, 0).await?;
+
+        let claim_tasks = (1_u32..=9).map(|worker_id| {
+            let database = db::Database::from_pool(pool.clone());
+            tokio::spawn(async move { database.claim_pending_job(worker_id).await })
+        });
 
-        async fn count_token_transfers(&self) -> Result<i64> {
-            Ok(sqlx::query_scalar::<_, i64>(
-                "
-                SELECT COUNT(*)
-                FROM token_transfers
-                WHERE tracked_owner = $1
-                ",
-            )
-            .bind(OWNER)
-            .fetch_one(&self.pool)
-            .await?)
+        let mut claimed_ids = BTreeSet::new();
+        for task_result in join_all(claim_tasks).await {
+            let claimed_job = task_result??.expect("each worker should claim one pending job");
+            assert!(claimed_ids.insert(claimed_job.job_id));
         }
 
-        async fn assert_no_persisted_data(&self) -> Result<()> {
-            assert!(self.signature_rows().await?.is_empty());
-            assert_eq!(self.count_transactions().await?, 0);
-            assert_eq!(self.count_token_transfers().await?, 0);
-            Ok(())
-        }
+        let per_submitter = sqlx::query_as::<_, (String, i64)>(
+            "
+            SELECT k.name, COUNT(*) FILTER (WHERE pd.status = 'indexing')
+            FROM api_keys k
+            JOIN processing_data pd ON pd.api_key_id = k.id
+            GROUP BY k.name
+            ORDER BY k.name
+            ",
+        )
+        .fetch_all(&pool)
+        .await?;
 
-        async fn assert_processed_signature(&self, signature: &str) -> Result<()> {
-            assert_eq!(
-                self.signature_rows().await?,
-                vec![(signature.to_string(), true, false)]
+        assert_eq!(claimed_ids.len(), 9);
+        assert_eq!(per_submitter.len(), 3);
+        // Claimers racing on the same snapshot can shift a single turn between submitters,
+        // but nobody waits behind the flooder's backlog.
+        for (name, claimed) in per_submitter {
+            assert!(
+                (2..=4).contains(&claimed),
+                "{name} claimed {claimed} of 9 jobs"
             );
-            Ok(())
         }
+
+        Ok(())
     }
 
-    fn signature_response(signatures: &[(&str, i64)]) -> Value {
-        let result = signatures
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_report_schema_behind_when_a_migration_is_missing(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        database.ensure_migrated().await?;
+
+        sqlx::query(
+            "DELETE FROM _sqlx_migrations WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)",
+        )
+        .execute(&pool)
+        .await?;
+
+        let err = database.ensure_migrated().await.unwrap_err();
+        assert!(err.to_string().contains("run `migrate` first"), "{err}");
+
+        Ok(())
+    }
+}
+
+mod signatures {
+    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
+    use anyhow::{Ok, Result};
+    use chrono::Utc;
+    use futures::future::join_all;
+    use on_chain_event_indexer::{
+        db,
+        requests::{RpcResponse, TransactionFetchError},
+    };
+    use pretty_assertions::assert_eq;
+    use sqlx::postgres::PgPool;
+    use std::collections::BTreeSet;
+    use std::time::Duration;
+
+    fn signatures_response(names: &[&str]) -> Result<RpcResponse> {
+        let block_time = Utc::now().timestamp() - 60;
+        let result = names
             .iter()
-            .map(|(signature, block_time)| {
-                json!({
+            .map(|signature| {
+                serde_json::json!({
                     "signature": signature,
                     "blockTime": block_time,
                 })
             })
             .collect::<Vec<_>>();
 
-        json!({
-            "jsonrpc": "2.0",
-            "id": "1",
-            "result": result,
-        })
+        Ok(serde_json::from_value(
+            serde_json::json!({ "result": result }),
+        )?)
     }
 
-    fn signature_page(prefix: &str, count: usize, block_time: i64) -> Vec<(String, i64)> {
-        (0..count)
-            .map(|index| (format!("{prefix}-{index:04}"), block_time))
-            .collect()
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_allow_same_signature_for_different_owners_when_using_composite_pk(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let response = signatures_response(&["shared-signature"])?;
+
+        assert_eq!(database.write_signatures(&response, "owner-1").await?, 1);
+        assert_eq!(database.write_signatures(&response, "owner-2").await?, 1);
+        assert_eq!(database.write_signatures(&response, "owner-1").await?, 0);
+
+        let rows = sqlx::query_as::<_, (String, String)>(
+            "
+            SELECT owner_address, signature
+            FROM signatures
+            ORDER BY owner_address, signature
+            ",
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        assert_eq!(
+            rows,
+            vec![
+                (String::from("owner-1"), String::from("shared-signature")),
+                (String::from("owner-2"), String::from("shared-signature")),
+            ]
+        );
+
+        Ok(())
     }
 
-    fn signature_refs(signatures: &[(String, i64)]) -> Vec<(&str, i64)> {
-        signatures
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_return_disjoint_batches_and_mark_as_processing_when_claiming_signatures_concurrently(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let signature_names = (0..200)
+            .map(|index| format!("signature-{index:03}"))
+            .collect::<Vec<_>>();
+        let signature_refs = signature_names
             .iter()
-            .map(|(signature, block_time)| (signature.as_str(), *block_time))
-            .collect()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let response = signatures_response(&signature_refs)?;
+
+        assert_eq!(database.write_signatures(&response, "owner-1").await?, 200);
+
+        let claim_tasks = (0..2).map(|_| {
+            let database = db::Database::from_pool(pool.clone());
+            tokio::spawn(async move { database.get_unprocessed_signatures("owner-1", 100).await })
+        });
+
+        let task_results = join_all(claim_tasks).await;
+        let mut claimed = BTreeSet::new();
+
+        for task_result in task_results {
+            let batch = task_result??;
+            assert_eq!(batch.len(), 100);
+            for signature in batch {
+                assert!(claimed.insert(signature));
+            }
+        }
+
+        assert_eq!(claimed.len(), 200);
+
+        let rows = sqlx::query_as::<_, (String, bool, Option<chrono::DateTime<chrono::Utc>>)>(
+            "
+            SELECT signature, is_processing, processing_started_at
+            FROM signatures
+            WHERE owner_address = $1
+            ORDER BY signature
+            ",
+        )
+        .bind("owner-1")
+        .fetch_all(&pool)
+        .await?;
+
+        assert_eq!(rows.len(), 200);
+        for (signature, is_processing, processing_started_at) in rows {
+            assert!(claimed.contains(&signature));
+            assert!(is_processing);
+            assert!(processing_started_at.is_some());
+        }
+
+        Ok(())
     }
 
-    fn transaction_fixture(signature: &str) -> Result<Value> {
-        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
-            .join("tests/fixtures/helius/transactions/success.json");
-        let mut value: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
-        value["result"]["transaction"]["signatures"] = json!([signature]);
-        Ok(value)
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_not_block_forever_on_failed_transaction_when_processing_signatures(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let response = signatures_response(&["stuck-signature"])?;
+
+        assert_eq!(database.write_signatures(&response, "owner-1").await?, 1);
+        assert_eq!(
+            database.get_unprocessed_signatures("owner-1", 100).await?,
+            vec![String::from("stuck-signature")]
+        );
+        assert!(
+            database
+                .get_unprocessed_signatures("owner-1", 100)
+                .await?
+                .is_empty()
+        );
+
+        sqlx::query(
+            "
+            UPDATE signatures
+            SET processing_started_at = NOW() - INTERVAL '6 minutes'
+            WHERE owner_address = $1
+              AND signature = $2
+            ",
+        )
+        .bind("owner-1")
+        .bind("stuck-signature")
+        .execute(&pool)
+        .await?;
+
+        assert_eq!(
+            database.get_unprocessed_signatures("owner-1", 100).await?,
+            vec![String::from("stuck-signature")]
+        );
+
+        Ok(())
     }
 
-    fn transaction_request(signature: &str) -> Value {
-        json!({
-            "method": "getTransaction",
-            "params": [
-                signature,
-                {
-                    "encoding": "jsonParsed",
-                    "maxSupportedTransactionVersion": 0,
-                }
-            ]
-        })
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_not_mark_job_as_ready_when_partial_transaction_failure_occurs(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let job_id = database
+            .create_processing_job("owner-1", 1000, 24, None, 0)
+            .await?
+            .expect("processing job should be created");
+        let response = signatures_response(&["success-signature", "failed-signature"])?;
+
+        assert_eq!(database.write_signatures(&response, "owner-1").await?, 2);
+        assert!(database.claim_pending_job(1).await?.is_some());
+
+        let claimed = database.get_unprocessed_signatures("owner-1", 100).await?;
+        assert_eq!(claimed.len(), 2);
+        assert_eq!(
+            database
+                .mark_signatures_processed("owner-1", &[String::from("success-signature")])
+                .await?,
+            1
+        );
+
+        let ready_update = database
+            .update_processing_status_by_job_id(job_id, "ready")
+            .await?;
+        assert_eq!(ready_update, 0);
+
+        let status = sqlx::query_scalar::<_, String>(
+            "
+            SELECT status
+            FROM processing_data
+            WHERE id = $1
+            ",
+        )
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(status, "indexing");
+
+        Ok(())
     }
 
-    async fn mount_signature_response(mock_server: &MockServer, body: Value, count: u64) {
-        Mock::given(method("POST"))
-            .and(path("/"))
-            .and(body_partial_json(
-                json!({ "method": "getSignaturesForAddress" }),
-            ))
-            .respond_with(ResponseTemplate::new(200).set_body_json(body))
-            .up_to_n_times(count)
-            .expect(count)
-            .mount(mock_server)
-            .await;
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_back_off_then_dead_letter_signature_when_fetch_keeps_failing(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let job_id = database
+            .create_processing_job("owner-1", 1000, 24, None, 0)
+            .await?
+            .expect("processing job should be created");
+        let response = signatures_response(&["failed-signature"])?;
+        let error = TransactionFetchError {
+            signature: String::from("failed-signature"),
+            status_code: Some(503),
+            rpc_code: None,
+            message: String::from("service unavailable"),
+        };
+
+        assert_eq!(database.write_signatures(&response, "owner-1").await?, 1);
+        assert!(database.claim_pending_job(1).await?.is_some());
+
+        for attempt in 1..=2 {
+            assert_eq!(
+                database.get_unprocessed_signatures("owner-1", 100).await?,
+                vec![String::from("failed-signature")]
+            );
+            let failures = database
+                .record_signature_failures(
+                    "owner-1",
+                    std::slice::from_ref(&error),
+                    3,
+                    Duration::from_mins(1),
+                    Duration::from_mins(90),
+                )
+                .await?;
+            assert_eq!(failures.len(), 1);
+            assert_eq!(failures[0].attempts, attempt);
+            assert!(!failures[0].is_dead);
+
+            let retry_in = failures[0]
+                .next_attempt_at
+                .expect("retry should be scheduled")
+                - Utc::now();
+            let expected_mins = 1 << (attempt - 1);
+            assert!(retry_in.num_seconds() > expected_mins * 60 - 5);
+            assert!(retry_in.num_seconds() <= expected_mins * 60);
+            assert_eq!(
+                database.get_next_signature_retry_at("owner-1").await?,
+                failures[0].next_attempt_at
+            );
+            assert!(
+                database
+                    .get_unprocessed_signatures("owner-1", 100)
+                    .await?
+                    .is_empty()
+            );
+            assert_eq!(
+                database
+                    .update_processing_status_by_job_id(job_id, "ready")
+                    .await?,
+                0
+            );
+
+            sqlx::query("UPDATE signatures SET next_attempt_at = NOW() WHERE owner_address = $1")
+                .bind("owner-1")
+                .execute(&pool)
+                .await?;
+        }
+
+        assert_eq!(
+            database.get_unprocessed_signatures("owner-1", 100).await?,
+            vec![String::from("failed-signature")]
+        );
+        let failures = database
+            .record_signature_failures(
+                "owner-1",
+                &[error],
+                3,
+                Duration::from_mins(1),
+                Duration::from_mins(90),
+            )
+            .await?;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].attempts, 3);
+        assert!(failures[0].is_dead);
+        assert_eq!(failures[0].next_attempt_at, None);
+        assert_eq!(database.get_next_signature_retry_at("owner-1").await?, None);
+        assert!(
+            database
+                .get_unprocessed_signatures("owner-1", 100)
+                .await?
+                .is_empty()
+        );
+
+        let dead = database
+            .get_job_dead_signatures(job_id, None)
+            .await?
+            .expect("job should exist");
+        assert_eq!(dead.signatures.len(), 1);
+        assert_eq!(dead.signatures[0].signature, "failed-signature");
+        assert_eq!(dead.signatures[0].last_status_code, Some(503));
+        assert_eq!(dead.signatures[0].last_rpc_code, None);
+        assert_eq!(
+            dead.signatures[0].last_error.as_deref(),
+            Some("service unavailable")
+        );
+        assert_eq!(
+            database
+                .update_processing_status_by_job_id(job_id, "ready")
+                .await?,
+            1
+        );
+        assert!(
+            database
+                .get_job_dead_signatures(job_id + 1, None)
+                .await?
+                .is_none()
+        );
+
+        Ok(())
     }
+}
+
+mod transactions {
+    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
+    use anyhow::{Ok, Result};
+    use on_chain_event_indexer::{
+        db,
+        requests::{TokenTransferChange, TransactionResult},
+        types::{
+            AssetTypeFilter, TransactionCursor, TransactionFilter, TransactionStatusFilter,
+            TransferCursor, TransferFilter, TransferTypeFilter,
+        },
+    };
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+    use sqlx::postgres::PgPool;
+
+    fn load_transaction_fixture(name: &str) -> Result<Value> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/helius/transactions")
+            .join(name);
+        let data = std::fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    fn transaction_result_from_fixture(name: &str) -> Result<TransactionResult> {
+        let mut transaction =
+            serde_json::from_value::<TransactionResult>(load_transaction_fixture(name)?)?;
+        transaction.calculate_token_transfer();
+
+        Ok(transaction)
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_not_duplicate_token_transfers_when_save_transaction_data_called_twice(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let transaction = transaction_result_from_fixture("success.json")?;
+        let first_signature = transaction
+            .result
+            .transaction
+            .signatures
+            .first()
+            .expect("fixture should contain a signature")
+            .clone();
+        let expected_transfers = i64::try_from(transaction.token_transfer_changes.len())?;
+
+        let first_save = database
+            .save_transaction_data(&[transaction], "tracked-owner")
+            .await?;
+        assert_eq!(first_save.transactions, 1);
+        assert_eq!(
+            first_save.token_transfers,
+            u64::try_from(expected_transfers)?
+        );
+
+        let duplicate_transaction = transaction_result_from_fixture("success.json")?;
+        let second_save = database
+            .save_transaction_data(&[duplicate_transaction], "tracked-owner")
+            .await?;
+        assert_eq!(second_save.transactions, 0);
+        assert_eq!(second_save.token_transfers, 0);
+
+        let transaction_count = sqlx::query_scalar::<_, i64>(
+            "
+            SELECT COUNT(*)
+            FROM transactions
+            WHERE owner_address = $1
+              AND signature = $2
+            ",
+        )
+        .bind("tracked-owner")
+        .bind(&first_signature)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(transaction_count, 1);
+
+        let transfer_count = sqlx::query_scalar::<_, i64>(
+            "
+            SELECT COUNT(*)
+            FROM token_transfers
+            WHERE tracked_owner = $1
+              AND signature = $2
+            ",
+        )
+        .bind("tracked-owner")
+        .bind(&first_signature)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(transfer_count, expected_transfers);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_store_transfer_direction_relative_to_tracked_owner_when_saving(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let transaction = transaction_result_from_fixture("success.json")?;
+        let tracked_owner = "8TPACXaKotSZ7WXktfmKDRhgoypyGXNzo1ctr2YBzxLc";
+
+        database
+            .save_transaction_data(&[transaction], tracked_owner)
+            .await?;
+
+        let native_direction = sqlx::query_scalar::<_, String>(
+            "
+            SELECT direction
+            FROM token_transfers
+            WHERE tracked_owner = $1
+              AND asset_type = 'native'
+              AND instruction_idx = 4
+            ",
+        )
+        .bind(tracked_owner)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(native_direction, "out");
+
+        let unknown_count = sqlx::query_scalar::<_, i64>(
+            "
+            SELECT COUNT(*)
+            FROM token_transfers
+            WHERE direction = 'unknown'
+            ",
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(unknown_count, 0);
+
+        Ok(())
+    }
+
+    const BACKFILL_TOKEN_TRANSFER_DIRECTIONS: &str =
+        include_str!("../migrations/20261018020000_backfill_token_transfer_directions.sql");
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_backfill_unknown_directions_and_drop_stale_duplicates(
+        pool: PgPool,
+    ) -> Result<()> {
+        let insert_legacy_transfer = "
+            INSERT INTO token_transfers
+            (tracked_owner, signature, source_owner, destination_owner, amount_raw, asset_type, transfer_type, direction, instruction_idx, slot, block_time)
+            VALUES ('tracked', $1, $2, $3, 100, 'native', 'transfer', $4, 0, 1, 1)
+        ";
+
+        for (signature, source, destination, direction) in [
+            ("sig-in", "other", "tracked", "unknown"),
+            ("sig-out", "tracked", "other", "unknown"),
+            ("sig-self", "tracked", "tracked", "unknown"),
+            ("sig-external", "other", "another", "unknown"),
+            ("sig-duplicate", "tracked", "other", "unknown"),
+            ("sig-duplicate", "tracked", "other", "out"),
+        ] {
+            sqlx::query(insert_legacy_transfer)
+                .bind(signature)
+                .bind(source)
+                .bind(destination)
+                .bind(direction)
+                .execute(&pool)
+                .await?;
+        }
+
+        for _ in 0..2 {
+            sqlx::raw_sql(BACKFILL_TOKEN_TRANSFER_DIRECTIONS)
+                .execute(&pool)
+                .await?;
+        }
+
+        let rows = sqlx::query_as::<_, (String, String)>(
+            "
+            SELECT signature, direction
+            FROM token_transfers
+            ORDER BY signature
+            ",
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        assert_eq!(
+            rows,
+            vec![
+                (String::from("sig-duplicate"), String::from("out")),
+                (String::from("sig-external"), String::from("external")),
+                (String::from("sig-in"), String::from("in")),
+                (String::from("sig-out"), String::from("out")),
+                (String::from("sig-self"), String::from("self")),
+            ]
+        );
+
+        Ok(())
+    }
+
+    async fn signature_state(pool: &PgPool, signature: &str) -> Result<(bool, bool)> {
+        Ok(sqlx::query_as::<_, (bool, bool)>(
+            "
+            SELECT is_processed, is_processing
+            FROM signatures
+            WHERE owner_address = $1
+              AND signature = $2
+            ",
+        )
+        .bind("tracked-owner")
+        .bind(signature)
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn claim_signature(database: &db::Database, signature: &str) -> Result<()> {
+        database
+            .write_signatures(
+                &serde_json::from_value(serde_json::json!({
+                    "result": [{ "signature": signature, "blockTime": 1 }]
+                }))?,
+                "tracked-owner",
+            )
+            .await?;
+        assert_eq!(
+            database
+                .get_unprocessed_signatures("tracked-owner", 10)
+                .await?,
+            vec![signature.to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_save_transactions_and_mark_signatures_when_saving_batch(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let transaction = transaction_result_from_fixture("success.json")?;
+        let signature = transaction.result.transaction.signatures[0].clone();
+        let expected_transfers = u64::try_from(transaction.token_transfer_changes.len())?;
+        claim_signature(&database, &signature).await?;
+
+        let save_stats = database
+            .save_transaction_batch(
+                &[transaction],
+                "tracked-owner",
+                std::slice::from_ref(&signature),
+                false,
+            )
+            .await?;
+
+        assert_eq!(save_stats.transactions, 1);
+        assert_eq!(save_stats.token_transfers, expected_transfers);
+        assert_eq!(save_stats.signatures_processed, 1);
+        assert_eq!(signature_state(&pool, &signature).await?, (true, false));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_roll_back_whole_batch_when_token_transfer_insert_fails(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let transaction = transaction_result_from_fixture("success.json")?;
+        let signature = transaction.result.transaction.signatures[0].clone();
+        claim_signature(&database, &signature).await?;
+
+        sqlx::raw_sql(
+            "
+            CREATE FUNCTION reject_token_transfers() RETURNS trigger AS $$
+            BEGIN
+                RAISE EXCEPTION 'token transfer insert rejected';
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE TRIGGER reject_token_transfers
+            BEFORE INSERT ON token_transfers
+            FOR EACH ROW EXECUTE FUNCTION reject_token_transfers();
+            ",
+        )
+        .execute(&pool)
+        .await?;
+
+        let result = database
+            .save_transaction_batch(
+                &[transaction],
+                "tracked-owner",
+                std::slice::from_ref(&signature),
+                false,
+            )
+            .await;
+        assert!(result.is_err());
+
+        let transaction_count = sqlx::query_scalar::<_, i64>(
+            "
+            SELECT COUNT(*)
+            FROM transactions
+            WHERE owner_address = $1
+            ",
+        )
+        .bind("tracked-owner")
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(transaction_count, 0);
+        assert_eq!(signature_state(&pool, &signature).await?, (false, true));
+
+        Ok(())
+    }
+
+    fn listed_transaction(
+        signature: &str,
+        block_time: i64,
+        fee: i64,
+        err: Value,
+    ) -> Result<TransactionResult> {
+        let mut value = load_transaction_fixture("success.json")?;
+        value["result"]["transaction"]["signatures"] = serde_json::json!([signature]);
+        value["result"]["blockTime"] = block_time.into();
+        value["result"]["meta"]["fee"] = fee.into();
+        value["result"]["meta"]["err"] = err;
+
+        Ok(serde_json::from_value::<TransactionResult>(value)?)
+    }
+
+    const fn transaction_filter(limit: i64) -> TransactionFilter {
+        TransactionFilter {
+            status: None,
+            min_fee: None,
+            min_compute_units: None,
+            cursor: None,
+            limit,
+        }
+    }
+
+    async fn seed_listed_transactions(database: &db::Database) -> Result<i64> {
+        let job_id = database
+            .create_processing_job("listing-owner", 1000, i32::from(i16::MAX), None, 0)
+            .await?
+            .expect("processing job should be created");
+        let failed = serde_json::json!({ "InstructionError": [0, "InvalidAccountData"] });
+        let transactions = [
+            listed_transaction("listing-a", 1_700_000_300, 5_000, Value::Null)?,
+            listed_transaction("listing-b", 1_700_000_200, 9_000, failed)?,
+            listed_transaction("listing-c", 1_700_000_200, 7_000, Value::Null)?,
+            listed_transaction("listing-d", 1_700_000_100, 5_000, Value::Null)?,
+        ];
+        database
+            .save_transaction_data(&transactions, "listing-owner")
+            .await?;
+        database
+            .save_transaction_data(
+                &[listed_transaction(
+                    "other",
+                    1_700_000_400,
+                    5_000,
+                    Value::Null,
+                )?],
+                "other-owner",
+            )
+            .await?;
+
+        Ok(job_id)
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_page_job_transactions_newest_first_when_following_cursor(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool);
+        let job_id = seed_listed_transactions(&database).await?;
+
+        let first = database
+            .get_job_transactions(job_id, None, &transaction_filter(2))
+            .await?
+            .expect("job should exist");
+        let signatures = first
+            .transactions
+            .iter()
+            .map(|tx| tx.signature.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(signatures, vec!["listing-a", "listing-c"]);
+        assert_eq!(first.next_cursor.as_deref(), Some("1700000200:listing-c"));
+
+        let mut filter = transaction_filter(2);
+        filter.cursor = Some(
+            first
+                .next_cursor
+                .as_deref()
+                .expect("first page should have a cursor")
+                .parse::<TransactionCursor>()?,
+        );
+        let second = database
+            .get_job_transactions(job_id, None, &filter)
+            .await?
+            .expect("job should exist");
+        let signatures = second
+            .transactions
+            .iter()
+            .map(|tx| tx.signature.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(signatures, vec!["listing-b", "listing-d"]);
+        assert_eq!(second.next_cursor, None);
+        assert!(
+            database
+                .get_job_transactions(i64::MAX, None, &transaction_filter(2))
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_filter_job_transactions_by_status_and_fee(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool);
+        let job_id = seed_listed_transactions(&database).await?;
+
+        let mut filter = transaction_filter(10);
+        filter.status = Some(TransactionStatusFilter::Failed);
+        let failed = database
+            .get_job_transactions(job_id, None, &filter)
+            .await?
+            .expect("job should exist");
+        assert_eq!(failed.transactions.len(), 1);
+        assert_eq!(failed.transactions[0].signature, "listing-b");
+        assert!(failed.transactions[0].err.is_some());
+
+        let mut filter = transaction_filter(10);
+        filter.status = Some(TransactionStatusFilter::Success);
+        filter.min_fee = Some(6_000);
+        let expensive = database
+            .get_job_transactions(job_id, None, &filter)
+            .await?
+            .expect("job should exist");
+        assert_eq!(expensive.transactions.len(), 1);
+        assert_eq!(expensive.transactions[0].signature, "listing-c");
+        assert_eq!(expensive.transactions[0].fee, 7_000);
+
+        let mut filter = transaction_filter(10);
+        filter.min_compute_units = Some(i32::MAX);
+        let heavy = database
+            .get_job_transactions(job_id, None, &filter)
+            .await?
+            .expect("job should exist");
+        assert!(heavy.transactions.is_empty());
+
+        Ok(())
+    }
+
+    fn listed_transfer(
+        asset_type: &str,
+        transfer_type: &str,
+        token_mint: Option<&str>,
+        counterparty: &str,
+        amount_raw: i128,
+        instruction_idx: i32,
+    ) -> TokenTransferChange {
+        TokenTransferChange {
+            token_mint: token_mint.map(str::to_string),
+            token_program: None,
+            source_owner: Some(String::from("listing-owner")),
+            destination_owner: Some(counterparty.to_string()),
+            source_token_account: None,
+            destination_token_account: None,
+            amount_raw,
+            amount_ui: None,
+            decimals: None,
+            transfer_type: transfer_type.to_string(),
+            asset_type: asset_type.to_string(),
+            authority: None,
+            instruction_idx: Some(instruction_idx),
+            inner_idx: None,
+        }
+    }
+
+    async fn seed_listed_transfers(database: &db::Database) -> Result<i64> {
+        let job_id = database
+            .create_processing_job("listing-owner", 1000, i32::from(i16::MAX), None, 0)
+            .await?
+            .expect("processing job should be created");
+
+        let mut newer = listed_transaction("transfers-newer", 1_700_000_200, 5_000, Value::Null)?;
+        newer.token_transfer_changes = vec![
+            listed_transfer("native", "transfer", None, "alice", 1_000, 0),
+            listed_transfer("spl", "transfer", Some("mint-a"), "bob", 50, 1),
+        ];
+        let mut older = listed_transaction("transfers-older", 1_700_000_100, 5_000, Value::Null)?;
+        older.token_transfer_changes = vec![
+            listed_transfer("spl", "mint", Some("mint-a"), "alice", 500, 0),
+            listed_transfer("spl", "burn", Some("mint-b"), "carol", 5, 1),
+        ];
+        database
+            .save_transaction_data(&[newer, older], "listing-owner")
+            .await?;
+
+        Ok(job_id)
+    }
+
+    const fn transfer_filter(limit: i64) -> TransferFilter {
+        TransferFilter {
+            token_mint: None,
+            asset_type: None,
+            transfer_type: None,
+            counterparty: None,
+            min_amount: None,
+            max_amount: None,
+            cursor: None,
+            limit,
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_page_job_transfers_newest_first_when_following_cursor(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool);
+        let job_id = seed_listed_transfers(&database).await?;
+
+        let first = database
+            .get_job_transfers(job_id, None, &transfer_filter(3))
+            .await?
+            .expect("job should exist");
+        assert_eq!(first.transfers.len(), 3);
+        assert_eq!(first.transfers[0].signature, "transfers-newer");
+        assert_eq!(first.transfers[1].signature, "transfers-newer");
+        assert_eq!(first.transfers[2].signature, "transfers-older");
+
+        let mut filter = transfer_filter(3);
+        filter.cursor = Some(
+            first
+                .next_cursor
+                .as_deref()
+                .expect("first page should have a cursor")
+                .parse::<TransferCursor>()?,
+        );
+        let second = database
+            .get_job_transfers(job_id, None, &filter)
+            .await?
+            .expect("job should exist");
+        assert_eq!(second.transfers.len(), 1);
+        assert_eq!(second.transfers[0].signature, "transfers-older");
+        assert_ne!(second.transfers[0].id, first.transfers[2].id);
+        assert_eq!(second.next_cursor, None);
+        assert!(
+            database
+                .get_job_transfers(i64::MAX, None, &transfer_filter(3))
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_filter_job_transfers_by_mint_type_counterparty_and_amount(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool);
+        let job_id = seed_listed_transfers(&database).await?;
+
+        let mut filter = transfer_filter(10);
+        filter.token_mint = Some(String::from("mint-a"));
+        filter.transfer_type = Some(TransferTypeFilter::Mint);
+        let minted = database
+            .get_job_transfers(job_id, None, &filter)
+            .await?
+            .expect("job should exist");
+        assert_eq!(minted.transfers.len(), 1);
+        assert_eq!(minted.transfers[0].amount_raw, "500");
+        assert_eq!(minted.transfers[0].direction, "out");
+
+        let mut filter = transfer_filter(10);
+        filter.asset_type = Some(AssetTypeFilter::Spl);
+        filter.min_amount = Some(10);
+        filter.max_amount = Some(100);
+        let mid_sized = database
+            .get_job_transfers(job_id, None, &filter)
+            .await?
+            .expect("job should exist");
+        assert_eq!(mid_sized.transfers.len(), 1);
+        assert_eq!(
+            mid_sized.transfers[0].destination_owner.as_deref(),
+            Some("bob")
+        );
+
+        let mut filter = transfer_filter(10);
+        filter.counterparty = Some(String::from("alice"));
+        let with_alice = database
+            .get_job_transfers(job_id, None, &filter)
+            .await?
+            .expect("job should exist");
+        let amounts = with_alice
+            .transfers
+            .iter()
+            .map(|transfer| transfer.amount_raw.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(amounts, vec!["1000", "500"]);
+
+        Ok(())
+    }
+}
+
+mod charts {
+    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
+    use anyhow::{Ok, Result};
+    use on_chain_event_indexer::{db, requests::TransactionResult, types::ChartBucket};
+    use pretty_assertions::assert_eq;
+    use serde_json::{Value, json};
+    use sqlx::postgres::PgPool;
+
+    const OWNER: &str = "charts-owner";
+
+    fn transaction_result(signature: &str, err: Value) -> Result<TransactionResult> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/helius/transactions/success.json");
+        let mut value: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        value["result"]["transaction"]["signatures"] = json!([signature]);
+        value["result"]["meta"]["err"] = err;
+
+        let mut transaction = serde_json::from_value::<TransactionResult>(value)?;
+        transaction.calculate_token_transfer();
+
+        Ok(transaction)
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_return_none_when_charts_requested_for_missing_job(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool);
+
+        assert!(
+            database
+                .get_job_charts(42, None, ChartBucket::Hour)
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_aggregate_transactions_into_buckets_when_job_window_covers_them(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let job_id = database
+            .create_processing_job(OWNER, 1000, i32::from(i16::MAX), None, 0)
+            .await?
+            .expect("processing job should be created");
+
+        let transactions = [
+            transaction_result("charts-success", Value::Null)?,
+            transaction_result(
+                "charts-failed",
+                json!({ "InstructionError": [0, "InvalidAccountData"] }),
+            )?,
+        ];
+        database.save_transaction_data(&transactions, OWNER).await?;
+        database
+            .save_transaction_data(&[transaction_result("other-owner", Value::Null)?], "other")
+            .await?;
+
+        let charts = database
+            .get_job_charts(job_id, None, ChartBucket::Day)
+            .await?
+            .expect("charts should be returned for an existing job");
+
+        assert_eq!(charts.bucket, ChartBucket::Day);
+        assert_eq!(charts.success_count, 1);
+        assert_eq!(charts.failed_count, 1);
+        assert_eq!(charts.total_fee, 248_000);
+        assert_eq!(charts.tx_time_line.len(), 1);
+        assert_eq!(charts.tx_time_line[0].transactions, 2);
+        assert_eq!(charts.tx_time_line[0].successful, 1);
+        assert_eq!(charts.tx_time_line[0].failed, 1);
+        assert_eq!(charts.tx_time_line[0].fee, 248_000);
+        assert_eq!(
+            charts.tx_time_line[0].bucket.timestamp() % 86_400,
+            0,
+            "day buckets should be truncated to midnight UTC"
+        );
+
+        let expected_native_volume = sqlx::query_scalar::<_, String>(
+            "
+            SELECT COALESCE(SUM(amount_raw), 0)::text
+            FROM token_transfers
+            WHERE tracked_owner = $1
+              AND asset_type = 'native'
+            ",
+        )
+        .bind(OWNER)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(charts.native_transfer_volume, expected_native_volume);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_exclude_transactions_outside_job_window_when_building_charts(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool);
+        let job_id = database
+            .create_processing_job(OWNER, 1000, 1, None, 0)
+            .await?
+            .expect("processing job should be created");
+
+        database
+            .save_transaction_data(&[transaction_result("charts-old", Value::Null)?], OWNER)
+            .await?;
+
+        let charts = database
+            .get_job_charts(job_id, None, ChartBucket::Minute)
+            .await?
+            .expect("charts should be returned for an existing job");
+
+        assert!(charts.tx_time_line.is_empty());
+        assert_eq!(charts.success_count, 0);
+        assert_eq!(charts.failed_count, 0);
+        assert_eq!(charts.total_fee, 0);
+        assert_eq!(charts.native_transfer_volume, "0");
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_sum_native_transfer_volume_beyond_bigint_range(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let job_id = database
+            .create_processing_job(OWNER, 1000, 24, None, 0)
+            .await?
+            .expect("processing job should be created");
+        let block_time = chrono::Utc::now().timestamp() - 60;
+        for (index, signature) in ["charts-big-1", "charts-big-2"].into_iter().enumerate() {
+            sqlx::query(
+                "
+                INSERT INTO token_transfers
+                    (tracked_owner, signature, amount_raw, asset_type, transfer_type, instruction_idx, slot, block_time)
+                VALUES ($1, $2, $3::numeric, 'native', 'transfer', $4, 1, $5)
+                ",
+            )
+            .bind(OWNER)
+            .bind(signature)
+            .bind(i64::MAX.to_string())
+            .bind(i32::try_from(index)?)
+            .bind(block_time)
+            .execute(&pool)
+            .await?;
+        }
+
+        let charts = database
+            .get_job_charts(job_id, None, ChartBucket::Hour)
+            .await?
+            .expect("charts should be returned for an existing job");
+
+        assert_eq!(
+            charts.native_transfer_volume,
+            (i128::from(i64::MAX) * 2).to_string()
+        );
+
+        Ok(())
+    }
+}
+
+mod workflow {
+    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
+    use anyhow::{Ok, Result};
+    use chrono::Utc;
+    use on_chain_event_indexer::{
+        AppState,
+        auth::ApiKeyRateLimiters,
+        db,
+        events::JobEvents,
+        indexer,
+        liveness::WorkerLiveness,
+        reparse,
+        requests::HeliusApi,
+        settings::{
+            DeduplicationSettings, RawPayloadSettings, SignatureRetrySettings, WebhookSettings,
+        },
+        shutdown::Shutdown,
+        types::SignatureCoverage,
+    };
+    use pretty_assertions::assert_eq;
+    use serde_json::{Value, json};
+    use sqlx::postgres::PgPool;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const OWNER: &str = "workflow-owner";
+    const SUCCESS_SIGNATURE: &str = "5SgJtP7Z9YvNQ9o4mN4YQk7G9xK8qP6eL3cR2wA1bV0m";
+    const FAILED_SIGNATURE: &str = "failed-signature";
+    const WORKER_ID: u32 = 7;
+    const SIGNATURE_MAX_ATTEMPTS: u32 = 3;
+
+    struct WorkflowHarness {
+        pool: PgPool,
+        database: db::Database,
+        mock_server: MockServer,
+        shutdown: Shutdown,
+    }
+
+    impl WorkflowHarness {
+        async fn new(pool: PgPool) -> Result<Self> {
+            let mock_server = MockServer::start().await;
+            let database = db::Database::from_pool(pool.clone());
+
+            Ok(Self {
+                pool,
+                database,
+                mock_server,
+                shutdown: Shutdown::new(std::time::Duration::from_secs(30)),
+            })
+        }
+
+        fn app_state(&self) -> Result<AppState> {
+            Ok(AppState {
+                database: db::Database::from_pool(self.pool.clone()),
+                rpc: Box::new(HeliusApi::new(100, 10, 4, &self.mock_server.uri(), "")?),
+                shutdown: self.shutdown.clone(),
+                worker_liveness: WorkerLiveness::new(std::time::Duration::from_mins(5)),
+                job_events: JobEvents::new(),
+                api_key_rate_limiters: ApiKeyRateLimiters::new(),
+                deduplication: DeduplicationSettings {
+                    enabled: true,
+                    ready_ttl_secs: 900,
+                    idempotency_key_ttl_secs: 86_400,
+                },
+                signature_retries: SignatureRetrySettings {
+                    max_attempts: SIGNATURE_MAX_ATTEMPTS,
+                    base_delay_ms: 1,
+                    max_delay_ms: 5,
+                },
+                raw_payloads: RawPayloadSettings { enabled: true },
+                webhooks: WebhookSettings {
+                    secret: String::from("workflow-secret"),
+                    max_attempts: 1,
+                    poll_interval_secs: 1,
+                    request_timeout_secs: 1,
+                },
+            })
+        }
+
+        async fn create_job(&self, tx_limit: i32, requested_hours: i32) -> Result<i64> {
+            Ok(self
+                .database
+                .create_processing_job(OWNER, tx_limit, requested_hours, None, 0)
+                .await?
+                .expect("processing job should be created"))
+        }
+
+        async fn create_job_for_address(
+            &self,
+            address: &str,
+            tx_limit: i32,
+            requested_hours: i32,
+        ) -> Result<i64> {
+            Ok(self
+                .database
+                .create_processing_job(address, tx_limit, requested_hours, None, 0)
+                .await?
+                .expect("processing job should be created"))
+        }
+
+        async fn process_once(&self) -> Result<Option<on_chain_event_indexer::types::JobInfo>> {
+            let app_state = self.app_state()?;
+            indexer::process_pending_job_once(&app_state, WORKER_ID).await
+        }
+
+        async fn job_status(&self, job_id: i64) -> Result<String> {
+            let status = sqlx::query_scalar::<_, String>(
+                "
+                SELECT status
+                FROM processing_data
+                WHERE id = $1
+                ",
+            )
+            .bind(job_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+            Ok(status)
+        }
+
+        async fn signature_rows(&self) -> Result<Vec<(String, bool, bool)>> {
+            self.signature_rows_for_address(OWNER).await
+        }
+
+        async fn signature_rows_for_address(
+            &self,
+            address: &str,
+        ) -> Result<Vec<(String, bool, bool)>> {
+            Ok(sqlx::query_as::<_, (String, bool, bool)>(
+                "
+                SELECT signature, is_processed, is_processing
+                FROM signatures
+                WHERE owner_address = $1
+                ORDER BY signature
+                ",
+            )
+            .bind(address)
+            .fetch_all(&self.pool)
+            .await?)
+        }
+
+        async fn count_transactions(&self) -> Result<i64> {
+            Ok(sqlx::query_scalar::<_, i64>(
+                "
+                SELECT COUNT(*)
+                FROM transactions
+                WHERE owner_address = $1
+                ",
+            )
+            .bind(OWNER)
+            .fetch_one(&self.pool)
+            .await?)
+        }
+
+        async fn count_token_transfers(&self) -> Result<i64> {
+            Ok(sqlx::query_scalar::<_, i64>(
+                "
+                SELECT COUNT(*)
+                FROM token_transfers
+                WHERE tracked_owner = $1
+                ",
+            )
+            .bind(OWNER)
+            .fetch_one(&self.pool)
+            .await?)
+        }
+
+        async fn assert_no_persisted_data(&self) -> Result<()> {
+            assert!(self.signature_rows().await?.is_empty());
+            assert_eq!(self.count_transactions().await?, 0);
+            assert_eq!(self.count_token_transfers().await?, 0);
+            Ok(())
+        }
+
+        async fn assert_processed_signature(&self, signature: &str) -> Result<()> {
+            assert_eq!(
+                self.signature_rows().await?,
+                vec![(signature.to_string(), true, false)]
+            );
+            Ok(())
+        }
+    }
+
+    fn signature_response(signatures: &[(&str, i64)]) -> Value {
+        let result = signatures
+            .iter()
+            .map(|(signature, block_time)| {
+                json!({
+                    "signature": signature,
+                    "blockTime": block_time,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "result": result,
+        })
+    }
+
+    fn signature_page(prefix: &str, count: usize, block_time: i64) -> Vec<(String, i64)> {
+        (0..count)
+            .map(|index| (format!("{prefix}-{index:04}"), block_time))
+            .collect()
+    }
+
+    fn signature_refs(signatures: &[(String, i64)]) -> Vec<(&str, i64)> {
+        signatures
+            .iter()
+            .map(|(signature, block_time)| (signature.as_str(), *block_time))
+            .collect()
+    }
+
+    fn transaction_fixture(signature: &str) -> Result<Value> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/helius/transactions/success.json");
+        let mut value: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        value["result"]["transaction"]["signatures"] = json!([signature]);
+        Ok(value)
+    }
+
+    fn transaction_request(signature: &str) -> Value {
+        json!({
+            "method": "getTransaction",
+            "params": [
+                signature,
+                {
+                    "encoding": "jsonParsed",
+                    "maxSupportedTransactionVersion": 0,
+                }
+            ]
+        })
+    }
+
+    async fn mount_signature_response(mock_server: &MockServer, body: Value, count: u64) {
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_partial_json(
+                json!({ "method": "getSignaturesForAddress" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .up_to_n_times(count)
+            .expect(count)
+            .mount(mock_server)
+            .await;
+    }
+
+    async fn mount_transaction_response(
+        mock_server: &MockServer,
+        signature: &str,
+        body: Value,
+        count: u64,
+    ) {
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_partial_json(transaction_request(signature)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .up_to_n_times(count)
+            .expect(count)
+            .mount(mock_server)
+            .await;
+    }
+
+    async fn mount_transaction_error(mock_server: &MockServer, signature: &str, count: u64) {
+        mount_transaction_response(
+            mock_server,
+            signature,
+            json!({
+                "jsonrpc": "2.0",
+                "id": "1",
+                "error": {
+                    "code": -32602,
+                    "message": "Invalid params",
+                }
+            }),
+            count,
+        )
+        .await;
+    }
+
+    async fn assert_signature_requests(
+        mock_server: &MockServer,
+        expected_before: &[Value],
+    ) -> Result<()> {
+        let requests = mock_server.received_requests().await.unwrap();
+        let signature_requests = requests
+            .iter()
+            .filter_map(|request| -> Option<Result<Value>> {
+                let body: Value = request.body_json().ok()?;
+                (body["method"] == "getSignaturesForAddress").then_some(Ok(body))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(signature_requests.len(), expected_before.len());
+        for (request, expected_before) in signature_requests.iter().zip(expected_before) {
+            assert_eq!(request["params"][0], OWNER);
+            assert_eq!(request["params"][1]["before"], *expected_before);
+            assert_eq!(request["params"][1]["limit"], 1000);
+        }
+
+        Ok(())
+    }
+
+    async fn assert_transaction_requests(
+        mock_server: &MockServer,
+        expected_signatures: &[&str],
+    ) -> Result<()> {
+        let requests = mock_server.received_requests().await.unwrap();
+        let mut actual = requests
+            .iter()
+            .filter_map(|request| -> Option<Result<String>> {
+                let body: Value = request.body_json().ok()?;
+                if body["method"] != "getTransaction" {
+                    return None;
+                }
+                Some(Ok(body["params"][0]
+                    .as_str()
+                    .expect("signature should be a string")
+                    .to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        actual.sort();
+
+        let mut expected = expected_signatures
+            .iter()
+            .map(|signature| (*signature).to_string())
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_release_job_to_pending_when_shutdown_requested(pool: PgPool) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let job_id = harness.create_job(1000, 24).await?;
+
+        harness.shutdown.trigger();
+        let job_info = harness.process_once().await?.expect("job should exist");
+
+        assert_eq!(job_info.status, "pending");
+        assert_eq!(harness.job_status(job_id).await?, "pending");
+        assert!(
+            harness
+                .mock_server
+                .received_requests()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        harness.assert_no_persisted_data().await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_stop_and_mark_job_cancelled_when_cancel_requested_during_indexing(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let job_id = harness.create_job(1000, 24).await?;
+        let claimed_job = harness
+            .database
+            .claim_pending_job(WORKER_ID)
+            .await?
+            .expect("job should be claimed");
+
+        harness.database.cancel_job(job_id, None).await?;
+        let app_state = harness.app_state()?;
+        indexer::process_claimed_job(&app_state, WORKER_ID, claimed_job).await;
+
+        assert_eq!(harness.job_status(job_id).await?, "cancelled");
+        assert!(
+            harness
+                .mock_server
+                .received_requests()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        harness.assert_no_persisted_data().await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_mark_job_as_ready_and_save_transfers_when_happy_path(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp() - 60;
+        let job_id = harness.create_job(1000, 24).await?;
+
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&[(SUCCESS_SIGNATURE, now)]),
+            1,
+        )
+        .await;
+        mount_transaction_response(
+            &harness.mock_server,
+            SUCCESS_SIGNATURE,
+            transaction_fixture(SUCCESS_SIGNATURE)?,
+            1,
+        )
+        .await;
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.total_transactions, 1);
+        assert_eq!(job_info.processed_transactions, 1);
+        assert_eq!(job_info.remaining_transactions, 0);
+        assert_eq!(harness.job_status(job_id).await?, "ready");
+        harness
+            .assert_processed_signature(SUCCESS_SIGNATURE)
+            .await?;
+        assert_eq!(harness.count_transactions().await?, 1);
+        assert!(harness.count_token_transfers().await? > 0);
+        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
+        assert_transaction_requests(&harness.mock_server, &[SUCCESS_SIGNATURE]).await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_rebuild_transactions_from_stored_payloads_without_rpc_when_reparsing(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp() - 60;
+        harness.create_job(1000, 24).await?;
+
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&[(SUCCESS_SIGNATURE, now)]),
+            1,
+        )
+        .await;
+        mount_transaction_response(
+            &harness.mock_server,
+            SUCCESS_SIGNATURE,
+            transaction_fixture(SUCCESS_SIGNATURE)?,
+            1,
+        )
+        .await;
+
+        harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        let stored_signature = sqlx::query_scalar::<_, String>(
+            "SELECT payload -> 'transaction' -> 'signatures' ->> 0 FROM raw_transactions",
+        )
+        .fetch_one(&harness.pool)
+        .await?;
+        assert_eq!(stored_signature, SUCCESS_SIGNATURE);
+        let token_transfers = harness.count_token_transfers().await?;
+        let fee = sqlx::query_scalar::<_, i64>("SELECT fee FROM transactions")
+            .fetch_one(&harness.pool)
+            .await?;
+
+        sqlx::query("DELETE FROM token_transfers")
+            .execute(&harness.pool)
+            .await?;
+        sqlx::query("UPDATE transactions SET fee = 0")
+            .execute(&harness.pool)
+            .await?;
+        let requests_before = harness.mock_server.received_requests().await.unwrap().len();
+
+        assert_eq!(
+            harness.database.get_raw_transaction_owners().await?,
+            vec![OWNER.to_string()]
+        );
+        let stats = reparse::reparse_address(&harness.database, OWNER, 100).await?;
+
+        assert_eq!(stats.transactions, 1);
+        assert_eq!(stats.failed_payloads, 0);
+        assert_eq!(i64::try_from(stats.token_transfers)?, token_transfers);
+        assert_eq!(harness.count_transactions().await?, 1);
+        assert_eq!(harness.count_token_transfers().await?, token_transfers);
+        assert_eq!(
+            sqlx::query_scalar::<_, i64>("SELECT fee FROM transactions")
+                .fetch_one(&harness.pool)
+                .await?,
+            fee
+        );
+        assert_eq!(
+            harness.mock_server.received_requests().await.unwrap().len(),
+            requests_before
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_keep_only_signatures_inside_range_when_job_has_absolute_range(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp();
+        let job_id = sqlx::query_scalar::<_, i64>(
+            "
+            INSERT INTO processing_data
+                (address, status, created_at, updated_at, tx_limit, range_start, range_end)
+            VALUES ($1, 'pending', NOW(), NOW(), 1000, to_timestamp($2), to_timestamp($3))
+            RETURNING id
+            ",
+        )
+        .bind(OWNER)
+        .bind(now - 3 * 3600)
+        .bind(now - 2 * 3600)
+        .fetch_one(&harness.pool)
+        .await?;
+
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&[
+                ("sig-after-range", now - 60),
+                (SUCCESS_SIGNATURE, now - 5 * 1800),
+                ("sig-before-range", now - 4 * 3600),
+            ]),
+            1,
+        )
+        .await;
+        mount_transaction_response(
+            &harness.mock_server,
+            SUCCESS_SIGNATURE,
+            transaction_fixture(SUCCESS_SIGNATURE)?,
+            1,
+        )
+        .await;
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.total_transactions, 1);
+        assert_eq!(job_info.remaining_transactions, 0);
+        assert_eq!(
+            job_info.range_end.map(|end| end.timestamp()),
+            Some(now - 2 * 3600)
+        );
+        assert_eq!(harness.job_status(job_id).await?, "ready");
+        harness
+            .assert_processed_signature(SUCCESS_SIGNATURE)
+            .await?;
+        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
+        assert_transaction_requests(&harness.mock_server, &[SUCCESS_SIGNATURE]).await?;
+        assert!(
+            harness
+                .database
+                .get_signature_coverage(OWNER)
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_mark_job_as_ready_with_empty_results_when_address_history_is_empty(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let job_id = harness.create_job(1000, 24).await?;
+
+        mount_signature_response(
+            &harness.mock_server,
+            json!({ "jsonrpc": "2.0", "id": "1", "result": [] }),
+            1,
+        )
+        .await;
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.total_transactions, 0);
+        assert_eq!(job_info.processed_transactions, 0);
+        assert_eq!(job_info.remaining_transactions, 0);
+        assert_eq!(harness.job_status(job_id).await?, "ready");
+        harness.assert_no_persisted_data().await?;
+        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
+        assert_transaction_requests(&harness.mock_server, &[]).await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_mark_job_as_error_when_get_signatures_returns_fatal_rpc_error(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let job_id = harness.create_job(1000, 24).await?;
+
+        mount_signature_response(
+            &harness.mock_server,
+            json!({
+                "jsonrpc": "2.0",
+                "id": "1",
+                "error": {
+                    "code": -32602,
+                    "message": "Invalid params",
+                }
+            }),
+            1,
+        )
+        .await;
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "error");
+        assert_eq!(harness.job_status(job_id).await?, "error");
+        harness.assert_no_persisted_data().await?;
+        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
+        assert_transaction_requests(&harness.mock_server, &[]).await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_mark_job_as_error_when_ready_update_is_blocked_by_unprocessed_signatures(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp() - 60;
+        let other_owner = "workflow-owner-other";
+        let other_job_id = harness
+            .create_job_for_address(other_owner, 1000, 24)
+            .await?;
+        let job_id = harness.create_job(1000, 24).await?;
+
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&[(SUCCESS_SIGNATURE, now), (FAILED_SIGNATURE, now)]),
+            1,
+        )
+        .await;
+        mount_transaction_response(
+            &harness.mock_server,
+            SUCCESS_SIGNATURE,
+            transaction_fixture(SUCCESS_SIGNATURE)?,
+            1,
+        )
+        .await;
+
+        let other_claim = harness
+            .database
+            .claim_pending_job(WORKER_ID + 1)
+            .await?
+            .expect("older job should be claimed first");
+        assert_eq!(other_claim.job_id, other_job_id);
+
+        harness
+            .database
+            .write_signatures(
+                &serde_json::from_value(json!({
+                    "result": [
+                        {
+                            "signature": FAILED_SIGNATURE,
+                            "blockTime": now,
+                        }
+                    ]
+                }))?,
+                OWNER,
+            )
+            .await?;
+        let leased = harness
+            .database
+            .get_unprocessed_signatures(OWNER, 100)
+            .await?;
+        assert_eq!(leased, vec![FAILED_SIGNATURE.to_string()]);
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "error");
+        assert_eq!(harness.job_status(job_id).await?, "error");
+        assert_eq!(harness.job_status(other_job_id).await?, "indexing");
+        assert_eq!(
+            harness.signature_rows_for_address(OWNER).await?,
+            vec![
+                (SUCCESS_SIGNATURE.to_string(), true, false),
+                (FAILED_SIGNATURE.to_string(), false, true),
+            ]
+        );
+        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
+        assert_transaction_requests(&harness.mock_server, &[SUCCESS_SIGNATURE]).await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_dead_letter_failing_signature_and_mark_job_ready_when_partial_transaction_failure(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp() - 60;
+        let job_id = harness.create_job(1000, 24).await?;
+        let successful_2 = "successful-signature-2";
+
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&[
+                (SUCCESS_SIGNATURE, now),
+                (FAILED_SIGNATURE, now),
+                (successful_2, now),
+            ]),
+            1,
+        )
+        .await;
+        mount_transaction_response(
+            &harness.mock_server,
+            SUCCESS_SIGNATURE,
+            transaction_fixture(SUCCESS_SIGNATURE)?,
+            1,
+        )
+        .await;
+        mount_transaction_error(
+            &harness.mock_server,
+            FAILED_SIGNATURE,
+            u64::from(SIGNATURE_MAX_ATTEMPTS),
+        )
+        .await;
+        mount_transaction_response(
+            &harness.mock_server,
+            successful_2,
+            transaction_fixture(successful_2)?,
+            1,
+        )
+        .await;
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.total_transactions, 3);
+        assert_eq!(job_info.processed_transactions, 2);
+        assert_eq!(job_info.remaining_transactions, 0);
+        assert_eq!(job_info.dead_transactions, 1);
+        assert_eq!(harness.job_status(job_id).await?, "ready");
+        assert_eq!(
+            harness.signature_rows().await?,
+            vec![
+                (SUCCESS_SIGNATURE.to_string(), true, false),
+                (FAILED_SIGNATURE.to_string(), false, false),
+                (successful_2.to_string(), true, false),
+            ]
+        );
+
+        let dead = harness
+            .database
+            .get_job_dead_signatures(job_id, None)
+            .await?
+            .expect("job should exist");
+        assert_eq!(dead.signatures.len(), 1);
+        let dead_signature = &dead.signatures[0];
+        assert_eq!(dead_signature.signature, FAILED_SIGNATURE);
+        assert_eq!(
+            dead_signature.attempts,
+            i32::try_from(SIGNATURE_MAX_ATTEMPTS)?
+        );
+        assert_eq!(dead_signature.last_status_code, Some(200));
+        assert_eq!(dead_signature.last_rpc_code, Some(-32602));
+        assert!(
+            dead_signature
+                .last_error
+                .as_deref()
+                .is_some_and(|error| error.contains("Invalid params"))
+        );
+        assert!(dead_signature.last_attempt_at.is_some());
+        assert!(
+            harness
+                .database
+                .get_unprocessed_signatures(OWNER, 100)
+                .await?
+                .is_empty()
+        );
+        assert_eq!(harness.count_transactions().await?, 2);
+        assert!(harness.count_token_transfers().await? > 0);
+        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
+        assert_transaction_requests(
+            &harness.mock_server,
+            &[
+                SUCCESS_SIGNATURE,
+                FAILED_SIGNATURE,
+                FAILED_SIGNATURE,
+                FAILED_SIGNATURE,
+                successful_2,
+            ],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_retry_failed_signature_with_backoff_and_mark_job_ready_when_retry_succeeds(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp() - 60;
+        let job_id = harness.create_job(1000, 24).await?;
+
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&[(FAILED_SIGNATURE, now)]),
+            1,
+        )
+        .await;
+        mount_transaction_error(&harness.mock_server, FAILED_SIGNATURE, 1).await;
+        mount_transaction_response(
+            &harness.mock_server,
+            FAILED_SIGNATURE,
+            transaction_fixture(FAILED_SIGNATURE)?,
+            1,
+        )
+        .await;
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.processed_transactions, 1);
+        assert_eq!(job_info.dead_transactions, 0);
+        assert_eq!(harness.job_status(job_id).await?, "ready");
+        assert_eq!(
+            harness.signature_rows().await?,
+            vec![(FAILED_SIGNATURE.to_string(), true, false)]
+        );
+        let attempts = sqlx::query_scalar::<_, i32>(
+            "SELECT attempts FROM signatures WHERE owner_address = $1 AND signature = $2",
+        )
+        .bind(OWNER)
+        .bind(FAILED_SIGNATURE)
+        .fetch_one(&harness.pool)
+        .await?;
+        assert_eq!(attempts, 1);
+        assert!(
+            harness
+                .database
+                .get_job_dead_signatures(job_id, None)
+                .await?
+                .expect("job should exist")
+                .signatures
+                .is_empty()
+        );
+        assert_transaction_requests(&harness.mock_server, &[FAILED_SIGNATURE, FAILED_SIGNATURE])
+            .await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_fetch_all_pages_with_correct_before_when_pagination_required(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp() - 60;
+        let job_id = harness.create_job(2000, 24).await?;
+        let first_page = signature_page("first-page", 1000, now);
+        let second_page = vec![(SUCCESS_SIGNATURE.to_string(), now)];
+        let mut all_signatures = first_page.clone();
+        all_signatures.extend(second_page.clone());
+
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&signature_refs(&first_page)),
+            1,
+        )
+        .await;
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&signature_refs(&second_page)),
+            1,
+        )
+        .await;
+
+        for (signature, _) in &all_signatures {
+            mount_transaction_response(
+                &harness.mock_server,
+                signature,
+                transaction_fixture(signature)?,
+                1,
+            )
+            .await;
+        }
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.total_transactions, 1001);
+        assert_eq!(job_info.processed_transactions, 1001);
+        assert_eq!(job_info.remaining_transactions, 0);
+        assert_eq!(harness.job_status(job_id).await?, "ready");
+        assert_eq!(harness.count_transactions().await?, 1001);
+        assert_signature_requests(
+            &harness.mock_server,
+            &[Value::Null, json!("first-page-0999")],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_stop_pagination_when_cutoff_signature_encountered_in_page(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp() - 60;
+        let old = Utc::now().timestamp() - 7_200;
+        let job_id = harness.create_job(1000, 1).await?;
+
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&[(SUCCESS_SIGNATURE, now), ("old-signature", old)]),
+            1,
+        )
+        .await;
+        mount_transaction_response(
+            &harness.mock_server,
+            SUCCESS_SIGNATURE,
+            transaction_fixture(SUCCESS_SIGNATURE)?,
+            1,
+        )
+        .await;
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.total_transactions, 1);
+        assert_eq!(job_info.processed_transactions, 1);
+        assert_eq!(harness.job_status(job_id).await?, "ready");
+        harness
+            .assert_processed_signature(SUCCESS_SIGNATURE)
+            .await?;
+        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
+        assert_transaction_requests(&harness.mock_server, &[SUCCESS_SIGNATURE]).await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_respect_tx_limit_even_if_page_larger_when_processing_signatures(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp() - 60;
+        let job_id = harness.create_job(100, 24).await?;
+        let page = signature_page("limit-page", 150, now);
+
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&signature_refs(&page)),
+            1,
+        )
+        .await;
+
+        for (signature, _) in &page {
+            mount_transaction_response(
+                &harness.mock_server,
+                signature,
+                transaction_fixture(signature)?,
+                1,
+            )
+            .await;
+        }
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(job_info.total_transactions, 150);
+        assert_eq!(job_info.processed_transactions, 150);
+        assert_eq!(job_info.remaining_transactions, 0);
+        assert_eq!(harness.job_status(job_id).await?, "ready");
+        assert_eq!(harness.count_transactions().await?, 150);
+        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
+
+        Ok(())
+    }
+
+    async fn seed_processed_signatures(
+        harness: &WorkflowHarness,
+        signatures: &[(&str, i64)],
+    ) -> Result<()> {
+        harness
+            .database
+            .write_signatures(
+                &serde_json::from_value(signature_response(signatures))?,
+                OWNER,
+            )
+            .await?;
+        if let (Some((newest, _)), Some((oldest, oldest_block_time))) =
+            (signatures.first(), signatures.last())
+        {
+            harness
+                .database
+                .save_signature_coverage(
+                    OWNER,
+                    &SignatureCoverage {
+                        newest_signature: (*newest).to_string(),
+                        oldest_signature: (*oldest).to_string(),
+                        oldest_block_time: *oldest_block_time,
+                    },
+                )
+                .await?;
+        }
+        sqlx::query("UPDATE signatures SET is_processed = TRUE WHERE owner_address = $1")
+            .bind(OWNER)
+            .execute(&harness.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn signature_request_params(mock_server: &MockServer) -> Result<Vec<Value>> {
+        let requests = mock_server.received_requests().await.unwrap();
+        requests
+            .iter()
+            .filter_map(|request| -> Option<Result<Value>> {
+                let body: Value = request.body_json().ok()?;
+                (body["method"] == "getSignaturesForAddress").then(|| Ok(body["params"][1].clone()))
+            })
+            .collect()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_only_fetch_newer_signatures_when_stored_history_covers_window(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp();
+        seed_processed_signatures(
+            &harness,
+            &[("stored-newest", now - 600), ("stored-oldest", now - 7_200)],
+        )
+        .await?;
+        let job_id = harness.create_job(1000, 1).await?;
+
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&[(SUCCESS_SIGNATURE, now - 60)]),
+            1,
+        )
+        .await;
+        mount_transaction_response(
+            &harness.mock_server,
+            SUCCESS_SIGNATURE,
+            transaction_fixture(SUCCESS_SIGNATURE)?,
+            1,
+        )
+        .await;
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(harness.job_status(job_id).await?, "ready");
+        assert_eq!(job_info.total_transactions, 2);
+        assert_eq!(job_info.processed_transactions, 2);
+
+        let params = signature_request_params(&harness.mock_server).await?;
+        assert_eq!(params.len(), 1);
+        assert_eq!(params[0]["before"], Value::Null);
+        assert_eq!(params[0]["until"], "stored-newest");
+        assert_transaction_requests(&harness.mock_server, &[SUCCESS_SIGNATURE]).await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_extend_backwards_from_oldest_signature_when_window_exceeds_stored_history(
+        pool: PgPool,
+    ) -> Result<()> {
+        let harness = WorkflowHarness::new(pool).await?;
+        let now = Utc::now().timestamp();
+        seed_processed_signatures(
+            &harness,
+            &[("stored-newest", now - 600), ("stored-oldest", now - 1_200)],
+        )
+        .await?;
+        let job_id = harness.create_job(1000, 24).await?;
+
+        mount_signature_response(
+            &harness.mock_server,
+            json!({ "jsonrpc": "2.0", "id": "1", "result": [] }),
+            1,
+        )
+        .await;
+        mount_signature_response(
+            &harness.mock_server,
+            signature_response(&[(SUCCESS_SIGNATURE, now - 3_600)]),
+            1,
+        )
+        .await;
+        mount_transaction_response(
+            &harness.mock_server,
+            SUCCESS_SIGNATURE,
+            transaction_fixture(SUCCESS_SIGNATURE)?,
+            1,
+        )
+        .await;
+
+        let job_info = harness
+            .process_once()
+            .await?
+            .expect("pending job should be processed");
+
+        assert_eq!(job_info.status, "ready");
+        assert_eq!(harness.job_status(job_id).await?, "ready");
+        assert_eq!(job_info.total_transactions, 3);
+
+        let params = signature_request_params(&harness.mock_server).await?;
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0]["before"], Value::Null);
+        assert_eq!(params[0]["until"], "stored-newest");
+        assert_eq!(params[1]["before"], "stored-oldest");
+        assert_eq!(params[1]["until"], Value::Null);
+        assert_transaction_requests(&harness.mock_server, &[SUCCESS_SIGNATURE]).await?;
+
+        Ok(())
+    }
+}
+
+mod webhooks {
+    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
+    use anyhow::{Ok, Result};
+    use axum::{Router, body::Bytes, http::HeaderMap, http::StatusCode, routing::post};
+    use on_chain_event_indexer::db;
+    use on_chain_event_indexer::settings::WebhookSettings;
+    use on_chain_event_indexer::webhooks::{SIGNATURE_HEADER, WebhookDispatcher, sign_payload};
+    use pretty_assertions::assert_eq;
+    use sqlx::postgres::PgPool;
+    use std::sync::{Arc, Mutex};
+
+    const OWNER: &str = "webhook-owner";
+    const SECRET: &str = "webhook-secret";
+
+    type Received = Arc<Mutex<Vec<(Option<String>, String)>>>;
+
+    async fn spawn_receiver(status: StatusCode) -> Result<(String, Received)> {
+        let received = Received::default();
+        let app = Router::new().route(
+            "/hook",
+            post({
+                let received = Arc::clone(&received);
+                move |headers: HeaderMap, body: Bytes| async move {
+                    let signature = headers
+                        .get(SIGNATURE_HEADER)
+                        .and_then(|value| value.to_str().ok())
+                        .map(str::to_string);
+                    received
+                        .lock()
+                        .unwrap()
+                        .push((signature, String::from_utf8_lossy(&body).into_owned()));
+                    status
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/hook", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        Ok((url, received))
+    }
+
+    fn settings(max_attempts: u32) -> WebhookSettings {
+        WebhookSettings {
+            secret: SECRET.to_string(),
+            max_attempts,
+            poll_interval_secs: 1,
+            request_timeout_secs: 5,
+        }
+    }
+
+    fn dispatcher(max_attempts: u32) -> Result<WebhookDispatcher> {
+        WebhookDispatcher::with_internal_hosts(&settings(max_attempts))
+    }
+
+    async fn create_finished_job(
+        database: &db::Database,
+        pool: &PgPool,
+        callback_url: Option<&str>,
+        status: &str,
+    ) -> Result<i64> {
+        let job_id = database
+            .create_processing_job(OWNER, 1000, 24, callback_url, 0)
+            .await?
+            .expect("processing job should be created");
+        sqlx::query("UPDATE processing_data SET status = $2 WHERE id = $1")
+            .bind(job_id)
+            .bind(status)
+            .execute(pool)
+            .await?;
+
+        Ok(job_id)
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_deliver_signed_summary_when_finished_job_has_callback_url(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let (url, received) = spawn_receiver(StatusCode::OK).await?;
+        let job_id = create_finished_job(&database, &pool, Some(&url), "ready").await?;
+
+        assert_eq!(dispatcher(3)?.deliver_due(&database).await?, 1);
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 1);
+        let (signature, body) = &received[0];
+        assert_eq!(
+            signature.as_deref(),
+            Some(sign_payload(SECRET, body)?.as_str())
+        );
+        let summary: serde_json::Value = serde_json::from_str(body)?;
+        assert_eq!(summary["job_id"], job_id);
+        assert_eq!(summary["status"], "ready");
+        assert_eq!(summary["address"], OWNER);
+
+        let webhooks = database
+            .get_job_webhooks(job_id, None)
+            .await?
+            .expect("job should exist");
+        assert_eq!(webhooks.callback_url.as_deref(), Some(url.as_str()));
+        assert_eq!(webhooks.deliveries.len(), 1);
+        assert_eq!(webhooks.deliveries[0].status, "delivered");
+        assert_eq!(webhooks.deliveries[0].attempts, 1);
+        assert_eq!(webhooks.deliveries[0].last_status_code, Some(200));
+        assert!(webhooks.deliveries[0].delivered_at.is_some());
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_schedule_retry_then_dead_letter_when_receiver_keeps_failing(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let (url, received) = spawn_receiver(StatusCode::INTERNAL_SERVER_ERROR).await?;
+        let job_id = create_finished_job(&database, &pool, Some(&url), "error").await?;
+        let dispatcher = dispatcher(2)?;
+
+        assert_eq!(dispatcher.deliver_due(&database).await?, 1);
+        let webhooks = database.get_job_webhooks(job_id, None).await?.unwrap();
+        assert_eq!(webhooks.deliveries[0].status, "pending");
+        assert_eq!(webhooks.deliveries[0].last_status_code, Some(500));
+        assert!(webhooks.deliveries[0].next_attempt_at > chrono::Utc::now());
+        assert_eq!(dispatcher.deliver_due(&database).await?, 0);
+
+        sqlx::query("UPDATE webhook_deliveries SET next_attempt_at = NOW()")
+            .execute(&pool)
+            .await?;
+        assert_eq!(dispatcher.deliver_due(&database).await?, 1);
+
+        let webhooks = database.get_job_webhooks(job_id, None).await?.unwrap();
+        assert_eq!(webhooks.deliveries[0].status, "dead");
+        assert_eq!(webhooks.deliveries[0].attempts, 2);
+        assert!(webhooks.deliveries[0].last_error.is_some());
+        assert_eq!(received.lock().unwrap().len(), 2);
+
+        sqlx::query("UPDATE webhook_deliveries SET next_attempt_at = NOW()")
+            .execute(&pool)
+            .await?;
+        assert_eq!(dispatcher.deliver_due(&database).await?, 0);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_not_follow_redirect_when_callback_redirects(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let (target, received) = spawn_receiver(StatusCode::OK).await?;
+        let app = Router::new().route(
+            "/hook",
+            post(move || async move {
+                (
+                    StatusCode::TEMPORARY_REDIRECT,
+                    [(axum::http::header::LOCATION, target)],
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/hook", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let job_id = create_finished_job(&database, &pool, Some(&url), "ready").await?;
+
+        assert_eq!(dispatcher(3)?.deliver_due(&database).await?, 1);
+
+        assert!(received.lock().unwrap().is_empty());
+        let webhooks = database.get_job_webhooks(job_id, None).await?.unwrap();
+        assert_eq!(webhooks.deliveries[0].status, "pending");
+        assert_eq!(webhooks.deliveries[0].last_status_code, Some(307));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_fail_delivery_when_callback_resolves_to_internal_address(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let (url, received) = spawn_receiver(StatusCode::OK).await?;
+        let url = url.replace("127.0.0.1", "localhost");
+        let job_id = create_finished_job(&database, &pool, Some(&url), "ready").await?;
+
+        assert_eq!(
+            WebhookDispatcher::new(&settings(3))?
+                .deliver_due(&database)
+                .await?,
+            1
+        );
+
+        assert!(received.lock().unwrap().is_empty());
+        let webhooks = database.get_job_webhooks(job_id, None).await?.unwrap();
+        assert_eq!(webhooks.deliveries[0].status, "pending");
+        assert_eq!(webhooks.deliveries[0].last_status_code, None);
+        assert!(
+            webhooks.deliveries[0]
+                .last_error
+                .as_deref()
+                .is_some_and(|error| error.contains("internal addresses"))
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_deliver_webhook_when_reaper_moves_job_to_error(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let (url, received) = spawn_receiver(StatusCode::OK).await?;
+        let job_id = create_finished_job(&database, &pool, Some(&url), "indexing").await?;
+        sqlx::query(
+            "UPDATE processing_data SET attempts = 3, heartbeat_at = NOW() - INTERVAL '1 hour' WHERE id = $1",
+        )
+        .bind(job_id)
+        .execute(&pool)
+        .await?;
+
+        let reaped = database.reap_expired_jobs(60, 3).await?;
+        assert_eq!(reaped.len(), 1);
+        assert_eq!(reaped[0].status, "error");
+        assert_eq!(dispatcher(3)?.deliver_due(&database).await?, 1);
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 1);
+        let summary: serde_json::Value = serde_json::from_str(&received[0].1)?;
+        assert_eq!(summary["job_id"], job_id);
+        assert_eq!(summary["status"], "error");
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_skip_webhook_when_job_has_no_callback_url_or_is_unfinished(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let without_callback = create_finished_job(&database, &pool, None, "ready").await?;
+        let unfinished = create_finished_job(
+            &database,
+            &pool,
+            Some("http://127.0.0.1:1/hook"),
+            "indexing",
+        )
+        .await?;
+
+        let unfinished = database.get_job_webhooks(unfinished, None).await?.unwrap();
+        assert!(unfinished.deliveries.is_empty());
+
+        let webhooks = database
+            .get_job_webhooks(without_callback, None)
+            .await?
+            .unwrap();
+        assert_eq!(webhooks.callback_url, None);
+        assert!(webhooks.deliveries.is_empty());
+        assert!(database.get_job_webhooks(i64::MAX, None).await?.is_none());
+
+        Ok(())
+    }
+}
+
+mod api_keys {
+    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
+    use anyhow::{Ok, Result};
+    use futures::future::join_all;
+    use on_chain_event_indexer::auth::{generate_api_key, hash_api_key};
+    use on_chain_event_indexer::db::{self, jobs::ConcurrentJobLimitExceeded};
+    use on_chain_event_indexer::types::{
+        ApiKey, ApiKeyLimits, ChartBucket, JobDedup, JobWindow, NewJob, TransactionFilter,
+    };
+    use pretty_assertions::assert_eq;
+    use sqlx::postgres::PgPool;
+
+    const LIMITS: ApiKeyLimits = ApiKeyLimits {
+        max_concurrent_jobs: 2,
+        max_tx_limit: 1000,
+        max_requested_hours: 24,
+        max_priority: 0,
+        requests_per_minute: 60,
+        allow_full_history: false,
+    };
+
+    async fn create_key(database: &db::Database, name: &str) -> Result<(String, ApiKey)> {
+        let key = generate_api_key();
+        database
+            .create_api_key(name, &hash_api_key(&key), LIMITS)
+            .await?;
+        let api_key = database
+            .find_active_api_key(&hash_api_key(&key))
+            .await?
+            .expect("created key should be active");
+
+        Ok((key, api_key))
+    }
+
+    fn new_job(address: &str) -> NewJob {
+        NewJob {
+            address: address.to_string(),
+            tx_limit: Some(1000),
+            window: JobWindow::Recent { hours: 24 },
+            callback_url: None,
+            priority: 0,
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_find_key_by_hash_until_it_is_revoked(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let (key, api_key) = create_key(&database, "partner").await?;
+
+        assert_eq!(api_key.name, "partner");
+        assert_eq!(api_key.limits, LIMITS);
+        let stored_plaintext =
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM api_keys WHERE key_hash = $1")
+                .bind(&key)
+                .fetch_one(&pool)
+                .await?;
+        assert_eq!(stored_plaintext, 0);
+
+        assert_eq!(database.revoke_api_key("partner").await?, 1);
+        assert!(
+            database
+                .find_active_api_key(&hash_api_key(&key))
+                .await?
+                .is_none()
+        );
+        assert_eq!(database.revoke_api_key("partner").await?, 0);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_reject_job_when_key_has_max_concurrent_jobs_active(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let (_, api_key) = create_key(&database, "partner").await?;
+        let (_, other_key) = create_key(&database, "other").await?;
+
+        let results = join_all((0..4).map(|i| {
+            let job = new_job(&format!("address-{i}"));
+            let database = &database;
+            let api_key = &api_key;
+            async move {
+                database
+                    .create_processing_job_for_api_key(api_key, &job, &JobDedup::default())
+                    .await
+            }
+        }))
+        .await;
+
+        let created = results.iter().filter(|result| result.is_ok()).count();
+        assert_eq!(created, 2);
+        assert!(
+            results
+                .iter()
+                .filter_map(|result| result.as_ref().err())
+                .all(|err| err
+                    .downcast_ref::<ConcurrentJobLimitExceeded>()
+                    .map(|err| err.limit)
+                    == Some(2))
+        );
+
+        database
+            .create_processing_job_for_api_key(
+                &other_key,
+                &new_job("address-other"),
+                &JobDedup::default(),
+            )
+            .await?;
+
+        sqlx::query(
+            "
+            UPDATE processing_data
+            SET status = 'ready'
+            WHERE id = (SELECT MIN(id) FROM processing_data WHERE api_key_id = $1)
+            ",
+        )
+        .bind(api_key.id)
+        .execute(&pool)
+        .await?;
+
+        database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &new_job("address-5"),
+                &JobDedup::default(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_reject_retry_when_key_has_max_concurrent_jobs_active(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let (_, api_key) = create_key(&database, "partner").await?;
+        let mut job_ids = Vec::new();
+        for i in 0..2 {
+            let submission = database
+                .create_processing_job_for_api_key(
+                    &api_key,
+                    &new_job(&format!("address-{i}")),
+                    &JobDedup::default(),
+                )
+                .await?;
+            job_ids.push(submission.job_id);
+        }
+        sqlx::query("UPDATE processing_data SET status = 'error' WHERE id = $1")
+            .bind(job_ids[0])
+            .execute(&pool)
+            .await?;
+        database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &new_job("address-2"),
+                &JobDedup::default(),
+            )
+            .await?;
+
+        let err = database
+            .retry_job(job_ids[0], Some(&api_key))
+            .await
+            .expect_err("retry should count against the key's capacity");
+        assert!(err.is::<ConcurrentJobLimitExceeded>());
+
+        sqlx::query("UPDATE processing_data SET status = 'ready' WHERE id = $1")
+            .bind(job_ids[1])
+            .execute(&pool)
+            .await?;
+        let transition = database
+            .retry_job(job_ids[0], Some(&api_key))
+            .await?
+            .expect("job should exist");
+        assert_eq!(transition.status.as_deref(), Some("pending"));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_hide_jobs_and_batches_from_keys_that_did_not_submit_them(
+        pool: PgPool,
+    ) -> Result<()> {
+        let database = db::Database::from_pool(pool);
+        let (_, owner) = create_key(&database, "owner").await?;
+        let (_, other) = create_key(&database, "other").await?;
+        let job_id = database
+            .create_processing_job_for_api_key(&owner, &new_job("address-1"), &JobDedup::default())
+            .await?
+            .job_id;
+        let batch_id = database
+            .create_job_batch(&owner, &[new_job("address-2")])
+            .await?
+            .batch_id;
+        let filter = TransactionFilter {
+            status: None,
+            min_fee: None,
+            min_compute_units: None,
+            cursor: None,
+            limit: 10,
+        };
+
+        assert!(
+            database
+                .get_job_info(job_id, Some(other.id))
+                .await?
+                .is_none()
+        );
+        assert!(
+            database
+                .get_job_charts(job_id, Some(other.id), ChartBucket::Day)
+                .await?
+                .is_none()
+        );
+        assert!(
+            database
+                .get_job_transactions(job_id, Some(other.id), &filter)
+                .await?
+                .is_none()
+        );
+        assert!(
+            database
+                .get_job_webhooks(job_id, Some(other.id))
+                .await?
+                .is_none()
+        );
+        assert!(
+            database
+                .get_job_dead_signatures(job_id, Some(other.id))
+                .await?
+                .is_none()
+        );
+        assert!(database.cancel_job(job_id, Some(other.id)).await?.is_none());
+        assert!(
+            database
+                .get_batch_info(batch_id, Some(other.id))
+                .await?
+                .is_none()
+        );
+
+        assert!(
+            database
+                .get_job_info(job_id, Some(owner.id))
+                .await?
+                .is_some()
+        );
+        assert!(
+            database
+                .get_batch_info(batch_id, Some(owner.id))
+                .await?
+                .is_some()
+        );
+        let cancelled = database
+            .cancel_job(job_id, Some(owner.id))
+            .await?
+            .expect("owner should cancel its job");
+        assert_eq!(cancelled.status.as_deref(), Some("cancelled"));
 
-    async fn mount_transaction_response(
-        mock_server: &MockServer,
-        signature: &str,
-        body: Value,
-        count: u64,
-    ) {
-        Mock::given(method("POST"))
-            .and(path("/"))
-            .and(body_partial_json(transaction_request(signature)))
-            .respond_with(ResponseTemplate::new(200).set_body_json(body))
-            .up_to_n_times(count)
-            .expect(count)
-            .mount(mock_server)
-            .await;
+        Ok(())
     }
+}
 
-    async fn mount_transaction_error(mock_server: &MockServer, signature: &str) {
-        mount_transaction_response(
-            mock_server,
-            signature,
-            json!({
-                "jsonrpc": "2.0",
-                "id": "1",
-                "error": {
-                    "code": -32602,
-                    "message": "Invalid params",
-                }
-            }),
-            1,
-        )
-        .await;
-    }
+mod batches {
+    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
 
-    async fn assert_signature_requests(
-        mock_server: &MockServer,
-        expected_before: &[Value],
-    ) -> Result<()> {
-        let requests = mock_server.received_requests().await.unwrap();
-        let signature_requests = requests
-            .iter()
-            .filter_map(|request| -> Option<Result<Value>> {
-                let body: Value = request.body_json().ok()?;
-                (body["method"] == "getSignaturesForAddress").then_some(Ok(body))
-            })
-            .collect::<Result<Vec<_>>>()?;
+    use anyhow::{Ok, Result};
+    use on_chain_event_indexer::db::{self, jobs::ConcurrentJobLimitExceeded};
+    use on_chain_event_indexer::types::{ApiKey, ApiKeyLimits, JobDedup, JobWindow, NewJob};
+    use pretty_assertions::assert_eq;
+    use sqlx::postgres::PgPool;
 
-        assert_eq!(signature_requests.len(), expected_before.len());
-        for (request, expected_before) in signature_requests.iter().zip(expected_before) {
-            assert_eq!(request["params"][0], OWNER);
-            assert_eq!(request["params"][1]["before"], *expected_before);
-            assert_eq!(request["params"][1]["limit"], 1000);
-        }
+    async fn create_key(database: &db::Database, max_concurrent_jobs: i32) -> Result<ApiKey> {
+        database
+            .create_api_key(
+                "batch-submitter",
+                "batch-submitter-hash",
+                ApiKeyLimits {
+                    max_concurrent_jobs,
+                    max_tx_limit: 1000,
+                    max_requested_hours: 24,
+                    max_priority: 0,
+                    requests_per_minute: 60,
+                    allow_full_history: false,
+                },
+            )
+            .await?;
 
-        Ok(())
+        Ok(database
+            .find_active_api_key("batch-submitter-hash")
+            .await?
+            .expect("batch key should be active"))
     }
 
-    async fn assert_transaction_requests(
-        mock_server: &MockServer,
-        expected_signatures: &[&str],
-    ) -> Result<()> {
-        let requests = mock_server.received_requests().await.unwrap();
-        let mut actual = requests
-            .iter()
-            .filter_map(|request| -> Option<Result<String>> {
-                let body: Value = request.body_json().ok()?;
-                if body["method"] != "getTransaction" {
-                    return None;
-                }
-                Some(Ok(body["params"][0]
-                    .as_str()
-                    .expect("signature should be a string")
-                    .to_string()))
+    fn new_jobs(count: usize) -> Vec<NewJob> {
+        (0..count)
+            .map(|index| NewJob {
+                address: format!("batch-address-{index}"),
+                tx_limit: Some(1000),
+                window: JobWindow::Recent { hours: 24 },
+                callback_url: None,
+                priority: 0,
             })
-            .collect::<Result<Vec<_>>>()?;
-        actual.sort();
+            .collect()
+    }
 
-        let mut expected = expected_signatures
-            .iter()
-            .map(|signature| (*signature).to_string())
-            .collect::<Vec<_>>();
-        expected.sort();
+    async fn set_status(pool: &PgPool, job_id: i64, status: &str) -> Result<()> {
+        sqlx::query("UPDATE processing_data SET status = $2 WHERE id = $1")
+            .bind(job_id)
+            .bind(status)
+            .execute(pool)
+            .await?;
 
-        assert_eq!(actual, expected);
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_mark_job_as_ready_and_save_transfers_when_happy_path(
+    async fn should_create_member_jobs_and_roll_up_status_when_batch_progresses(
         pool: PgPool,
     ) -> Result<()> {
-        let harness = WorkflowHarness::new(pool).await?;
-        let now = Utc::now().timestamp() - 60;
-        let job_id = harness.create_job(1000, 24).await?;
+        let database = db::Database::from_pool(pool.clone());
+        let api_key = create_key(&database, 10).await?;
 
-        mount_signature_response(
-            &harness.mock_server,
-            signature_response(&[(SUCCESS_SIGNATURE, now)]),
-            1,
-        )
-        .await;
-        mount_transaction_response(
-            &harness.mock_server,
-            SUCCESS_SIGNATURE,
-            transaction_fixture(SUCCESS_SIGNATURE)?,
-            1,
+        let batch = database.create_job_batch(&api_key, &new_jobs(3)).await?;
+        let job_ids = batch.jobs.iter().map(|job| job.job_id).collect::<Vec<_>>();
+        assert_eq!(
+            batch
+                .jobs
+                .iter()
+                .map(|job| job.address.as_str())
+                .collect::<Vec<_>>(),
+            vec!["batch-address-0", "batch-address-1", "batch-address-2"]
+        );
+
+        let owners = sqlx::query_as::<_, (Option<i64>, Option<i64>)>(
+            "SELECT DISTINCT api_key_id, batch_id FROM processing_data",
         )
-        .await;
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(owners, vec![(Some(api_key.id), Some(batch.batch_id))]);
 
-        let job_info = harness
-            .process_once()
+        let info = database
+            .get_batch_info(batch.batch_id, None)
             .await?
-            .expect("pending job should be processed");
+            .unwrap();
+        assert_eq!(info.status, "pending");
+        assert_eq!(info.total_jobs, 3);
+        assert_eq!(info.jobs_by_status.pending, 3);
+
+        set_status(&pool, job_ids[0], "ready").await?;
+        set_status(&pool, job_ids[1], "indexing").await?;
+        let info = database
+            .get_batch_info(batch.batch_id, None)
+            .await?
+            .unwrap();
+        assert_eq!(info.status, "indexing");
+        assert_eq!(info.jobs_by_status.ready, 1);
+        assert_eq!(info.jobs_by_status.indexing, 1);
+
+        set_status(&pool, job_ids[1], "error").await?;
+        set_status(&pool, job_ids[2], "ready").await?;
+        let info = database
+            .get_batch_info(batch.batch_id, None)
+            .await?
+            .unwrap();
+        assert_eq!(info.status, "partial");
 
-        assert_eq!(job_info.status, "ready");
-        assert_eq!(job_info.total_transactions, 1);
-        assert_eq!(job_info.processed_transactions, 1);
-        assert_eq!(job_info.remaining_transactions, 0);
-        assert_eq!(harness.job_status(job_id).await?, "ready");
-        harness
-            .assert_processed_signature(SUCCESS_SIGNATURE)
-            .await?;
-        assert_eq!(harness.count_transactions().await?, 1);
-        assert!(harness.count_token_transfers().await? > 0);
-        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
-        assert_transaction_requests(&harness.mock_server, &[SUCCESS_SIGNATURE]).await?;
+        set_status(&pool, job_ids[1], "ready").await?;
+        let info = database
+            .get_batch_info(batch.batch_id, None)
+            .await?
+            .unwrap();
+        assert_eq!(info.status, "ready");
+        assert_eq!(
+            info.jobs.iter().map(|job| job.job_id).collect::<Vec<_>>(),
+            job_ids
+        );
+
+        assert!(database.get_batch_info(i64::MAX, None).await?.is_none());
 
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_mark_job_as_ready_with_empty_results_when_address_history_is_empty(
+    async fn should_insert_only_remaining_capacity_when_batch_exceeds_key_limit(
         pool: PgPool,
     ) -> Result<()> {
-        let harness = WorkflowHarness::new(pool).await?;
-        let job_id = harness.create_job(1000, 24).await?;
+        let database = db::Database::from_pool(pool.clone());
+        let api_key = create_key(&database, 3).await?;
+        database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &NewJob {
+                    address: String::from("already-active"),
+                    ..new_jobs(1).remove(0)
+                },
+                &JobDedup::default(),
+            )
+            .await?;
 
-        mount_signature_response(
-            &harness.mock_server,
-            json!({ "jsonrpc": "2.0", "id": "1", "result": [] }),
-            1,
-        )
-        .await;
+        let batch = database.create_job_batch(&api_key, &new_jobs(5)).await?;
+        assert_eq!(
+            batch
+                .jobs
+                .iter()
+                .map(|job| job.address.as_str())
+                .collect::<Vec<_>>(),
+            vec!["batch-address-0", "batch-address-1"]
+        );
 
-        let job_info = harness
-            .process_once()
-            .await?
-            .expect("pending job should be processed");
+        let err = database
+            .create_job_batch(&api_key, &new_jobs(1))
+            .await
+            .unwrap_err();
+        assert!(err.is::<ConcurrentJobLimitExceeded>());
 
-        assert_eq!(job_info.status, "ready");
-        assert_eq!(job_info.total_transactions, 0);
-        assert_eq!(job_info.processed_transactions, 0);
-        assert_eq!(job_info.remaining_transactions, 0);
-        assert_eq!(harness.job_status(job_id).await?, "ready");
-        harness.assert_no_persisted_data().await?;
-        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
-        assert_transaction_requests(&harness.mock_server, &[]).await?;
+        let batches = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM job_batches")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(batches, 1);
 
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_mark_job_as_error_when_get_signatures_returns_fatal_rpc_error(
+    async fn should_create_new_member_job_even_when_a_covering_job_exists(
         pool: PgPool,
     ) -> Result<()> {
-        let harness = WorkflowHarness::new(pool).await?;
-        let job_id = harness.create_job(1000, 24).await?;
+        let database = db::Database::from_pool(pool.clone());
+        let api_key = create_key(&database, 10).await?;
+        let existing = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &new_jobs(1).remove(0),
+                &JobDedup {
+                    reuse_ready_within: Some(std::time::Duration::from_mins(15)),
+                    ..JobDedup::default()
+                },
+            )
+            .await?;
 
-        mount_signature_response(
-            &harness.mock_server,
-            json!({
-                "jsonrpc": "2.0",
-                "id": "1",
-                "error": {
-                    "code": -32602,
-                    "message": "Invalid params",
-                }
-            }),
-            1,
-        )
-        .await;
+        let batch = database.create_job_batch(&api_key, &new_jobs(1)).await?;
+        assert_eq!(batch.jobs.len(), 1);
+        assert_ne!(batch.jobs[0].job_id, existing.job_id);
 
-        let job_info = harness
-            .process_once()
+        let info = database
+            .get_batch_info(batch.batch_id, None)
             .await?
-            .expect("pending job should be processed");
+            .unwrap();
+        assert_eq!(info.total_jobs, 1);
+        assert_eq!(info.jobs[0].job_id, batch.jobs[0].job_id);
 
-        assert_eq!(job_info.status, "error");
-        assert_eq!(harness.job_status(job_id).await?, "error");
-        harness.assert_no_persisted_data().await?;
-        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
-        assert_transaction_requests(&harness.mock_server, &[]).await?;
+        Ok(())
+    }
+}
+
+mod deduplication {
+    #![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
+    use anyhow::{Ok, Result};
+    use on_chain_event_indexer::db::{self, jobs::IdempotencyKeyMismatch};
+    use on_chain_event_indexer::types::{
+        ApiKey, ApiKeyLimits, JobDedup, JobSubmission, JobWindow, NewJob,
+    };
+    use pretty_assertions::assert_eq;
+    use sqlx::postgres::PgPool;
+    use std::time::Duration;
+
+    const ADDRESS: &str = "dedup-address";
+
+    async fn create_key(database: &db::Database) -> Result<ApiKey> {
+        create_named_key(database, "dedup-submitter").await
+    }
+
+    async fn create_named_key(database: &db::Database, name: &str) -> Result<ApiKey> {
+        let key_hash = format!("{name}-hash");
+        database
+            .create_api_key(
+                name,
+                &key_hash,
+                ApiKeyLimits {
+                    max_concurrent_jobs: 10,
+                    max_tx_limit: 1000,
+                    max_requested_hours: 48,
+                    max_priority: 0,
+                    requests_per_minute: 60,
+                    allow_full_history: false,
+                },
+            )
+            .await?;
+
+        Ok(database
+            .find_active_api_key(&key_hash)
+            .await?
+            .expect("dedup key should be active"))
+    }
+
+    fn new_job(requested_hours: i32, tx_limit: i32) -> NewJob {
+        NewJob {
+            address: ADDRESS.to_string(),
+            tx_limit: Some(tx_limit),
+            window: JobWindow::Recent {
+                hours: requested_hours,
+            },
+            callback_url: None,
+            priority: 0,
+        }
+    }
+
+    fn reuse_within(ready_ttl: Duration) -> JobDedup<'static> {
+        JobDedup {
+            reuse_ready_within: Some(ready_ttl),
+            ..JobDedup::default()
+        }
+    }
+
+    async fn count_jobs(pool: &PgPool) -> Result<i64> {
+        Ok(
+            sqlx::query_scalar("SELECT COUNT(*) FROM processing_data WHERE address = $1")
+                .bind(ADDRESS)
+                .fetch_one(pool)
+                .await?,
+        )
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_reuse_unfinished_job_when_it_covers_the_request(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let api_key = create_key(&database).await?;
+        let dedup = reuse_within(Duration::from_mins(15));
+
+        let first = database
+            .create_processing_job_for_api_key(&api_key, &new_job(24, 500), &dedup)
+            .await?;
+        assert!(!first.reused);
+
+        for covered in [new_job(24, 500), new_job(12, 100)] {
+            assert_eq!(
+                database
+                    .create_processing_job_for_api_key(&api_key, &covered, &dedup)
+                    .await?,
+                JobSubmission {
+                    job_id: first.job_id,
+                    reused: true,
+                }
+            );
+        }
+
+        let wider_window = database
+            .create_processing_job_for_api_key(&api_key, &new_job(48, 500), &dedup)
+            .await?;
+        assert!(!wider_window.reused);
+
+        let larger_limit = database
+            .create_processing_job_for_api_key(&api_key, &new_job(24, 1000), &dedup)
+            .await?;
+        assert!(!larger_limit.reused);
+
+        let disabled = database
+            .create_processing_job_for_api_key(&api_key, &new_job(12, 100), &JobDedup::default())
+            .await?;
+        assert!(!disabled.reused);
+        assert_eq!(count_jobs(&pool).await?, 4);
 
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_mark_job_as_error_when_ready_update_is_blocked_by_unprocessed_signatures(
-        pool: PgPool,
-    ) -> Result<()> {
-        let harness = WorkflowHarness::new(pool).await?;
-        let now = Utc::now().timestamp() - 60;
-        let other_owner = "workflow-owner-other";
-        let other_job_id = harness
-            .create_job_for_address(other_owner, 1000, 24)
+    async fn should_not_reuse_job_submitted_by_another_key(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let first_key = create_named_key(&database, "first-submitter").await?;
+        let second_key = create_named_key(&database, "second-submitter").await?;
+        let dedup = reuse_within(Duration::from_mins(15));
+
+        let first = database
+            .create_processing_job_for_api_key(&first_key, &new_job(24, 500), &dedup)
+            .await?;
+        let second = database
+            .create_processing_job_for_api_key(&second_key, &new_job(24, 500), &dedup)
             .await?;
-        let job_id = harness.create_job(1000, 24).await?;
 
-        mount_signature_response(
-            &harness.mock_server,
-            signature_response(&[(SUCCESS_SIGNATURE, now), (FAILED_SIGNATURE, now)]),
-            1,
-        )
-        .await;
-        mount_transaction_response(
-            &harness.mock_server,
-            SUCCESS_SIGNATURE,
-            transaction_fixture(SUCCESS_SIGNATURE)?,
-            1,
-        )
-        .await;
+        assert!(!second.reused);
+        assert_ne!(second.job_id, first.job_id);
+        assert!(
+            database
+                .get_job_info(second.job_id, Some(second_key.id))
+                .await?
+                .is_some()
+        );
+        assert_eq!(count_jobs(&pool).await?, 2);
 
-        let other_claim = harness
-            .database
-            .claim_pending_job(WORKER_ID + 1)
-            .await?
-            .expect("older job should be claimed first");
-        assert_eq!(other_claim.job_id, other_job_id);
+        Ok(())
+    }
 
-        harness
-            .database
-            .write_signatures(
-                &serde_json::from_value(json!({
-                    "result": [
-                        {
-                            "signature": FAILED_SIGNATURE,
-                            "blockTime": now,
-                        }
-                    ]
-                }))?,
-                OWNER,
+    #[sqlx::test(migrations = "./migrations")]
+    async fn should_reuse_ranged_job_only_for_ranges_inside_it(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let api_key = create_key(&database).await?;
+        let dedup = reuse_within(Duration::from_mins(15));
+        let ranged = |from: &str, to: &str| -> Result<NewJob> {
+            Ok(NewJob {
+                window: JobWindow::Range {
+                    from: from.parse()?,
+                    to: to.parse()?,
+                },
+                ..new_job(24, 500)
+            })
+        };
+
+        let first = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &NewJob {
+                    tx_limit: None,
+                    ..ranged("2025-03-01T00:00:00Z", "2025-03-03T00:00:00Z")?
+                },
+                &dedup,
+            )
+            .await?;
+        let inside = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &ranged("2025-03-01T12:00:00Z", "2025-03-02T00:00:00Z")?,
+                &dedup,
             )
             .await?;
-
-        let job_info = harness
-            .process_once()
-            .await?
-            .expect("pending job should be processed");
-
-        assert_eq!(job_info.status, "error");
-        assert_eq!(harness.job_status(job_id).await?, "error");
-        assert_eq!(harness.job_status(other_job_id).await?, "indexing");
         assert_eq!(
-            harness.signature_rows_for_address(OWNER).await?,
-            vec![
-                (SUCCESS_SIGNATURE.to_string(), true, false),
-                (FAILED_SIGNATURE.to_string(), false, true),
-            ]
+            inside,
+            JobSubmission {
+                job_id: first.job_id,
+                reused: true,
+            }
         );
-        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
-        assert_transaction_requests(&harness.mock_server, &[SUCCESS_SIGNATURE, FAILED_SIGNATURE])
+
+        let overlapping = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &ranged("2025-03-02T00:00:00Z", "2025-03-04T00:00:00Z")?,
+                &dedup,
+            )
+            .await?;
+        assert!(!overlapping.reused);
+
+        let recent = database
+            .create_processing_job_for_api_key(&api_key, &new_job(24, 500), &dedup)
             .await?;
+        assert!(!recent.reused);
+        assert_eq!(count_jobs(&pool).await?, 3);
 
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_save_successful_signatures_and_not_mark_fully_ready_when_partial_transaction_failure(
+    async fn should_reuse_capped_ranged_job_only_when_it_ends_where_the_request_ends(
         pool: PgPool,
     ) -> Result<()> {
-        let harness = WorkflowHarness::new(pool).await?;
-        let now = Utc::now().timestamp() - 60;
-        let job_id = harness.create_job(1000, 24).await?;
-        let successful_2 = "successful-signature-2";
+        let database = db::Database::from_pool(pool.clone());
+        let api_key = create_key(&database).await?;
+        let dedup = reuse_within(Duration::from_mins(15));
+        let ranged = |from: &str, to: &str| -> Result<NewJob> {
+            Ok(NewJob {
+                window: JobWindow::Range {
+                    from: from.parse()?,
+                    to: to.parse()?,
+                },
+                ..new_job(24, 10)
+            })
+        };
 
-        mount_signature_response(
-            &harness.mock_server,
-            signature_response(&[
-                (SUCCESS_SIGNATURE, now),
-                (FAILED_SIGNATURE, now),
-                (successful_2, now),
-            ]),
-            1,
-        )
-        .await;
-        mount_transaction_response(
-            &harness.mock_server,
-            SUCCESS_SIGNATURE,
-            transaction_fixture(SUCCESS_SIGNATURE)?,
-            1,
-        )
-        .await;
-        mount_transaction_error(&harness.mock_server, FAILED_SIGNATURE).await;
-        mount_transaction_response(
-            &harness.mock_server,
-            successful_2,
-            transaction_fixture(successful_2)?,
-            1,
-        )
-        .await;
+        let first = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &ranged("2025-03-01T00:00:00Z", "2025-03-03T00:00:00Z")?,
+                &dedup,
+            )
+            .await?;
 
-        let job_info = harness
-            .process_once()
-            .await?
-            .expect("pending job should be processed");
+        let ends_earlier = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &ranged("2025-03-01T00:00:00Z", "2025-03-02T00:00:00Z")?,
+                &dedup,
+            )
+            .await?;
+        assert!(!ends_earlier.reused);
 
-        assert_eq!(job_info.status, "error");
-        assert_eq!(job_info.total_transactions, 3);
-        assert_eq!(job_info.processed_transactions, 2);
-        assert_eq!(job_info.remaining_transactions, 1);
-        assert_eq!(harness.job_status(job_id).await?, "error");
+        let same_end = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &ranged("2025-03-02T00:00:00Z", "2025-03-03T00:00:00Z")?,
+                &dedup,
+            )
+            .await?;
         assert_eq!(
-            harness.signature_rows().await?,
-            vec![
-                (SUCCESS_SIGNATURE.to_string(), true, false),
-                (FAILED_SIGNATURE.to_string(), false, true),
-                (successful_2.to_string(), true, false),
-            ]
+            same_end,
+            JobSubmission {
+                job_id: first.job_id,
+                reused: true,
+            }
         );
-        assert_eq!(harness.count_transactions().await?, 2);
-        assert!(harness.count_token_transfers().await? > 0);
-        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
-        assert_transaction_requests(
-            &harness.mock_server,
-            &[SUCCESS_SIGNATURE, FAILED_SIGNATURE, successful_2],
-        )
-        .await?;
+        assert_eq!(count_jobs(&pool).await?, 2);
 
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_fetch_all_pages_with_correct_before_when_pagination_required(
-        pool: PgPool,
-    ) -> Result<()> {
-        let harness = WorkflowHarness::new(pool).await?;
-        let now = Utc::now().timestamp() - 60;
-        let job_id = harness.create_job(2000, 24).await?;
-        let first_page = signature_page("first-page", 1000, now);
-        let second_page = vec![(SUCCESS_SIGNATURE.to_string(), now)];
-        let mut all_signatures = first_page.clone();
-        all_signatures.extend(second_page.clone());
+    async fn should_reuse_ready_job_only_within_freshness_ttl(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let api_key = create_key(&database).await?;
 
-        mount_signature_response(
-            &harness.mock_server,
-            signature_response(&signature_refs(&first_page)),
-            1,
-        )
-        .await;
-        mount_signature_response(
-            &harness.mock_server,
-            signature_response(&signature_refs(&second_page)),
-            1,
+        let ready = database
+            .create_processing_job_for_api_key(&api_key, &new_job(24, 500), &JobDedup::default())
+            .await?;
+        sqlx::query(
+            "
+            UPDATE processing_data
+            SET status = 'ready', updated_at = NOW() - INTERVAL '10 minutes'
+            WHERE id = $1
+            ",
         )
-        .await;
+        .bind(ready.job_id)
+        .execute(&pool)
+        .await?;
 
-        for (signature, _) in &all_signatures {
-            mount_transaction_response(
-                &harness.mock_server,
-                signature,
-                transaction_fixture(signature)?,
-                1,
+        let fresh = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &new_job(24, 500),
+                &reuse_within(Duration::from_mins(15)),
             )
-            .await;
-        }
+            .await?;
+        assert_eq!(
+            fresh,
+            JobSubmission {
+                job_id: ready.job_id,
+                reused: true,
+            }
+        );
 
-        let job_info = harness
-            .process_once()
-            .await?
-            .expect("pending job should be processed");
+        sqlx::query("UPDATE processing_data SET status = 'failed' WHERE id = $1")
+            .bind(ready.job_id)
+            .execute(&pool)
+            .await?;
+        let after_failure = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &new_job(24, 500),
+                &reuse_within(Duration::from_mins(15)),
+            )
+            .await?;
+        assert!(!after_failure.reused);
 
-        assert_eq!(job_info.status, "ready");
-        assert_eq!(job_info.total_transactions, 1001);
-        assert_eq!(job_info.processed_transactions, 1001);
-        assert_eq!(job_info.remaining_transactions, 0);
-        assert_eq!(harness.job_status(job_id).await?, "ready");
-        assert_eq!(harness.count_transactions().await?, 1001);
-        assert_signature_requests(
-            &harness.mock_server,
-            &[Value::Null, json!("first-page-0999")],
+        sqlx::query(
+            "
+            UPDATE processing_data
+            SET status = 'ready', updated_at = NOW() - INTERVAL '10 minutes'
+            WHERE id = $1
+            ",
         )
+        .bind(after_failure.job_id)
+        .execute(&pool)
         .await?;
+        let stale = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &new_job(24, 500),
+                &reuse_within(Duration::from_mins(5)),
+            )
+            .await?;
+        assert!(!stale.reused);
+        assert_eq!(count_jobs(&pool).await?, 3);
 
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_stop_pagination_when_cutoff_signature_encountered_in_page(
+    async fn should_replay_idempotency_key_and_reject_a_different_request(
         pool: PgPool,
     ) -> Result<()> {
-        let harness = WorkflowHarness::new(pool).await?;
-        let now = Utc::now().timestamp() - 60;
-        let old = Utc::now().timestamp() - 7_200;
-        let job_id = harness.create_job(1000, 1).await?;
-
-        mount_signature_response(
-            &harness.mock_server,
-            signature_response(&[(SUCCESS_SIGNATURE, now), ("old-signature", old)]),
-            1,
-        )
-        .await;
-        mount_transaction_response(
-            &harness.mock_server,
-            SUCCESS_SIGNATURE,
-            transaction_fixture(SUCCESS_SIGNATURE)?,
-            1,
-        )
-        .await;
+        let database = db::Database::from_pool(pool.clone());
+        let api_key = create_key(&database).await?;
+        let dedup = JobDedup {
+            idempotency_key: Some("order-42"),
+            idempotency_ttl: Duration::from_hours(24),
+            reuse_ready_within: None,
+        };
+
+        let first = database
+            .create_processing_job_for_api_key(&api_key, &new_job(24, 500), &dedup)
+            .await?;
+        assert!(!first.reused);
 
-        let job_info = harness
-            .process_once()
-            .await?
-            .expect("pending job should be processed");
+        let replay = database
+            .create_processing_job_for_api_key(&api_key, &new_job(24, 500), &dedup)
+            .await?;
+        assert_eq!(
+            replay,
+            JobSubmission {
+                job_id: first.job_id,
+                reused: true,
+            }
+        );
 
-        assert_eq!(job_info.status, "ready");
-        assert_eq!(job_info.total_transactions, 1);
-        assert_eq!(job_info.processed_transactions, 1);
-        assert_eq!(harness.job_status(job_id).await?, "ready");
-        harness
-            .assert_processed_signature(SUCCESS_SIGNATURE)
+        let mismatch = database
+            .create_processing_job_for_api_key(&api_key, &new_job(12, 500), &dedup)
+            .await
+            .expect_err("a different request should not reuse the idempotency key");
+        assert!(mismatch.is::<IdempotencyKeyMismatch>());
+
+        let other_key = database
+            .create_processing_job_for_api_key(
+                &api_key,
+                &new_job(12, 500),
+                &JobDedup {
+                    idempotency_key: Some("order-43"),
+                    ..dedup
+                },
+            )
             .await?;
-        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
-        assert_transaction_requests(&harness.mock_server, &[SUCCESS_SIGNATURE]).await?;
+        assert!(!other_key.reused);
+        assert_eq!(count_jobs(&pool).await?, 2);
 
         Ok(())
     }
 
     #[sqlx::test(migrations = "./migrations")]
-    async fn should_respect_tx_limit_even_if_page_larger_when_processing_signatures(
-        pool: PgPool,
-    ) -> Result<()> {
-        let harness = WorkflowHarness::new(pool).await?;
-        let now = Utc::now().timestamp() - 60;
-        let job_id = harness.create_job(100, 24).await?;
-        let page = signature_page("limit-page", 150, now);
-
-        mount_signature_response(
-            &harness.mock_server,
-            signature_response(&signature_refs(&page)),
-            1,
-        )
-        .await;
-
-        for (signature, _) in &page {
-            mount_transaction_response(
-                &harness.mock_server,
-                signature,
-                transaction_fixture(signature)?,
-                1,
-            )
-            .await;
-        }
+    async fn should_purge_idempotency_keys_older_than_ttl(pool: PgPool) -> Result<()> {
+        let database = db::Database::from_pool(pool.clone());
+        let api_key = create_key(&database).await?;
+        let dedup = JobDedup {
+            idempotency_key: Some("order-42"),
+            idempotency_ttl: Duration::from_hours(24),
+            reuse_ready_within: None,
+        };
+
+        database
+            .create_processing_job_for_api_key(&api_key, &new_job(24, 500), &dedup)
+            .await?;
+        assert_eq!(
+            database
+                .purge_expired_idempotency_keys(Duration::from_hours(24))
+                .await?,
+            0
+        );
 
-        let job_info = harness
-            .process_once()
-            .await?
-            .expect("pending job should be processed");
+        sqlx::query("UPDATE idempotency_keys SET created_at = NOW() - INTERVAL '2 days'")
+            .execute(&pool)
+            .await?;
+        assert_eq!(
+            database
+                .purge_expired_idempotency_keys(Duration::from_hours(24))
+                .await?,
+            1
+        );
 
-        assert_eq!(job_info.status, "ready");
-        assert_eq!(job_info.total_transactions, 150);
-        assert_eq!(job_info.processed_transactions, 150);
-        assert_eq!(job_info.remaining_transactions, 0);
-        assert_eq!(harness.job_status(job_id).await?, "ready");
-        assert_eq!(harness.count_transactions().await?, 150);
-        assert_signature_requests(&harness.mock_server, &[Value::Null]).await?;
+        let reused_key = database
+            .create_processing_job_for_api_key(&api_key, &new_job(12, 500), &dedup)
+            .await?;
+        assert!(!reused_key.reused);
 
         Ok(())
     }
@@ -9,7 +9,8 @@ use common::{
 
 use anyhow::{Ok, Result};
 use chrono::Utc;
-use on_chain_event_indexer::requests::client::SignaturesPage;
+use on_chain_event_indexer::requests::SolanaRpc;
+use on_chain_event_indexer::requests::client::{SignatureRange, SignaturesPage};
 use pretty_assertions::assert_eq;
 use serde_json::{Value, json};
 use wiremock::MockServer;
@@ -132,7 +133,9 @@ async fn should_return_all_signatures_when_all_results_are_within_requested_wind
     mount_post_json_response(&mock_server, build_signatures_result(now_ts)).await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let result = helius_api.get_signatures(ADDRESS, None, 24).await?;
+    let result = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(24))
+        .await?;
 
     assert_signatures_page(
         &result,
@@ -153,7 +156,9 @@ async fn should_filter_only_recent_signatures_and_set_reached_cutoff_true() -> R
     mount_post_json_response(&mock_server, build_signatures_result(now_ts)).await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let result = helius_api.get_signatures(ADDRESS, None, 2).await?;
+    let result = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(2))
+        .await?;
 
     assert_signatures_page(
         &result,
@@ -175,7 +180,12 @@ async fn should_send_cursor_in_before_param_when_provided() -> Result<()> {
 
     let helius_api = create_helius_api(&mock_server)?;
     let result = helius_api
-        .get_signatures(ADDRESS, Some("sig-fresh-2".to_string()), 24)
+        .get_signatures(
+            ADDRESS,
+            Some("sig-fresh-2".to_string()),
+            None,
+            SignatureRange::last_hours(24),
+        )
         .await?;
 
     assert_signatures_page(
@@ -189,6 +199,29 @@ async fn should_send_cursor_in_before_param_when_provided() -> Result<()> {
     assert_all_signatures_rpc_requests(&mock_server, &[json!("sig-fresh-2")]).await
 }
 
+#[tokio::test]
+async fn should_send_until_param_when_provided() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    mount_post_json_response(&mock_server, build_empty_signatures_result()).await;
+
+    let helius_api = create_helius_api(&mock_server)?;
+    helius_api
+        .get_signatures(
+            ADDRESS,
+            None,
+            Some("sig-newest-stored"),
+            SignatureRange::last_hours(24),
+        )
+        .await?;
+
+    let received_requests = mock_server.received_requests().await.unwrap();
+    let request_json: Value = received_requests[0].body_json()?;
+    assert_eq!(request_json["params"][1]["until"], "sig-newest-stored");
+
+    assert_all_signatures_rpc_requests(&mock_server, &[Value::Null]).await
+}
+
 #[tokio::test]
 async fn should_return_empty_result_when_rpc_returns_empty_list() -> Result<()> {
     let mock_server = MockServer::start().await;
@@ -196,7 +229,9 @@ async fn should_return_empty_result_when_rpc_returns_empty_list() -> Result<()>
     mount_post_json_response(&mock_server, build_empty_signatures_result()).await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let result = helius_api.get_signatures(ADDRESS, None, 4).await?;
+    let result = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(4))
+        .await?;
 
     assert_signatures_page(&result, 0, &[], None, false);
 
@@ -215,7 +250,9 @@ async fn should_skip_null_blocktime_and_continue_filtering() -> Result<()> {
     .await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let result = helius_api.get_signatures(ADDRESS, None, 1).await?;
+    let result = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(1))
+        .await?;
 
     assert_signatures_page(
         &result,
@@ -235,7 +272,10 @@ async fn should_return_error_without_retry_on_regular_rpc_error() -> Result<()>
     mount_post_json_response(&mock_server, rpc_error_envelope(-32602, "Invalid params")).await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let Err(error) = helius_api.get_signatures(ADDRESS, None, 4).await else {
+    let Err(error) = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(4))
+        .await
+    else {
         panic!("regular rpc error must fail without retry")
     };
     let error_text = error.to_string();
@@ -252,7 +292,10 @@ async fn should_return_decode_error_on_invalid_json_at_200() -> Result<()> {
     mount_post_raw_response(&mock_server, 200, "{invalid-json", "application/json").await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let Err(error) = helius_api.get_signatures(ADDRESS, None, 4).await else {
+    let Err(error) = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(4))
+        .await
+    else {
         panic!("invalid json must fail decoding")
     };
     let error_text = error.to_string();
@@ -275,7 +318,9 @@ async fn should_retry_after_http_429_and_succeed() -> Result<()> {
     mount_post_json_response(&mock_server, build_signatures_result(now_ts)).await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let result = helius_api.get_signatures(ADDRESS, None, 24).await?;
+    let result = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(24))
+        .await?;
 
     assert_signatures_page(
         &result,
@@ -302,7 +347,9 @@ async fn should_retry_on_rpc_rate_limit_and_succeed() -> Result<()> {
     mount_post_json_response(&mock_server, build_signatures_result(now_ts)).await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let result = helius_api.get_signatures(ADDRESS, None, 24).await?;
+    let result = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(24))
+        .await?;
 
     assert_signatures_page(
         &result,
@@ -323,7 +370,10 @@ async fn should_exhaust_retries_on_429_with_invalid_json() -> Result<()> {
         .await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let Err(error) = helius_api.get_signatures(ADDRESS, None, 4).await else {
+    let Err(error) = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(4))
+        .await
+    else {
         panic!("persistent 429 with invalid json must fail after retry budget is exhausted")
     };
 
@@ -348,7 +398,10 @@ async fn should_fail_with_missing_result_error_on_invalid_envelope() -> Result<(
     .await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let Err(error) = helius_api.get_signatures(ADDRESS, None, 4).await else {
+    let Err(error) = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(4))
+        .await
+    else {
         panic!("envelope without result or error must fail")
     };
 
@@ -372,7 +425,10 @@ async fn should_exhaust_retries_and_fail_on_persistent_rpc_rate_limit() -> Resul
     .await;
 
     let helius_api = create_helius_api(&mock_server)?;
-    let Err(error) = helius_api.get_signatures(ADDRESS, None, 4).await else {
+    let Err(error) = helius_api
+        .get_signatures(ADDRESS, None, None, SignatureRange::last_hours(4))
+        .await
+    else {
         panic!("persistent rpc rate limit must fail after retry budget is exhausted")
     };
 
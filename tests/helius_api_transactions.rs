@@ -8,7 +8,7 @@ use common::{
 };
 
 use anyhow::{Ok, Result};
-use on_chain_event_indexer::requests::HeliusApi;
+use on_chain_event_indexer::requests::{HeliusApi, SolanaRpc};
 use pretty_assertions::assert_eq;
 use serde_json::{Value, json};
 use std::collections::BTreeMap;
@@ -162,7 +162,14 @@ async fn should_calculate_token_transfer_changes_after_successful_fetch() -> Res
     assert_eq!(native_transfer.decimals, Some(9));
     assert_eq!(native_transfer.transfer_type, "transfer");
     assert_eq!(native_transfer.asset_type, "native");
-    assert_eq!(native_transfer.direction, "unknown");
+    assert_eq!(
+        native_transfer.direction_for("8TPACXaKotSZ7WXktfmKDRhgoypyGXNzo1ctr2YBzxLc"),
+        "out"
+    );
+    assert_eq!(
+        native_transfer.direction_for("2naDnfYtHQAiUfxcMFsygUXCDCbiqiY79eCwmB7ExTAM"),
+        "in"
+    );
     assert_eq!(native_transfer.instruction_idx, Some(4));
     assert_eq!(native_transfer.inner_idx, None);
 
@@ -190,7 +197,14 @@ async fn should_calculate_token_transfer_changes_after_successful_fetch() -> Res
     assert_eq!(mint_transfer.decimals, Some(9));
     assert_eq!(mint_transfer.transfer_type, "mint");
     assert_eq!(mint_transfer.asset_type, "spl");
-    assert_eq!(mint_transfer.direction, "unknown");
+    assert_eq!(
+        mint_transfer.direction_for("AFCp9ZKwdvg1f8b4BjGiXfzQA7FsLaDSez4pCdFS1vAA"),
+        "in"
+    );
+    assert_eq!(
+        mint_transfer.direction_for("8TPACXaKotSZ7WXktfmKDRhgoypyGXNzo1ctr2YBzxLc"),
+        "external"
+    );
     assert_eq!(mint_transfer.instruction_idx, Some(6));
     assert_eq!(mint_transfer.inner_idx, Some(4));
 
@@ -218,7 +232,10 @@ async fn should_calculate_token_transfer_changes_after_successful_fetch() -> Res
     assert_eq!(burn_transfer.decimals, Some(6));
     assert_eq!(burn_transfer.transfer_type, "burn");
     assert_eq!(burn_transfer.asset_type, "spl");
-    assert_eq!(burn_transfer.direction, "unknown");
+    assert_eq!(
+        burn_transfer.direction_for("AFCp9ZKwdvg1f8b4BjGiXfzQA7FsLaDSez4pCdFS1vAA"),
+        "out"
+    );
     assert_eq!(burn_transfer.instruction_idx, Some(6));
     assert_eq!(burn_transfer.inner_idx, Some(7));
 
@@ -545,7 +562,7 @@ async fn should_process_signatures_across_chunks_of_ten() -> Result<()> {
         mount_transaction_json_response(&mock_server, signature, success.clone()).await;
     }
 
-    let helius_api = HeliusApi::new(100, 20, 4, mock_server.uri())?;
+    let helius_api = HeliusApi::new(100, 20, 4, &mock_server.uri(), "")?;
     let result = helius_api.get_transaction(&signatures).await?;
 
     assert!(result.errors.is_empty());